@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use toydump::{decode_aarch64_instruction, disassemble_aarch64};
+
+fuzz_target!(|data: &[u8]| {
+    if let Some(word) = data.first_chunk::<4>() {
+        let _ = decode_aarch64_instruction(u32::from_le_bytes(*word));
+    }
+
+    let _ = disassemble_aarch64(data);
+});
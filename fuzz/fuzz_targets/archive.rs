@@ -0,0 +1,33 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use toydump::ElfHeader;
+use toydump::archive::Archive;
+use toydump::output::MemorySink;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(archive) = Archive::new(data) else {
+        return;
+    };
+
+    let mut sink = MemorySink::new(false);
+    archive.dump_members(&mut sink);
+    archive.dump_index("fuzz.a", &mut sink);
+
+    for symbol in archive.symbols() {
+        let _ = archive.member_at(symbol.member_offset);
+    }
+
+    for member in archive.members() {
+        let _ = archive.member_by_name(&member.name);
+
+        if let Ok(elf) = ElfHeader::new(archive.member_data(member)) {
+            elf.dump_symbols(true, &mut sink);
+            let _ = elf.dump_disassembly(true, true, &mut sink);
+        }
+    }
+
+    for diagnostic in archive.diagnostics() {
+        let _ = diagnostic.to_string();
+    }
+});
@@ -0,0 +1,63 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use toydump::disassemble::{DW_TAG_CLASS_TYPE, DW_TAG_STRUCTURE_TYPE, DW_TAG_UNION_TYPE};
+use toydump::output::MemorySink;
+use toydump::{ElfHeader, Session};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(elf) = ElfHeader::new(data) else {
+        return;
+    };
+
+    let session = Session::new(data.to_vec(), 16, 2, false);
+    let mut sink = MemorySink::new(false);
+
+    elf.dump_header(&mut sink);
+    elf.dump_sections(&mut sink);
+    elf.dump_segments(&mut sink);
+    elf.dump_symbols(true, &mut sink);
+    elf.dump_dynamic(&mut sink);
+    elf.dump_relocations(true, &mut sink);
+    elf.dump_notes(&mut sink);
+    elf.dump_checksec(&mut sink);
+    elf.dump_debug_info(&mut sink);
+    elf.dump_versions(&mut sink);
+    elf.dump_histogram(&mut sink);
+    elf.dump_core(&mut sink);
+    let _ = elf.dump_disassembly(true, true, &mut sink);
+
+    let names = elf
+        .sections()
+        .iter()
+        .map(|section| section.name.clone())
+        .collect::<Vec<_>>();
+
+    for name in &names {
+        let _ = session.dump_section(&elf, name, &mut sink);
+    }
+
+    let _ = elf.dump_section_disassembly(&names, true, true, true, &mut sink);
+
+    for symbol in elf.symbols().iter().filter(|symbol| symbol.is_function()) {
+        let _ = elf.dump_function_disassembly(&symbol.name, true, true, &mut sink);
+    }
+
+    for unit in elf.compile_units() {
+        for entry in &unit.entries {
+            if let (DW_TAG_STRUCTURE_TYPE | DW_TAG_CLASS_TYPE | DW_TAG_UNION_TYPE, Some(name)) =
+                (entry.tag, entry.name())
+            {
+                let _ = elf.dump_struct_layout(name, &mut sink);
+            }
+        }
+    }
+
+    let entry = elf.file_header().entry;
+    let _ = elf.file_range_at_vaddr(entry);
+    let _ = elf.dump_disassembly_at(entry, true, true, true, &mut sink);
+
+    for diagnostic in elf.diagnostics() {
+        let _ = diagnostic.to_string();
+    }
+});
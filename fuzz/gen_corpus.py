@@ -0,0 +1,249 @@
+#!/usr/bin/env python3
+# Regenerates the hand-built ELF seeds in corpus/elf. Each file is a minimal
+# aarch64 image (ELF header, .text, .shstrtab and the section header table),
+# optionally broken in one specific way so the fuzzer starts from the edge cases.
+import os
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+OUT = os.path.join(HERE, "corpus", "elf")
+
+TEXT = [0x91001020, 0x51000462, 0xF9400441, 0x90000000, 0xD65F03C0]
+
+
+def build(endian="<", elf_class=2, text_offset=None, shnum=None, shstrndx=2, machine=0xB7, os_abi=0):
+    # aarch64 code is little endian even in aarch64_be files, only the headers follow EI_DATA
+    text = b"".join(struct.pack("<I", word) for word in TEXT)
+    shstrtab = b"\0.text\0.shstrtab\0"
+
+    ident = b"\x7fELF" + bytes([elf_class, 1 if endian == "<" else 2, 1, os_abi]) + bytes(8)
+    shnum = 3 if shnum is None else shnum
+
+    if elf_class == 1:
+        header_size, section_size = 0x34, 0x28
+        layout, section_layout = "HHIIIIIHHHHHH", "IIIIIIIIII"
+    else:
+        header_size, section_size = 0x40, 0x40
+        layout, section_layout = "HHIQQQIHHHHHH", "IIQQQQIIQQ"
+
+    text_start = header_size
+    shstrtab_start = text_start + len(text)
+    shoff = (shstrtab_start + len(shstrtab) + 7) & ~7
+
+    # e_type ET_REL, e_version 1, no entry point and no program headers
+    header = ident + struct.pack(
+        endian + layout, 1, machine, 1, 0, 0, shoff, 0, header_size, 0, 0, section_size, shnum, shstrndx
+    )
+
+    def section(name, kind, flags, offset, size):
+        return struct.pack(endian + section_layout, name, kind, flags, 0, offset, size, 0, 0, 4, 0)
+
+    sections = (
+        section(0, 0, 0, 0, 0)
+        + section(1, 1, 0x6, text_start if text_offset is None else text_offset, len(text))
+        + section(7, 3, 0, shstrtab_start, len(shstrtab))
+    )
+
+    body = header + text + shstrtab
+    return body + bytes(shoff - len(body)) + sections
+
+
+def build_dynamic(elf_class=2):
+    # an ET_DYN image with just .dynstr, .dynamic and .shstrtab, enough to exercise the dynamic
+    # section parser without any code or program headers
+    dynstr = b"\0libc.so.6\0libm.so.6\0libdemo.so.1\0$ORIGIN/../lib\0"
+    shstrtab = b"\0.dynstr\0.dynamic\0.shstrtab\0"
+    # DT_NEEDED x2, DT_SONAME, DT_RUNPATH, DT_FLAGS (BIND_NOW), DT_FLAGS_1 (NOW | PIE), DT_NULL
+    entries = [(1, 1), (1, 11), (14, 21), (29, 34), (30, 0x8), (0x6FFFFFFB, 0x08000001), (0, 0)]
+
+    ident = b"\x7fELF" + bytes([elf_class, 1, 1, 0]) + bytes(8)
+
+    if elf_class == 1:
+        header_size, section_size = 0x34, 0x28
+        layout, section_layout, entry_layout = "HHIIIIIHHHHHH", "IIIIIIIIII", "<II"
+    else:
+        header_size, section_size = 0x40, 0x40
+        layout, section_layout, entry_layout = "HHIQQQIHHHHHH", "IIQQQQIIQQ", "<QQ"
+
+    dynamic = b"".join(struct.pack(entry_layout, tag, value) for tag, value in entries)
+
+    dynstr_start = header_size
+    dynamic_start = (dynstr_start + len(dynstr) + 7) & ~7
+    shstrtab_start = dynamic_start + len(dynamic)
+    shoff = (shstrtab_start + len(shstrtab) + 7) & ~7
+
+    header = ident + struct.pack(
+        "<" + layout, 3, 0xB7, 1, 0, 0, shoff, 0, header_size, 0, 0, section_size, 4, 3
+    )
+
+    def section(name, kind, flags, offset, size, link=0, entsize=0):
+        return struct.pack("<" + section_layout, name, kind, flags, 0, offset, size, link, 0, 8, entsize)
+
+    sections = (
+        section(0, 0, 0, 0, 0)
+        + section(1, 3, 0x2, dynstr_start, len(dynstr))
+        + section(9, 6, 0x3, dynamic_start, len(dynamic), link=1, entsize=len(dynamic) // len(entries))
+        + section(18, 3, 0, shstrtab_start, len(shstrtab))
+    )
+
+    body = header + dynstr
+    body += bytes(dynamic_start - len(body)) + dynamic + shstrtab
+    return body + bytes(shoff - len(body)) + sections
+
+
+def build_executable():
+    # an ET_EXEC image whose single PT_LOAD maps the whole file at 0x400000, with .text followed
+    # by a .rodata string, for the virtual address translation
+    base = 0x400000
+    text = b"".join(struct.pack("<I", word) for word in TEXT)
+    rodata = b"needle\0"
+    shstrtab = b"\0.text\0.rodata\0.shstrtab\0"
+
+    ident = b"\x7fELF" + bytes([2, 1, 1, 0]) + bytes(8)
+    text_start = 0x40 + 0x38
+    rodata_start = text_start + len(text)
+    shstrtab_start = rodata_start + len(rodata)
+    shoff = (shstrtab_start + len(shstrtab) + 7) & ~7
+
+    header = ident + struct.pack(
+        "<HHIQQQIHHHHHH", 2, 0xB7, 1, base + text_start, 0x40, shoff, 0, 0x40, 0x38, 1, 0x40, 4, 3
+    )
+    load = struct.pack("<IIQQQQQQ", 1, 5, 0, base, base, shoff, shoff, 0x1000)
+
+    def section(name, kind, flags, offset, size):
+        address = base + offset if flags & 0x2 else 0
+        return struct.pack("<IIQQQQIIQQ", name, kind, flags, address, offset, size, 0, 0, 1, 0)
+
+    sections = (
+        section(0, 0, 0, 0, 0)
+        + section(1, 1, 0x6, text_start, len(text))
+        + section(7, 1, 0x2, rodata_start, len(rodata))
+        + section(15, 3, 0, shstrtab_start, len(shstrtab))
+    )
+
+    body = header + load + text + rodata + shstrtab
+    return body + bytes(shoff - len(body)) + sections
+
+
+def build_zstd_zeros():
+    # an ET_REL with one SHF_COMPRESSED section holding 1 MiB of zeros as a zstd frame of RLE
+    # blocks, compressed far harder than zlib ever could
+    size = 1 << 20
+    block = 128 << 10
+    frame = b"\x28\xb5\x2f\xfd" + bytes([0x00, 7 << 3])
+
+    for start in range(0, size, block):
+        last = 1 if start + block == size else 0
+        frame += struct.pack("<I", block << 3 | 1 << 1 | last)[:3] + b"\0"
+
+    # Elf64_Chdr: ELFCOMPRESS_ZSTD, the decompressed size and alignment
+    contents = struct.pack("<IIQQ", 2, 0, size, 1) + frame
+    shstrtab = b"\0.debug_zeros\0.shstrtab\0"
+
+    ident = b"\x7fELF" + bytes([2, 1, 1, 0]) + bytes(8)
+    contents_start = 0x40
+    shstrtab_start = contents_start + len(contents)
+    shoff = (shstrtab_start + len(shstrtab) + 7) & ~7
+
+    header = ident + struct.pack(
+        "<HHIQQQIHHHHHH", 1, 0xB7, 1, 0, 0, shoff, 0, 0x40, 0, 0, 0x40, 3, 2
+    )
+
+    def section(name, kind, flags, offset, size, alignment):
+        return struct.pack("<IIQQQQIIQQ", name, kind, flags, 0, offset, size, 0, 0, alignment, 0)
+
+    sections = (
+        section(0, 0, 0, 0, 0, 0)
+        + section(1, 1, 0x800, contents_start, len(contents), 8)
+        + section(14, 3, 0, shstrtab_start, len(shstrtab), 1)
+    )
+
+    body = header + contents + shstrtab
+    return body + bytes(shoff - len(body)) + sections
+
+
+def build_core():
+    # an aarch64 ET_CORE with the notes a Linux core starts with (NT_PRSTATUS, NT_PRPSINFO, NT_AUXV
+    # and NT_FILE), a dumped stack page holding the AT_EXECFN and AT_PLATFORM strings, and a
+    # PT_LOAD for the executable's text that was left out of the dump
+    stack = 0x7FFFF000
+    stack_page = bytearray(0x1000)
+    stack_page[0xF00:0xF0C] = b"/bin/crashy\0"
+    stack_page[0xF10:0xF18] = b"aarch64\0"
+
+    def note(note_type, descriptor):
+        descriptor += bytes(-len(descriptor) % 4)
+        return struct.pack("<III", 5, len(descriptor), note_type) + b"CORE\0\0\0\0" + descriptor
+
+    # siginfo, pr_cursig SIGSEGV, masks, pid/ppid/pgrp/sid, times, then x0-x30, sp, pc, pstate
+    registers = list(range(31)) + [stack + 0xE00, 0x400010, 0x60000000]
+    prstatus = (
+        struct.pack("<iiiHxxQQIIII", 11, 1, 0, 11, 0, 0, 4242, 1, 4242, 4242)
+        + bytes(64)
+        + b"".join(struct.pack("<Q", value) for value in registers)
+        + struct.pack("<ixxxx", 0)
+    )
+    prpsinfo = (
+        struct.pack("<bcbbxxxxQIIIIII", 0, b"R", 0, 0, 0x400600, 1000, 1000, 4242, 1, 4242, 4242)
+        + b"crashy".ljust(16, b"\0")
+        + b"/bin/crashy --now ".ljust(80, b"\0")
+    )
+    # AT_PHDR, AT_PAGESZ, AT_ENTRY, AT_UID, AT_PLATFORM, AT_EXECFN, AT_NULL
+    auxv = b"".join(
+        struct.pack("<QQ", key, value)
+        for key, value in [
+            (3, 0x400040), (6, 0x1000), (9, 0x400000), (11, 1000),
+            (15, stack + 0xF10), (31, stack + 0xF00), (0, 0),
+        ]
+    )
+    mapped = struct.pack("<QQQQQ", 1, 0x1000, 0x400000, 0x401000, 0) + b"/bin/crashy\0"
+
+    notes = note(1, prstatus) + note(3, prpsinfo) + note(6, auxv) + note(0x46494C45, mapped)
+
+    phoff = 0x40
+    notes_start = phoff + 3 * 0x38
+    stack_start = (notes_start + len(notes) + 0xFFF) & ~0xFFF
+
+    ident = b"\x7fELF" + bytes([2, 1, 1, 0]) + bytes(8)
+    header = ident + struct.pack("<HHIQQQIHHHHHH", 4, 0xB7, 1, 0, phoff, 0, 0, 0x40, 0x38, 3, 0x40, 0, 0)
+    segments = (
+        struct.pack("<IIQQQQQQ", 4, 0, notes_start, 0, 0, len(notes), 0, 4)
+        + struct.pack("<IIQQQQQQ", 1, 5, stack_start, 0x400000, 0, 0, 0x1000, 0x1000)
+        + struct.pack("<IIQQQQQQ", 1, 6, stack_start, stack, 0, 0x1000, 0x1000, 0x1000)
+    )
+
+    body = header + segments + notes
+    return body + bytes(stack_start - len(body)) + bytes(stack_page)
+
+
+def write(name, data):
+    with open(os.path.join(OUT, name), "wb") as f:
+        f.write(data)
+
+
+def main():
+    os.makedirs(OUT, exist_ok=True)
+
+    valid = build()
+    write("aarch64_le.elf", valid)
+    write("aarch64_be.elf", build(endian=">"))
+    write("aarch64_elf32.elf", build(elf_class=1))
+    write("invalid_class.elf", build(elf_class=7))
+    write("invalid_data_encoding.elf", valid[:5] + b"\x03" + valid[6:])
+    write("unknown_machine.elf", build(machine=0x3E))
+    write("freebsd_abi.elf", build(os_abi=9))
+    write("header_only.elf", valid[:0x40])
+    write("truncated_section_table.elf", valid[:-0x20])
+    write("text_out_of_bounds.elf", build(text_offset=0xFFFF_FFFF_0000))
+    write("shstrndx_out_of_range.elf", build(shstrndx=9))
+    write("huge_section_count.elf", build(shnum=0xFFFF))
+    write("no_sections.elf", build(shnum=0))
+    write("dynamic.elf", build_dynamic())
+    write("dynamic_elf32.elf", build_dynamic(elf_class=1))
+    write("executable.elf", build_executable())
+    write("aarch64_core.elf", build_core())
+    write("zstd_zeros.elf", build_zstd_zeros())
+
+
+if __name__ == "__main__":
+    main()
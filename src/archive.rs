@@ -0,0 +1,541 @@
+use crate::decode_byte::*;
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+/// The magic at the start of every `ar` archive.
+pub const ARCHIVE_MAGIC: &[u8] = b"!<arch>\n";
+
+const MEMBER_HEADER_SIZE: usize = 60;
+
+/// The two bytes that end every member header.
+const MEMBER_HEADER_END: &[u8] = b"`\n";
+
+const ARCHIVE_MEMBER: &str = "archive member";
+const ARCHIVE_INDEX: &str = "archive index";
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// The two ways archives store long member names and the symbol index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// System V and GNU: names end in `/`, longer ones live in the `//` member and are referred to
+    /// as `/<offset>`, and the index is the `/` (or 64-bit `/SYM64/`) member.
+    Gnu,
+    /// BSD and Darwin: a `#1/<length>` name is followed by the real name at the start of the
+    /// member's data, and the index is the `__.SYMDEF` member.
+    Bsd,
+}
+
+/// One file stored in an archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveMember {
+    pub name: String,
+    /// Offset of the member's header, which is what the symbol index refers to.
+    pub header_offset: u64,
+    /// Offset of the member's contents, past any BSD long name.
+    pub offset: u64,
+    pub size: u64,
+    /// Modification time in seconds since the epoch; 0 in deterministic archives.
+    pub date: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+}
+
+/// A symbol from the archive index, which the linker searches instead of every member.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveSymbol {
+    pub name: String,
+    /// Header offset of the member that defines the symbol.
+    pub member_offset: u64,
+}
+
+/// A Unix `ar` archive, such as a static library.
+pub struct Archive {
+    data: Vec<u8>,
+    format: ArchiveFormat,
+    members: Vec<ArchiveMember>,
+    symbols: Vec<ArchiveSymbol>,
+    /// Size of the string table holding the index's symbol names.
+    symbol_names_size: u64,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Whether `bytes` starts like an `ar` archive.
+pub fn is_archive(bytes: &[u8]) -> bool {
+    bytes.starts_with(ARCHIVE_MAGIC)
+}
+
+/// Reads a space padded numeric header field in `radix`; blank fields, as in the GNU name table's
+/// header, are 0.
+fn numeric_field(field: &[u8], radix: u32) -> Option<u64> {
+    let text = std::str::from_utf8(field).ok()?.trim_end_matches(' ');
+
+    if text.is_empty() {
+        return Some(0);
+    }
+
+    u64::from_str_radix(text, radix).ok()
+}
+
+/// The NUL terminated string at the start of `bytes`.
+fn c_string(bytes: &[u8]) -> String {
+    let length = bytes
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..length]).into_owned()
+}
+
+/// The permission bits the way `ar tv` and `ls -l` show them, e.g. `rw-r--r--`.
+fn mode_string(mode: u32) -> String {
+    "rwxrwxrwx"
+        .chars()
+        .enumerate()
+        .map(|(bit, flag)| {
+            if mode & (0o400 >> bit) != 0 {
+                flag
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// A modification time in UTC the way `ar tv` shows it, e.g. `Jan  1 00:00 1970`.
+fn date_string(seconds: u64) -> String {
+    let days = seconds / 86400;
+    let minutes = seconds % 86400 / 60;
+
+    // days since 0000-03-01 split into 400 year eras, so leap days fall at the end of each year
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = (month + 2) % 12;
+    let year = era * 400 + year_of_era + u64::from(month < 2);
+
+    format!(
+        "{} {:>2} {:02}:{:02} {year}",
+        MONTHS[month as usize],
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+impl Archive {
+    /// Parses the archive at the start of `bytes`. Damage past the magic is recorded in
+    /// [`Archive::diagnostics`], keeping the members read up to it.
+    pub fn new(bytes: &[u8]) -> Result<Archive> {
+        if !is_archive(bytes) {
+            return Err(Error::MalformedArchive {
+                offset: 0,
+                problem: "missing !<arch> magic",
+            });
+        }
+
+        let mut archive = Archive {
+            data: bytes.to_vec(),
+            format: ArchiveFormat::Bsd,
+            members: Vec::new(),
+            symbols: Vec::new(),
+            symbol_names_size: 0,
+            diagnostics: Vec::new(),
+        };
+
+        archive.parse_members();
+
+        Ok(archive)
+    }
+
+    /// Reads the member header at `position`, returning it with the range of the member's data.
+    fn parse_header(&self, position: usize) -> Result<(ArchiveMember, usize, usize)> {
+        let available = self.data.len() as u64;
+        let malformed = |problem| Error::MalformedArchive {
+            offset: position as u64,
+            problem,
+        };
+
+        let header = self
+            .data
+            .get(position..position.saturating_add(MEMBER_HEADER_SIZE))
+            .ok_or(Error::Truncated {
+                what: "archive member header",
+                offset: position as u64,
+                needed: MEMBER_HEADER_SIZE as u64,
+                available,
+            })?;
+
+        if &header[58..60] != MEMBER_HEADER_END {
+            return Err(malformed("member header does not end in `\\n"));
+        }
+
+        let size = numeric_field(&header[48..58], 10)
+            .ok_or_else(|| malformed("member size is not a decimal number"))?;
+
+        let start = position + MEMBER_HEADER_SIZE;
+        let end = usize::try_from(size)
+            .ok()
+            .and_then(|size| start.checked_add(size))
+            .filter(|end| *end <= self.data.len())
+            .ok_or(Error::Truncated {
+                what: "archive member",
+                offset: start as u64,
+                needed: size,
+                available,
+            })?;
+
+        let member = ArchiveMember {
+            name: String::from_utf8_lossy(&header[..16])
+                .trim_end_matches(' ')
+                .to_string(),
+            header_offset: position as u64,
+            offset: start as u64,
+            size,
+            date: numeric_field(&header[16..28], 10).unwrap_or(0),
+            uid: numeric_field(&header[28..34], 10).unwrap_or(0) as u32,
+            gid: numeric_field(&header[34..40], 10).unwrap_or(0) as u32,
+            mode: numeric_field(&header[40..48], 8).unwrap_or(0) as u32,
+        };
+
+        Ok((member, start, end))
+    }
+
+    /// The GNU `/` index: a big endian count, the header offset of each symbol's member, then the
+    /// NUL terminated names. `/SYM64/` is the same with 64-bit words.
+    fn parse_gnu_index(&mut self, contents: &[u8], word_size: usize) -> Result<()> {
+        let word = |index: usize| match word_size {
+            4 => read_value::<u32>(Endianness::BigEndian, contents, index * 4).map(u64::from),
+            _ => read_value::<u64>(Endianness::BigEndian, contents, index * 8),
+        };
+        let truncated = |needed: usize| Error::Truncated {
+            what: "archive index",
+            offset: 0,
+            needed: needed as u64,
+            available: contents.len() as u64,
+        };
+
+        let count = word(0).ok_or(truncated(word_size))? as usize;
+        let names_start = count
+            .checked_add(1)
+            .and_then(|words| words.checked_mul(word_size))
+            .filter(|start| *start <= contents.len())
+            .ok_or(truncated(count.saturating_add(1).saturating_mul(word_size)))?;
+
+        let mut names = contents[names_start..].split(|byte| *byte == 0);
+
+        for index in 0..count {
+            let (Some(member_offset), Some(name)) = (word(index + 1), names.next()) else {
+                return Err(truncated(contents.len() + 1));
+            };
+
+            self.symbols.push(ArchiveSymbol {
+                name: String::from_utf8_lossy(name).into_owned(),
+                member_offset,
+            });
+        }
+
+        self.symbol_names_size = (contents.len() - names_start) as u64;
+        Ok(())
+    }
+
+    /// The BSD `__.SYMDEF` index: the byte size of an array of `ranlib` string index and member
+    /// offset pairs, the array, then the size of the string table and the table. Darwin writes them
+    /// little endian, and `__.SYMDEF_64` with 64-bit words.
+    fn parse_bsd_index(&mut self, contents: &[u8], word_size: usize) -> Result<()> {
+        let word = |offset: usize| match word_size {
+            4 => read_value::<u32>(Endianness::LittleEndian, contents, offset).map(u64::from),
+            _ => read_value::<u64>(Endianness::LittleEndian, contents, offset),
+        };
+        let truncated = |needed: u64| Error::Truncated {
+            what: "archive index",
+            offset: 0,
+            needed,
+            available: contents.len() as u64,
+        };
+
+        let ranlib_size = word(0).ok_or(truncated(word_size as u64))?;
+        let names_size_at = (ranlib_size as usize).saturating_add(word_size);
+        let names_size = word(names_size_at).ok_or(truncated(names_size_at as u64))?;
+        let names = &contents[names_size_at + word_size..];
+
+        if names_size > names.len() as u64 {
+            return Err(truncated(names_size_at as u64 + names_size));
+        }
+
+        for entry in 0..ranlib_size as usize / (word_size * 2) {
+            let at = word_size + entry * word_size * 2;
+            let (Some(name), Some(member_offset)) = (word(at), word(at + word_size)) else {
+                return Err(truncated(at as u64 + word_size as u64 * 2));
+            };
+
+            let name = usize::try_from(name)
+                .ok()
+                .and_then(|name| names.get(name..))
+                .ok_or(Error::OutOfRange {
+                    what: "archive index name",
+                    offset: name,
+                    limit: names.len() as u64,
+                })?;
+
+            self.symbols.push(ArchiveSymbol {
+                name: c_string(name),
+                member_offset,
+            });
+        }
+
+        self.symbol_names_size = names_size;
+        Ok(())
+    }
+
+    /// The name at `offset` in the GNU `//` table, where each ends in `/\n`.
+    fn long_name(&self, table: Option<(usize, usize)>, offset: u64) -> Result<String> {
+        let (start, end) = table.unwrap_or((0, 0));
+        let names = &self.data[start..end];
+
+        let name = usize::try_from(offset)
+            .ok()
+            .and_then(|offset| names.get(offset..))
+            .ok_or(Error::OutOfRange {
+                what: "archive long name offset",
+                offset,
+                limit: names.len() as u64,
+            })?;
+
+        let length = name
+            .iter()
+            .position(|byte| matches!(byte, b'\n' | 0))
+            .unwrap_or(name.len());
+
+        Ok(String::from_utf8_lossy(&name[..length])
+            .trim_end_matches('/')
+            .to_string())
+    }
+
+    fn parse_members(&mut self) {
+        let mut position = ARCHIVE_MAGIC.len();
+        let mut long_names = Option::None;
+        let mut diagnostics = Vec::new();
+        let mut gnu = false;
+
+        // members start on even offsets, so a final padding newline may be all that is left
+        while position < self.data.len() && self.data[position..] != *b"\n" {
+            let (mut member, mut start, end) = match self.parse_header(position) {
+                Ok(header) => header,
+                Err(error) => {
+                    diagnostics.push(Diagnostic {
+                        table: ARCHIVE_MEMBER,
+                        index: Some(self.members.len()),
+                        error,
+                    });
+                    break;
+                }
+            };
+
+            position = end + (end & 1);
+
+            let index = match member.name.as_str() {
+                "/" | "/SYM64/" => {
+                    gnu = true;
+                    Some(if member.name == "/" { 4 } else { 8 })
+                }
+                "//" => {
+                    gnu = true;
+                    long_names = Some((start, end));
+                    continue;
+                }
+                _ => Option::None,
+            };
+
+            if let Some(length) = member.name.strip_prefix("#1/") {
+                let Some(length) = numeric_field(length.as_bytes(), 10)
+                    .map(|length| length as usize)
+                    .filter(|length| *length <= end - start)
+                else {
+                    diagnostics.push(Diagnostic {
+                        table: ARCHIVE_MEMBER,
+                        index: Some(self.members.len()),
+                        error: Error::MalformedArchive {
+                            offset: member.header_offset,
+                            problem: "BSD long name length is not a size within the member",
+                        },
+                    });
+                    continue;
+                };
+
+                member.name = c_string(&self.data[start..start + length]);
+                start += length;
+                member.offset = start as u64;
+                member.size = (end - start) as u64;
+            } else if index.is_none()
+                && let Some(offset) = member.name.strip_prefix('/')
+            {
+                gnu = true;
+
+                match numeric_field(offset.as_bytes(), 10)
+                    .ok_or(Error::MalformedArchive {
+                        offset: member.header_offset,
+                        problem: "GNU long name reference is not a decimal offset",
+                    })
+                    .and_then(|offset| self.long_name(long_names, offset))
+                {
+                    Ok(name) => member.name = name,
+                    Err(error) => diagnostics.push(Diagnostic {
+                        table: ARCHIVE_MEMBER,
+                        index: Some(self.members.len()),
+                        error,
+                    }),
+                }
+            } else if index.is_none()
+                && let Some(name) = member.name.strip_suffix('/')
+            {
+                gnu = true;
+                member.name = name.to_string();
+            }
+
+            let index = index.map(|word_size| (word_size, true)).or_else(|| {
+                member.name.strip_prefix("__.SYMDEF").map(|variant| {
+                    let word_size = if variant.starts_with("_64") { 8 } else { 4 };
+                    (word_size, false)
+                })
+            });
+
+            if let Some((word_size, is_gnu)) = index {
+                let contents = self.data[start..end].to_vec();
+                let result = if is_gnu {
+                    self.parse_gnu_index(&contents, word_size)
+                } else {
+                    self.parse_bsd_index(&contents, word_size)
+                };
+
+                if let Err(error) = result {
+                    diagnostics.push(Diagnostic {
+                        table: ARCHIVE_INDEX,
+                        index: Option::None,
+                        error,
+                    });
+                }
+
+                continue;
+            }
+
+            self.members.push(member);
+        }
+
+        if gnu {
+            self.format = ArchiveFormat::Gnu;
+        }
+
+        self.diagnostics.extend(diagnostics);
+    }
+
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    /// The members in archive order, leaving out the symbol index and long name table.
+    pub fn members(&self) -> &[ArchiveMember] {
+        &self.members
+    }
+
+    /// The symbols of the archive index, in index order.
+    pub fn symbols(&self) -> &[ArchiveSymbol] {
+        &self.symbols
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn member_data(&self, member: &ArchiveMember) -> &[u8] {
+        let start = member.offset as usize;
+        &self.data[start..start + member.size as usize]
+    }
+
+    pub fn member_by_name(&self, name: &str) -> Option<&ArchiveMember> {
+        self.members.iter().find(|member| member.name == name)
+    }
+
+    /// The member whose header is at `offset`, as the symbol index refers to them.
+    pub fn member_at(&self, offset: u64) -> Option<&ArchiveMember> {
+        self.members
+            .iter()
+            .find(|member| member.header_offset == offset)
+    }
+
+    /// Lists the members the way `ar tv` does, with times in UTC.
+    pub fn member_lines(&self) -> Vec<String> {
+        self.members
+            .iter()
+            .map(|member| {
+                format!(
+                    "{} {}/{} {:>6} {} {}",
+                    mode_string(member.mode),
+                    member.uid,
+                    member.gid,
+                    member.size,
+                    date_string(member.date),
+                    member.name
+                )
+            })
+            .collect()
+    }
+
+    /// Formats the symbol index the way `readelf -c` does, grouped by the member defining them.
+    pub fn index_lines(&self, archive_name: &str) -> Vec<String> {
+        if self.symbols.is_empty() {
+            return vec![format!("{archive_name} has no archive index")];
+        }
+
+        let mut lines = vec![format!(
+            "Index of archive {archive_name}: ({} entries, {:#x} bytes in the symbol table)",
+            self.symbols.len(),
+            self.symbol_names_size
+        )];
+        let mut current = Option::None;
+
+        for symbol in &self.symbols {
+            if current != Some(symbol.member_offset) {
+                current = Some(symbol.member_offset);
+
+                let name = self
+                    .member_at(symbol.member_offset)
+                    .map(|member| member.name.as_str())
+                    .unwrap_or("<unknown>");
+
+                lines.push(format!(
+                    "Contents of binary {archive_name}({name}) at offset {:#x}",
+                    symbol.member_offset
+                ));
+            }
+
+            lines.push(format!("\t{}", symbol.name));
+        }
+
+        lines
+    }
+
+    pub fn dump_members(&self, out: &mut dyn OutputSink) {
+        out.begin_section("archive members");
+
+        for line in self.member_lines() {
+            out.write_line(line.as_str());
+        }
+    }
+
+    pub fn dump_index(&self, archive_name: &str, out: &mut dyn OutputSink) {
+        out.begin_section("archive index");
+
+        for line in self.index_lines(archive_name) {
+            out.write_line(line.as_str());
+        }
+    }
+}
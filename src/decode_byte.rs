@@ -1,47 +1,63 @@
-#[derive(Clone, Copy)]
+/// Byte order used when reading multi-byte values out of a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Endianness {
     LittleEndian,
     BigEndian,
 }
 
+/// Integers that can be read from a byte slice of exactly their own size.
 pub trait FromBytes: Sized {
-    fn from_le_bytes(bytes: &[u8]) -> Self;
-    fn from_be_bytes(bytes: &[u8]) -> Self;
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self>;
+    fn from_be_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl FromBytes for u8 {
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_le_bytes(bytes.try_into().ok()?))
+    }
+    fn from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_be_bytes(bytes.try_into().ok()?))
+    }
 }
 
 impl FromBytes for u16 {
-    fn from_le_bytes(bytes: &[u8]) -> Self {
-        Self::from_le_bytes(bytes.try_into().unwrap())
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_le_bytes(bytes.try_into().ok()?))
     }
-    fn from_be_bytes(bytes: &[u8]) -> Self {
-        Self::from_be_bytes(bytes.try_into().unwrap())
+    fn from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_be_bytes(bytes.try_into().ok()?))
     }
 }
 
 impl FromBytes for u32 {
-    fn from_le_bytes(bytes: &[u8]) -> Self {
-        Self::from_le_bytes(bytes.try_into().unwrap())
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_le_bytes(bytes.try_into().ok()?))
     }
-    fn from_be_bytes(bytes: &[u8]) -> Self {
-        Self::from_be_bytes(bytes.try_into().unwrap())
+    fn from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_be_bytes(bytes.try_into().ok()?))
     }
 }
 
 impl FromBytes for u64 {
-    fn from_le_bytes(bytes: &[u8]) -> Self {
-        Self::from_le_bytes(bytes.try_into().unwrap())
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_le_bytes(bytes.try_into().ok()?))
     }
-    fn from_be_bytes(bytes: &[u8]) -> Self {
-        Self::from_be_bytes(bytes.try_into().unwrap())
+    fn from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        Some(Self::from_be_bytes(bytes.try_into().ok()?))
     }
 }
 
-pub fn get_value<T: FromBytes>(endianness: Endianness, bytes: &[u8]) -> T {
-    assert_eq!(bytes.len(), size_of::<T>());
-
+/// Decodes `bytes` as a `T`, returning `None` unless `bytes` is exactly `size_of::<T>()` long.
+pub fn get_value<T: FromBytes>(endianness: Endianness, bytes: &[u8]) -> Option<T> {
     if matches!(endianness, Endianness::LittleEndian) {
         return T::from_le_bytes(bytes);
     }
 
-    return T::from_be_bytes(bytes);
+    T::from_be_bytes(bytes)
+}
+
+/// Reads a `T` at `offset` in `data`, returning `None` if it would run past the end.
+pub fn read_value<T: FromBytes>(endianness: Endianness, data: &[u8], offset: usize) -> Option<T> {
+    let end = offset.checked_add(size_of::<T>())?;
+    get_value(endianness, data.get(offset..end)?)
 }
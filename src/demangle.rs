@@ -0,0 +1,30 @@
+use cpp_demangle::DemangleOptions;
+
+/// Demangles a Rust (legacy or v0) or Itanium C++ symbol name, returning `None` for anything else.
+pub fn demangle(name: &str) -> Option<String> {
+    demangle_with(name, DemangleOptions::new())
+}
+
+/// Like [`demangle`] but leaves out C++ parameter lists, so `_ZN2ns3fooEi` becomes `ns::foo`.
+pub fn demangle_without_parameters(name: &str) -> Option<String> {
+    demangle_with(name, DemangleOptions::new().no_params())
+}
+
+fn demangle_with(name: &str, options: DemangleOptions) -> Option<String> {
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        // the alternate form drops the legacy hash suffix
+        return Some(format!("{demangled:#}"));
+    }
+
+    if !name.starts_with("_Z") {
+        return Option::None;
+    }
+
+    let symbol = cpp_demangle::Symbol::new(name).ok()?;
+    symbol.demangle_with_options(&options).ok()
+}
+
+/// Demangles `name` if possible, otherwise returns it unchanged.
+pub fn demangle_or_original(name: &str) -> String {
+    demangle(name).unwrap_or_else(|| name.to_owned())
+}
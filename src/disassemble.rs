@@ -1,23 +1,92 @@
-mod aarch64_disassembler;
-use std::fmt::Write;
-use std::sync::{Arc, Mutex};
+pub mod aarch64_disassembler;
+mod addresses;
+mod checksec;
+mod compression;
+mod core_dump;
+mod debug_info;
+mod debug_line;
+mod dwarf;
+mod dynamic;
+mod hash;
+mod header;
+mod notes;
+mod relocations;
+mod sections;
+mod segments;
+mod symbols;
+mod versions;
+
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::fmt;
 
 use aarch64_disassembler::*;
 
 use crate::decode_byte::*;
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+pub use checksec::*;
+pub use compression::*;
+pub use core_dump::*;
+pub use debug_info::*;
+pub use debug_line::*;
+pub use dwarf::*;
+pub use dynamic::*;
+pub use hash::*;
+pub use header::*;
+pub use notes::*;
+pub use relocations::*;
+pub use sections::*;
+pub use segments::*;
+pub use symbols::*;
+pub use versions::*;
 
 const ELF_MAGIC_NUMBER: u8 = 0x7F;
 const ELF_IDENTITY: &str = "ELF";
+const ELF_IDENTIFICATION_SIZE: usize = 16;
 
+#[derive(Clone, Copy)]
 enum BitFormat {
     Bit32,
     Bit64,
 }
 
-enum OSAbi {
-    Linux,
-    SystemV,
-    Unsupported,
+impl BitFormat {
+    fn address_size(&self) -> usize {
+        match self {
+            BitFormat::Bit32 => 4,
+            BitFormat::Bit64 => 8,
+        }
+    }
+
+    fn header_size(&self) -> usize {
+        match self {
+            BitFormat::Bit32 => 0x34,
+            BitFormat::Bit64 => 0x40,
+        }
+    }
+
+    fn section_header_size(&self) -> u64 {
+        match self {
+            BitFormat::Bit32 => 0x28,
+            BitFormat::Bit64 => 0x40,
+        }
+    }
+
+    fn program_header_size(&self) -> u64 {
+        match self {
+            BitFormat::Bit32 => 0x20,
+            BitFormat::Bit64 => 0x38,
+        }
+    }
+}
+
+fn read_address(format: BitFormat, endianness: Endianness, bytes: &[u8], offset: usize) -> u64 {
+    match format {
+        BitFormat::Bit32 => read_value::<u32>(endianness, bytes, offset).unwrap_or(0) as u64,
+        BitFormat::Bit64 => read_value::<u64>(endianness, bytes, offset).unwrap_or(0),
+    }
 }
 
 enum InstructionSet {
@@ -25,184 +94,522 @@ enum InstructionSet {
     Unsupported,
 }
 
-#[allow(dead_code)]
+/// A decoded machine instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    /// Byte offset of the instruction from the start of its section.
+    pub offset: usize,
+    pub word: u32,
+    pub text: String,
+}
+
+impl Instruction {
+    /// The disassembly listing line for the instruction loaded at `address`.
+    pub fn line(&self, address: u64) -> String {
+        format!(
+            "{:>8x}:   0x{:08X}          {}",
+            address, self.word, self.text
+        )
+    }
+}
+
+/// Formats the listing line with the offset in the section standing in for the address.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.line(self.offset as u64).as_str())
+    }
+}
+
+/// The disassembly of one section of an ELF file.
+#[derive(Clone, Debug)]
+pub struct DisassembledSection {
+    /// Index of the section header the code came from.
+    pub index: usize,
+    pub name: String,
+    /// Virtual address of the first byte of the section.
+    pub address: u64,
+    pub instructions: Vec<Instruction>,
+}
+
+impl DisassembledSection {
+    /// The virtual address of `instruction`, which must belong to this section.
+    pub fn address_of(&self, instruction: &Instruction) -> u64 {
+        self.address.wrapping_add(instruction.offset as u64)
+    }
+}
+
+/// A parsed ELF file, either 32 or 64 bit, for any OS/ABI and machine.
 pub struct ElfHeader {
     data: Vec<u8>,
     format: BitFormat,
     endianness: Endianness,
-    abi: OSAbi,
     instruction_set: InstructionSet,
-    section_header_offset: u64,
-    section_header_entry_size: u16,
-    section_header_entry_count: u16,
-    section_header_names_index: u16,
-    output_string: Arc<Mutex<String>>,
+    header: FileHeader,
+    sections: Vec<SectionHeader>,
+    segments: Vec<ProgramHeader>,
+    symbols: Vec<Symbol>,
+    /// The `.gnu.version` entries, one per symbol of the table the section links to.
+    symbol_versions: Vec<u16>,
+    version_needs: Vec<VersionNeed>,
+    version_definitions: Vec<VersionDefinition>,
+    hash_table: Option<HashTable>,
+    gnu_hash_table: Option<GnuHashTable>,
+    dynamic: Vec<DynamicEntry>,
+    relocations: Vec<Relocation>,
+    notes: Vec<NoteTable>,
+    /// `e_shnum` and `e_shstrndx`, read from section 0 when they do not fit in the file header.
+    section_count: u64,
+    section_names_index: usize,
+    /// `.debug_line` and `.debug_info`, parsed on first use along with the problems found, as
+    /// most commands never look at them.
+    line_programs: OnceCell<(Vec<LineProgram>, Vec<Diagnostic>)>,
+    compile_units: OnceCell<(Vec<CompileUnit>, Vec<Diagnostic>)>,
+    /// Each compressed section by index, decompressed on first use.
+    decompressed: BTreeMap<usize, OnceCell<std::result::Result<Vec<u8>, Diagnostic>>>,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl ElfHeader {
-    pub fn new(bytes: &[u8], output_string: Arc<Mutex<String>>) -> Option<ElfHeader> {
-        if bytes.len() < 64 {
-            let _ = writeln!(output_string.lock().unwrap(), "invalid header");
-            return Option::None;
+    /// Parses the ELF header at the start of `bytes`.
+    pub fn new(bytes: &[u8]) -> Result<ElfHeader> {
+        if bytes.len() < ELF_IDENTIFICATION_SIZE {
+            return Err(Error::Truncated {
+                what: "ELF identification",
+                offset: 0,
+                needed: ELF_IDENTIFICATION_SIZE as u64,
+                available: bytes.len() as u64,
+            });
         }
 
-        let header_info = &bytes[0..64];
-
-        if header_info[0] != ELF_MAGIC_NUMBER {
-            let _ = writeln!(output_string.lock().unwrap(), "invalid header");
-            return Option::None;
+        if bytes[0] != ELF_MAGIC_NUMBER {
+            return Err(Error::BadMagic);
         }
 
-        if header_info[1..4] != ELF_IDENTITY.to_owned().into_bytes() {
-            let _ = writeln!(output_string.lock().unwrap(), "invalid header");
-            return Option::None;
+        if bytes[1..4] != ELF_IDENTITY.to_owned().into_bytes() {
+            return Err(Error::BadMagic);
         }
 
-        let format = if header_info[4] == 1 {
-            BitFormat::Bit32
-        } else {
-            BitFormat::Bit64
+        let format = match bytes[4] {
+            1 => BitFormat::Bit32,
+            2 => BitFormat::Bit64,
+            class => return Err(Error::UnsupportedClass(class)),
         };
 
-        if matches!(format, BitFormat::Bit32) {
-            let _ = writeln!(
-                output_string.lock().unwrap(),
-                "currently unsupported on 32 bit systems"
-            );
-            return Option::None;
+        if bytes.len() < format.header_size() {
+            return Err(Error::Truncated {
+                what: "ELF header",
+                offset: 0,
+                needed: format.header_size() as u64,
+                available: bytes.len() as u64,
+            });
         }
 
-        let endianness = if header_info[5] == 1 {
-            Endianness::LittleEndian
-        } else {
-            Endianness::BigEndian
-        };
+        let header_info = &bytes[0..format.header_size()];
 
-        let abi = match header_info[7] {
-            0x03 => OSAbi::Linux,
-            0x00 => OSAbi::SystemV,
-            _ => OSAbi::Unsupported,
+        let endianness = match header_info[5] {
+            1 => Endianness::LittleEndian,
+            2 => Endianness::BigEndian,
+            encoding => return Err(Error::UnsupportedDataEncoding(encoding)),
         };
 
-        if matches!(abi, OSAbi::Unsupported) {
-            let _ = writeln!(
-                output_string.lock().unwrap(),
-                "unsupported abi {:02X}",
-                header_info[7]
-            );
+        let file_header = FileHeader::parse(format, endianness, header_info);
 
-            return Option::None;
-        }
-
-        let value = get_value::<u16>(endianness, &bytes[0x12..0x12 + 2]);
-        let instruction_set = match value {
-            0xB7 => InstructionSet::Arm64,
+        let instruction_set = match file_header.machine {
+            EM_AARCH64 => InstructionSet::Arm64,
             _ => InstructionSet::Unsupported,
         };
 
-        if matches!(instruction_set, InstructionSet::Unsupported) {
-            let _ = writeln!(
-                output_string.lock().unwrap(),
-                "unsupported instruction set {:04X}",
-                value
-            );
-            return Option::None;
-        }
-
-        let section_header_offset = get_value::<u64>(endianness, &bytes[0x28..0x28 + 8]);
-        let section_header_entry_size = get_value::<u16>(endianness, &bytes[0x3A..0x3A + 2]);
-        let section_header_entry_count = get_value::<u16>(endianness, &bytes[0x3C..0x3C + 2]);
-        let section_header_names_index = get_value::<u16>(endianness, &bytes[0x3E..0x3E + 2]);
-
-        let header = ElfHeader {
+        let mut header = ElfHeader {
             data: bytes.to_vec(),
             format,
             endianness,
-            abi,
             instruction_set,
-            section_header_offset,
-            section_header_entry_size,
-            section_header_entry_count,
-            section_header_names_index,
-            output_string,
+            header: file_header,
+            sections: Vec::new(),
+            segments: Vec::new(),
+            symbols: Vec::new(),
+            symbol_versions: Vec::new(),
+            version_needs: Vec::new(),
+            version_definitions: Vec::new(),
+            hash_table: Option::None,
+            gnu_hash_table: Option::None,
+            dynamic: Vec::new(),
+            relocations: Vec::new(),
+            notes: Vec::new(),
+            section_count: 0,
+            section_names_index: 0,
+            line_programs: OnceCell::new(),
+            compile_units: OnceCell::new(),
+            decompressed: BTreeMap::new(),
+            diagnostics: Vec::new(),
         };
 
-        Some(header)
+        header.parse_section_headers();
+        header.find_compressed_sections();
+        header.parse_program_headers();
+        header.parse_symbol_tables();
+        header.parse_version_tables();
+        header.parse_hash_tables();
+        header.parse_dynamic_section();
+        header.parse_relocation_tables();
+        header.parse_note_tables();
+
+        Ok(header)
     }
 
-    fn string_from_shstrtab(&self, offset: usize) -> String {
-        let shstrtab_section_header_offset = self.section_header_offset
-            + self.section_header_names_index as u64 * self.section_header_entry_size as u64;
+    fn read<T: FromBytes>(&self, what: &'static str, offset: u64) -> Result<T> {
+        let truncated = || Error::Truncated {
+            what,
+            offset,
+            needed: size_of::<T>() as u64,
+            available: self.data.len() as u64,
+        };
 
-        let shstrtab_section_header_offset = shstrtab_section_header_offset as usize;
+        let offset = usize::try_from(offset).map_err(|_| truncated())?;
+        read_value::<T>(self.endianness, &self.data, offset).ok_or_else(truncated)
+    }
 
-        let shstrtab_offset = get_value::<u64>(
-            self.endianness,
-            &self.data
-                [shstrtab_section_header_offset + 0x18..shstrtab_section_header_offset + 0x18 + 8],
-        ) as usize;
+    /// Reads an address sized field, which is 4 bytes wide in 32 bit files and 8 in 64 bit ones.
+    fn read_address(&self, what: &'static str, offset: u64) -> Result<u64> {
+        match self.format {
+            BitFormat::Bit32 => Ok(self.read::<u32>(what, offset)? as u64),
+            BitFormat::Bit64 => self.read::<u64>(what, offset),
+        }
+    }
 
-        let mut name = String::new();
-        let mut i: usize = 0;
+    /// How many entries of a table starting at `offset` can be read, recording a diagnostic when the
+    /// entries are too small to hold a whole record or the table runs past the end of the file.
+    fn readable_entry_count(
+        &mut self,
+        table: &'static str,
+        offset: u64,
+        entry_size: u16,
+        minimum_entry_size: u64,
+        count: u64,
+    ) -> usize {
+        if count == 0 {
+            return 0;
+        }
 
-        loop {
-            let byte = self.data[shstrtab_offset + offset + i];
-            i += 1;
+        if (entry_size as u64) < minimum_entry_size {
+            self.diagnostics.push(Diagnostic {
+                table,
+                index: Option::None,
+                error: Error::InvalidValue {
+                    what: "entry size",
+                    value: entry_size as u64,
+                },
+            });
+            return 0;
+        }
 
-            if byte == 0 {
-                break;
+        let table_size = (entry_size as u64).saturating_mul(count);
+        let available = (self.data.len() as u64).saturating_sub(offset);
+
+        if table_size > available {
+            self.diagnostics.push(Diagnostic {
+                table,
+                index: Option::None,
+                error: Error::Truncated {
+                    what: table,
+                    offset,
+                    needed: table_size,
+                    available: self.data.len() as u64,
+                },
+            });
+
+            return (available / entry_size as u64) as usize;
+        }
+
+        count as usize
+    }
+
+    fn section_header_offset_of(&self, header_index: usize) -> u64 {
+        self.header.section_header_offset.saturating_add(
+            (header_index as u64).saturating_mul(self.header.section_header_entry_size as u64),
+        )
+    }
+
+    /// Problems found while parsing; the affected entries are left out of the parsed tables. The
+    /// debug info and compressed sections only add theirs once something has used them.
+    pub fn diagnostics(&self) -> Vec<&Diagnostic> {
+        let decompressed = self
+            .decompressed
+            .values()
+            .filter_map(|contents| contents.get()?.as_ref().err());
+        let compile_units = self
+            .compile_units
+            .get()
+            .into_iter()
+            .flat_map(|(_, found)| found);
+        let line_programs = self
+            .line_programs
+            .get()
+            .into_iter()
+            .flat_map(|(_, found)| found);
+
+        self.diagnostics
+            .iter()
+            .chain(decompressed)
+            .chain(compile_units)
+            .chain(line_programs)
+            .collect()
+    }
+
+    /// Whether there is an instruction decoder for this file's `e_machine`.
+    pub fn can_disassemble(&self) -> bool {
+        !matches!(self.instruction_set, InstructionSet::Unsupported)
+    }
+
+    fn require_decoder(&self) -> Result<()> {
+        if !self.can_disassemble() {
+            return Err(Error::UnsupportedMachine(self.header.machine));
+        }
+
+        Ok(())
+    }
+
+    fn disassemble_section(&self, section: &SectionHeader) -> Option<DisassembledSection> {
+        let bytes = self.section_data(section)?;
+
+        let instructions = match self.instruction_set {
+            InstructionSet::Arm64 => disassemble_aarch64(bytes),
+            InstructionSet::Unsupported => Vec::new(),
+        };
+
+        Some(DisassembledSection {
+            index: section.index,
+            name: section.name.clone(),
+            address: section.address,
+            instructions,
+        })
+    }
+
+    /// Disassembles every `SHF_EXECINSTR` section; sections whose data lies outside the file are skipped.
+    pub fn disassemble(&self) -> Result<Vec<DisassembledSection>> {
+        self.require_decoder()?;
+
+        Ok(self
+            .sections
+            .iter()
+            .filter(|section| section.is_executable())
+            .filter_map(|section| self.disassemble_section(section))
+            .collect())
+    }
+
+    /// Disassembles the named sections in the order given.
+    ///
+    /// Sections without `SHF_EXECINSTR` are refused unless `force` is set, since decoding data as
+    /// code rarely means anything; sections with no bytes in the file come back empty.
+    pub fn disassemble_sections(
+        &self,
+        names: &[String],
+        force: bool,
+    ) -> Result<Vec<DisassembledSection>> {
+        self.require_decoder()?;
+
+        let mut sections = Vec::new();
+
+        for name in names {
+            let section = self
+                .section_by_name(name)
+                .ok_or_else(|| Error::SectionNotFound(name.clone()))?;
+
+            if !section.is_executable() && !force {
+                return Err(Error::SectionNotExecutable(name.clone()));
             }
 
-            name.push(byte as char);
+            let code = self
+                .disassemble_section(section)
+                .unwrap_or_else(|| DisassembledSection {
+                    index: section.index,
+                    name: section.name.clone(),
+                    address: section.address,
+                    instructions: Vec::new(),
+                });
+
+            sections.push(code);
         }
 
-        name
+        Ok(sections)
     }
 
-    fn dump_section_code(&self, header_index: usize) {
-        let header_offset = self.section_header_offset
-            + header_index as u64 * self.section_header_entry_size as u64;
+    /// Disassembles the single function or label `name`, matched exactly or by its demangled name.
+    ///
+    /// The function ends after `st_size` bytes, or at the next label in its section when the
+    /// symbol has no size, as is common for hand written assembly.
+    pub fn disassemble_function(&self, name: &str) -> Result<DisassembledSection> {
+        self.require_decoder()?;
+
+        let not_found = || Error::SymbolNotFound(name.to_owned());
 
-        let header_offset = header_offset as usize;
+        let symbol = self.symbol_by_name(name).ok_or_else(not_found)?;
+        let section = self
+            .section(symbol.section_index as usize)
+            .ok_or_else(not_found)?;
+        let mut code = self.disassemble_section(section).ok_or_else(not_found)?;
 
-        let flags = get_value::<u64>(
-            self.endianness,
-            &self.data[header_offset + 0x08..header_offset + 0x08 + 8],
-        );
+        let start = self.symbol_address(symbol);
+        let end = if symbol.size > 0 {
+            start.saturating_add(symbol.size)
+        } else {
+            self.symbol_labels(section.index)
+                .range(start.saturating_add(1)..)
+                .next()
+                .map(|(address, _)| *address)
+                .unwrap_or(u64::MAX)
+        };
 
-        let name_offset = get_value::<u32>(
-            self.endianness,
-            &self.data[header_offset..header_offset + 4],
-        ) as usize;
+        code.instructions.retain(|instruction| {
+            let address = section.address.wrapping_add(instruction.offset as u64);
+            address >= start && address < end
+        });
 
-        let name = self.string_from_shstrtab(name_offset);
-        if name != ".text" {
-            return;
+        Ok(code)
+    }
+
+    /// Formats `code` with its virtual addresses, a `<name>:` line before every labelled address and
+    /// a `; R_TYPE target` line after every instruction a relocation patches.
+    ///
+    /// With `source`, each change of source line is marked with a `path:line` line, followed by the
+    /// text of that line when the file can be read, like `objdump -S -l`.
+    pub fn disassembly_lines(
+        &self,
+        code: &DisassembledSection,
+        demangle: bool,
+        source: bool,
+    ) -> Vec<String> {
+        let labels = self.symbol_labels(code.index);
+        let relocations = self.relocations_in(code);
+        let address_width = self.format.address_size() * 2;
+        let mut lines = vec![format!("Disassembly of section {}:", code.name)];
+
+        let locations = if source {
+            self.source_locations(
+                code.instructions
+                    .iter()
+                    .map(|instruction| code.address_of(instruction)),
+            )
+        } else {
+            Vec::new()
+        };
+        let mut source_files = SourceFiles::default();
+        let mut previous_location = Option::None;
+
+        for (index, instruction) in code.instructions.iter().enumerate() {
+            let address = code.address_of(instruction);
+
+            if let Some(symbol) = labels.get(&address) {
+                lines.push(String::new());
+                lines.push(format!(
+                    "{:0aw$x} <{}>:",
+                    address,
+                    self.symbol_display_name(symbol, demangle),
+                    aw = address_width
+                ));
+                previous_location = Option::None;
+            }
+
+            if let Some(Some(location)) = locations.get(index) {
+                let position = (&location.path, location.line);
+
+                if previous_location != Some(position) {
+                    lines.push(format!("{}:{}", location.path, location.line));
+
+                    if let Some(text) = source_files.line(location) {
+                        lines.push(String::from(text));
+                    }
+
+                    previous_location = Some(position);
+                }
+            }
+
+            lines.push(instruction.line(address));
+
+            for relocation in relocations.get(&address).into_iter().flatten() {
+                lines.push(format!(
+                    "{:32}; {} {}",
+                    "",
+                    relocation_type_name(self.header.machine, relocation.relocation_type),
+                    self.relocation_target(relocation, demangle)
+                ));
+            }
         }
 
-        let _ = writeln!(self.output_string.lock().unwrap(), "section {name}");
+        lines
+    }
 
-        let section_offset = get_value::<u64>(
-            self.endianness,
-            &self.data[header_offset + 0x18..header_offset + 0x18 + 8],
-        ) as usize;
+    fn write_disassembly(
+        &self,
+        code: &DisassembledSection,
+        demangle: bool,
+        source: bool,
+        out: &mut dyn OutputSink,
+    ) {
+        out.begin_section(code.name.as_str());
+
+        for line in self.disassembly_lines(code, demangle, source) {
+            out.write_line(line.as_str());
+        }
 
-        let section_size = get_value::<u64>(
-            self.endianness,
-            &self.data[header_offset + 0x20..header_offset + 0x20 + 8],
-        ) as usize;
+        out.write_line("");
+    }
 
-        if matches!(self.instruction_set, InstructionSet::Arm64) {
-            print_aarch64_disassembly(
-                &self.data[section_offset..section_offset + section_size],
-                self.output_string.clone(),
-            );
+    pub fn dump_disassembly(
+        &self,
+        demangle: bool,
+        source: bool,
+        out: &mut dyn OutputSink,
+    ) -> Result<()> {
+        for code in self.disassemble()? {
+            self.write_disassembly(&code, demangle, source, out);
         }
+
+        Ok(())
     }
 
-    pub fn dump_disassembly(&self) {
-        for i in 0..self.section_header_entry_count {
-            self.dump_section_code(i as usize);
+    /// Writes the disassembly of the named sections; see [`ElfHeader::disassemble_sections`].
+    pub fn dump_section_disassembly(
+        &self,
+        names: &[String],
+        force: bool,
+        demangle: bool,
+        source: bool,
+        out: &mut dyn OutputSink,
+    ) -> Result<()> {
+        for code in self.disassemble_sections(names, force)? {
+            self.write_disassembly(&code, demangle, source, out);
         }
+
+        Ok(())
+    }
+
+    /// Writes the disassembly from `address` to the end of its section; see
+    /// [`ElfHeader::disassemble_at`].
+    pub fn dump_disassembly_at(
+        &self,
+        address: u64,
+        force: bool,
+        demangle: bool,
+        source: bool,
+        out: &mut dyn OutputSink,
+    ) -> Result<()> {
+        let code = self.disassemble_at(address, force)?;
+        self.write_disassembly(&code, demangle, source, out);
+
+        Ok(())
+    }
+
+    pub fn dump_function_disassembly(
+        &self,
+        name: &str,
+        demangle: bool,
+        source: bool,
+        out: &mut dyn OutputSink,
+    ) -> Result<()> {
+        let code = self.disassemble_function(name)?;
+        self.write_disassembly(&code, demangle, source, out);
+
+        Ok(())
     }
 }
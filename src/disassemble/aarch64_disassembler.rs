@@ -1,9 +1,9 @@
-use std::fmt::Write;
-use std::sync::{Arc, Mutex};
-
+use super::Instruction;
 use crate::decode_byte::*;
 
-const ARM64_INSTRUCTION_SIZE: usize = 4;
+pub const ARM64_INSTRUCTION_SIZE: usize = 4;
+/// AArch64 instructions are always little endian, even in big endian (`aarch64_be`) images where
+/// only data accesses are byte swapped.
 const ARM64_INSTRUCTION_ENDIAN: Endianness = Endianness::LittleEndian;
 
 fn does_bit_pattern_match(pattern: &str, number: u32) -> bool {
@@ -41,7 +41,7 @@ fn format_register(is_w: bool, r: u32) -> String {
         return format!("w{}", r);
     }
 
-    return format!("x{}", r);
+    format!("x{}", r)
 }
 
 fn format_instruction_rd_imm(name: &str, is_w: bool, rd: u32, imm: u32) -> String {
@@ -86,7 +86,7 @@ fn decode_aarch64_data_processing_one_source(instruction: u32) -> String {
         }
     }
 
-    return format_instruction_rd_imm("unknown", false, rd, imm16);
+    format_instruction_rd_imm("unknown", false, rd, imm16)
 }
 
 fn decode_aarch64_data_processing_immediate_pc_rel_addressing(instruction: u32) -> String {
@@ -101,7 +101,7 @@ fn decode_aarch64_data_processing_immediate_pc_rel_addressing(instruction: u32)
         return format_instruction_rd_imm("adr", false, rd, data);
     }
 
-    return format_instruction_rd_imm("adrp", false, rd, data);
+    format_instruction_rd_imm("adrp", false, rd, data)
 }
 
 fn decode_aarch64_data_processing_immediate_add_sub(instruction: u32) -> String {
@@ -159,7 +159,7 @@ fn decode_aarch64_data_processing_register_extended_add_sub(instruction: u32) ->
     String::from("data_processing_register_extended_add_sub")
 }
 
-fn decode_aarch64_data_processing_register_three_source(instruction: u32) -> String {
+fn decode_aarch64_data_processing_register_three_source(_instruction: u32) -> String {
     String::from("data_processing_register_three_source")
 }
 
@@ -169,16 +169,12 @@ fn decode_aarch64_data_processing_register(instruction: u32) -> String {
     let op2 = (instruction >> 21) & 0xF;
     let _op3 = (instruction >> 10) & 0x3F;
 
-    if op1 == 0 {
-        if does_bit_pattern_match("1xx1", op2) {
-            return decode_aarch64_data_processing_register_extended_add_sub(instruction);
-        }
+    if op1 == 0 && does_bit_pattern_match("1xx1", op2) {
+        return decode_aarch64_data_processing_register_extended_add_sub(instruction);
     }
 
-    if op1 == 1 {
-        if does_bit_pattern_match("1xxx", op2) {
-            return decode_aarch64_data_processing_register_three_source(instruction);
-        }
+    if op1 == 1 && does_bit_pattern_match("1xxx", op2) {
+        return decode_aarch64_data_processing_register_three_source(instruction);
     }
 
     String::from("data_processing_register")
@@ -193,10 +189,10 @@ fn decode_aarch64_sve(_instruction: u32) -> String {
 }
 
 fn decode_aarch64_load_store_load_register_literal(instruction: u32) -> String {
-    let opc = instruction >> 30;
-    let vr = (instruction >> 26) & 1;
-    let imm19 = (instruction >> 5) & 0x7FFFF;
-    let rd = instruction & 0x1F;
+    let _opc = instruction >> 30;
+    let _vr = (instruction >> 26) & 1;
+    let _imm19 = (instruction >> 5) & 0x7FFFF;
+    let _rd = instruction & 0x1F;
 
     String::from("load_store_load_register_literal")
 }
@@ -251,10 +247,8 @@ fn decode_aarch64_load_store(instruction: u32) -> String {
     String::from("load_store")
 }
 
-fn decode_aarch64_instruction(instruction: &[u8]) -> String {
-    let instruction: [u8; ARM64_INSTRUCTION_SIZE] = instruction.try_into().unwrap();
-    let instruction = get_value::<u32>(ARM64_INSTRUCTION_ENDIAN, &instruction);
-
+/// Decodes a single aarch64 instruction word into its assembly text.
+pub fn decode_aarch64_instruction(instruction: u32) -> String {
     //NOTE: see the arm architecture reference manual for  better explanations
     let op0 = instruction >> 31;
     let op1 = (instruction >> 25) & 0xF;
@@ -290,18 +284,19 @@ fn decode_aarch64_instruction(instruction: &[u8]) -> String {
     String::from("instruction")
 }
 
-pub fn print_aarch64_disassembly(bytes: &[u8], output_string: Arc<Mutex<String>>) {
-    let str = bytes
+/// Decodes every whole instruction in `bytes`; trailing bytes that do not form a word are ignored.
+pub fn disassemble_aarch64(bytes: &[u8]) -> Vec<Instruction> {
+    bytes
         .chunks_exact(ARM64_INSTRUCTION_SIZE)
-        .map(|instruction| {
-            return (
-                decode_aarch64_instruction(instruction),
-                get_value::<u32>(ARM64_INSTRUCTION_ENDIAN, instruction),
-            );
-        })
         .enumerate()
-        .map(|(i, s)| format!("{:<5}          0x{:08X}          {}\n", i, s.1, s.0))
-        .collect::<String>();
-
-    writeln!(output_string.lock().unwrap(), "{str}");
+        .filter_map(|(i, instruction)| {
+            let word = get_value::<u32>(ARM64_INSTRUCTION_ENDIAN, instruction)?;
+
+            Some(Instruction {
+                offset: i * ARM64_INSTRUCTION_SIZE,
+                word,
+                text: decode_aarch64_instruction(word),
+            })
+        })
+        .collect()
 }
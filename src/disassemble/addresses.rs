@@ -0,0 +1,148 @@
+use super::{DisassembledSection, ElfHeader, PT_LOAD, SHF_ALLOC, SHT_NULL, SectionHeader};
+use crate::error::{Error, Result};
+
+/// Whether `value` lies in the `size` bytes starting at `start`.
+fn within(value: u64, start: u64, size: u64) -> bool {
+    value >= start && value - start < size
+}
+
+impl ElfHeader {
+    /// Whether the file has loadable segments, so addresses come from `PT_LOAD` rather than from
+    /// the section headers as in relocatable objects.
+    fn has_load_segments(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| segment.segment_type == PT_LOAD)
+    }
+
+    /// The allocated sections with bytes in the file, which are the only ones with both an
+    /// address and an offset.
+    fn mapped_sections(&self) -> impl Iterator<Item = &SectionHeader> {
+        self.sections.iter().filter(|section| {
+            section.flags & SHF_ALLOC != 0 && section.has_file_data() && section.size > 0
+        })
+    }
+
+    /// The file offset `address` is loaded from, using the `PT_LOAD` segments or, in files without
+    /// any, the allocated sections. Addresses in `.bss` style zero fill have no offset.
+    pub fn vaddr_to_offset(&self, address: u64) -> Option<u64> {
+        if self.has_load_segments() {
+            return self
+                .segments
+                .iter()
+                .filter(|segment| segment.segment_type == PT_LOAD)
+                .find(|segment| within(address, segment.virtual_address, segment.file_size))
+                .map(|segment| {
+                    segment
+                        .offset
+                        .saturating_add(address - segment.virtual_address)
+                });
+        }
+
+        self.mapped_sections()
+            .find(|section| within(address, section.address, section.size))
+            .map(|section| section.offset.saturating_add(address - section.address))
+    }
+
+    /// The virtual address the byte at file `offset` is loaded at, if it is loaded at all.
+    pub fn offset_to_vaddr(&self, offset: u64) -> Option<u64> {
+        if self.has_load_segments() {
+            return self
+                .segments
+                .iter()
+                .filter(|segment| segment.segment_type == PT_LOAD)
+                .find(|segment| within(offset, segment.offset, segment.file_size))
+                .map(|segment| {
+                    segment
+                        .virtual_address
+                        .wrapping_add(offset - segment.offset)
+                });
+        }
+
+        self.mapped_sections()
+            .find(|section| within(offset, section.offset, section.size))
+            .map(|section| section.address.wrapping_add(offset - section.offset))
+    }
+
+    /// The section whose bytes in the file include `offset`.
+    pub fn section_containing_offset(&self, offset: u64) -> Option<&SectionHeader> {
+        self.sections.iter().find(|section| {
+            section.section_type != SHT_NULL
+                && section.has_file_data()
+                && within(offset, section.offset, section.size)
+        })
+    }
+
+    /// The allocated section that `address` falls in, including zero filled ones like `.bss`.
+    pub fn section_containing_vaddr(&self, address: u64) -> Option<&SectionHeader> {
+        self.sections.iter().find(|section| {
+            section.flags & SHF_ALLOC != 0 && within(address, section.address, section.size)
+        })
+    }
+
+    /// The file range mapped from `address` to the end of its section, or of its segment when no
+    /// section covers it, as an `(offset, length)` pair. In a file cut short, like a core dump
+    /// that hit its size limit, the range stops at the end of the file.
+    pub fn file_range_at_vaddr(&self, address: u64) -> Result<(u64, u64)> {
+        let offset = self
+            .vaddr_to_offset(address)
+            .ok_or(Error::AddressNotMapped(address))?;
+
+        let length = match self
+            .section_containing_vaddr(address)
+            .filter(|section| section.has_file_data())
+        {
+            Some(section) => section.size - (address - section.address),
+            Option::None => {
+                let end = self
+                    .segments
+                    .iter()
+                    .filter(|segment| segment.segment_type == PT_LOAD)
+                    .find(|segment| within(address, segment.virtual_address, segment.file_size))
+                    .map(|segment| segment.virtual_address.saturating_add(segment.file_size))
+                    .unwrap_or(address);
+
+                end - address
+            }
+        };
+
+        let available = self.data.len() as u64;
+
+        if offset >= available {
+            return Err(Error::Truncated {
+                what: "memory at virtual address",
+                offset,
+                needed: length,
+                available,
+            });
+        }
+
+        Ok((offset, length.min(available - offset)))
+    }
+
+    /// Disassembles the section containing `address`, starting at `address`.
+    ///
+    /// As with [`ElfHeader::disassemble_sections`], a section without `SHF_EXECINSTR` is refused
+    /// unless `force` is set.
+    pub fn disassemble_at(&self, address: u64, force: bool) -> Result<DisassembledSection> {
+        self.require_decoder()?;
+
+        let section = self
+            .section_containing_vaddr(address)
+            .ok_or(Error::AddressNotMapped(address))?;
+
+        if !section.is_executable() && !force {
+            return Err(Error::SectionNotExecutable(section.name.clone()));
+        }
+
+        let mut code = self
+            .disassemble_section(section)
+            .ok_or(Error::AddressNotMapped(address))?;
+
+        code.instructions.retain(|instruction| {
+            section.address.wrapping_add(instruction.offset as u64) >= address
+        });
+
+        Ok(code)
+    }
+}
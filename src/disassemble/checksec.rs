@@ -0,0 +1,205 @@
+use super::{
+    EM_AARCH64, ET_DYN, ET_EXEC, ET_REL, ElfHeader, GNU_PROPERTY_AARCH64_FEATURE_1_BTI,
+    GNU_PROPERTY_AARCH64_FEATURE_1_PAC, PF_X, PT_GNU_RELRO, PT_GNU_STACK, SHT_SYMTAB,
+};
+use crate::output::OutputSink;
+
+/// Symbols whose presence shows the code was built with a stack protector.
+const CANARY_SYMBOLS: [&str; 3] = [
+    "__stack_chk_fail",
+    "__stack_chk_guard",
+    "__intel_security_cookie",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pie {
+    /// `ET_EXEC`, loaded at a fixed address.
+    Disabled,
+    /// `ET_DYN` with `DF_1_PIE` set in `DT_FLAGS_1`.
+    Enabled,
+    /// `ET_DYN` without `DF_1_PIE`, i.e. a shared library, even one with `PT_INTERP` like libc.
+    SharedObject,
+    /// `ET_REL`, not linked yet.
+    Relocatable,
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relro {
+    None,
+    /// `PT_GNU_RELRO` without `BIND_NOW`, so the GOT stays writable for lazy binding.
+    Partial,
+    Full,
+}
+
+/// The hardening features of a binary, in the spirit of `checksec.sh`.
+#[derive(Clone, Debug)]
+pub struct SecurityReport {
+    pub pie: Pie,
+    pub relro: Relro,
+    /// Whether `PT_GNU_STACK` exists and is not executable.
+    pub nx: bool,
+    pub canary: bool,
+    /// The `__*_chk` functions the binary calls, sorted and without version suffixes.
+    pub fortified: Vec<String>,
+    pub rpath: Option<String>,
+    pub runpath: Option<String>,
+    /// Whether the `.symtab` has been removed.
+    pub stripped: bool,
+    /// `Some` only for AArch64 files: whether every input was built with BTI and PAC enforcement.
+    pub bti: Option<bool>,
+    pub pac: Option<bool>,
+}
+
+fn yes_no(value: bool, yes: &str, no: &str) -> String {
+    String::from(if value { yes } else { no })
+}
+
+impl ElfHeader {
+    /// Collects the hardening checks from the parsed segments, dynamic section, symbols and notes.
+    pub fn security_report(&self) -> SecurityReport {
+        let has_segment = |segment_type| {
+            self.segments
+                .iter()
+                .any(|segment| segment.segment_type == segment_type)
+        };
+
+        let pie = match self.header.file_type {
+            ET_EXEC => Pie::Disabled,
+            ET_DYN if self.is_position_independent_executable() => Pie::Enabled,
+            ET_DYN => Pie::SharedObject,
+            ET_REL => Pie::Relocatable,
+            _ => Pie::Unknown,
+        };
+
+        let relro = match (has_segment(PT_GNU_RELRO), self.is_bind_now()) {
+            (false, _) => Relro::None,
+            (true, false) => Relro::Partial,
+            (true, true) => Relro::Full,
+        };
+
+        let nx = self
+            .segments
+            .iter()
+            .find(|segment| segment.segment_type == PT_GNU_STACK)
+            .is_some_and(|segment| segment.flags & PF_X == 0);
+
+        // static symbol tables spell versioned imports as name@VERSION
+        let names = self
+            .symbols
+            .iter()
+            .map(|symbol| symbol.name.split('@').next().unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        let canary = names.iter().any(|name| CANARY_SYMBOLS.contains(name));
+
+        let mut fortified = names
+            .iter()
+            .filter(|name| {
+                name.starts_with("__") && name.ends_with("_chk") && !CANARY_SYMBOLS.contains(name)
+            })
+            .map(|name| String::from(*name))
+            .collect::<Vec<_>>();
+        fortified.sort();
+        fortified.dedup();
+
+        let stripped = !self
+            .sections
+            .iter()
+            .any(|section| section.section_type == SHT_SYMTAB);
+
+        let (bti, pac) = if self.header.machine == EM_AARCH64 {
+            let features = self.aarch64_feature_1().unwrap_or(0);
+            (
+                Some(features & GNU_PROPERTY_AARCH64_FEATURE_1_BTI != 0),
+                Some(features & GNU_PROPERTY_AARCH64_FEATURE_1_PAC != 0),
+            )
+        } else {
+            (Option::None, Option::None)
+        };
+
+        SecurityReport {
+            pie,
+            relro,
+            nx,
+            canary,
+            fortified,
+            rpath: self.rpath(),
+            runpath: self.runpath(),
+            stripped,
+            bti,
+            pac,
+        }
+    }
+
+    /// Formats the security report, one `Check: result` line per feature.
+    pub fn checksec_lines(&self) -> Vec<String> {
+        let report = self.security_report();
+
+        let pie = match report.pie {
+            Pie::Disabled => "No PIE",
+            Pie::Enabled => "PIE enabled",
+            Pie::SharedObject => "DSO",
+            Pie::Relocatable => "REL",
+            Pie::Unknown => "Unknown file type",
+        };
+
+        let relro = match report.relro {
+            Relro::None => "No RELRO",
+            Relro::Partial => "Partial RELRO",
+            Relro::Full => "Full RELRO",
+        };
+
+        let fortify = if report.fortified.is_empty() {
+            String::from("No")
+        } else {
+            format!(
+                "Yes ({} fortified: {})",
+                report.fortified.len(),
+                report.fortified.join(", ")
+            )
+        };
+
+        let path = |label: &str, value: &Option<String>| match value {
+            Some(path) => format!("{label} {path}"),
+            Option::None => format!("No {label}"),
+        };
+
+        let mut fields = vec![
+            ("RELRO", String::from(relro)),
+            (
+                "Stack",
+                yes_no(report.canary, "Canary found", "No canary found"),
+            ),
+            ("NX", yes_no(report.nx, "NX enabled", "NX disabled")),
+            ("PIE", String::from(pie)),
+            ("RPATH", path("RPATH", &report.rpath)),
+            ("RUNPATH", path("RUNPATH", &report.runpath)),
+            (
+                "Symbols",
+                yes_no(report.stripped, "Stripped", "Not stripped"),
+            ),
+            ("FORTIFY", fortify),
+        ];
+
+        if let (Some(bti), Some(pac)) = (report.bti, report.pac) {
+            fields.push(("BTI", yes_no(bti, "BTI enabled", "No BTI")));
+            fields.push(("PAC", yes_no(pac, "PAC enabled", "No PAC")));
+        }
+
+        fields
+            .into_iter()
+            .map(|(name, value)| format!("{:<10}{}", format!("{name}:"), value))
+            .collect()
+    }
+
+    pub fn dump_checksec(&self, out: &mut dyn OutputSink) {
+        out.begin_section("checksec");
+
+        for line in self.checksec_lines() {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+}
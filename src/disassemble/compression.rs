@@ -0,0 +1,202 @@
+use std::cell::OnceCell;
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use ruzstd::decoding::StreamingDecoder;
+
+use super::{BitFormat, ElfHeader, SHF_COMPRESSED, SectionHeader};
+use crate::decode_byte::*;
+use crate::error::{Diagnostic, Error, Result};
+
+pub const ELFCOMPRESS_ZLIB: u32 = 1;
+pub const ELFCOMPRESS_ZSTD: u32 = 2;
+
+const COMPRESSED_SECTION: &str = "compressed section";
+
+/// Magic at the start of legacy `.zdebug_*` sections, followed by the big endian size.
+const ZDEBUG_MAGIC: &[u8] = b"ZLIB";
+
+/// The most a zlib section may claim to grow by when decompressed. zlib cannot exceed about
+/// 1032:1, so anything beyond this is a corrupt or hostile size field.
+const MAXIMUM_ZLIB_RATIO: u64 = 1100;
+
+/// The largest section zstd may claim to decompress to. zstd has no such ratio limit, as a block
+/// repeating one byte takes 4 bytes for up to 128 KiB, so only an absolute bound protects memory.
+const MAXIMUM_ZSTD_SIZE: u64 = 1 << 30;
+
+/// The `Elf32_Chdr`/`Elf64_Chdr` at the start of a `SHF_COMPRESSED` section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressionHeader {
+    /// An `ELFCOMPRESS_*` value.
+    pub compression_type: u32,
+    /// Size of the section once decompressed.
+    pub size: u64,
+    pub alignment: u64,
+}
+
+/// Reads the whole output of `decoder`, which must be exactly `size` bytes.
+fn read_exactly(decoder: impl Read, size: u64, what: &'static str) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+
+    decoder
+        .take(size)
+        .read_to_end(&mut output)
+        .map_err(|error| Error::Decompression {
+            what,
+            message: error.to_string(),
+        })?;
+
+    if output.len() as u64 != size {
+        return Err(Error::Decompression {
+            what,
+            message: format!(
+                "expected {size} bytes but the stream holds {}",
+                output.len()
+            ),
+        });
+    }
+
+    Ok(output)
+}
+
+impl ElfHeader {
+    /// The compression header of a `SHF_COMPRESSED` section.
+    pub fn compression_header(&self, section: &SectionHeader) -> Option<CompressionHeader> {
+        if section.flags & SHF_COMPRESSED == 0 {
+            return Option::None;
+        }
+
+        let data = self.section_data(section)?;
+
+        let header = match self.format {
+            BitFormat::Bit32 => CompressionHeader {
+                compression_type: read_value::<u32>(self.endianness, data, 0)?,
+                size: read_value::<u32>(self.endianness, data, 4)? as u64,
+                alignment: read_value::<u32>(self.endianness, data, 8)? as u64,
+            },
+            BitFormat::Bit64 => CompressionHeader {
+                compression_type: read_value::<u32>(self.endianness, data, 0)?,
+                size: read_value::<u64>(self.endianness, data, 8)?,
+                alignment: read_value::<u64>(self.endianness, data, 16)?,
+            },
+        };
+
+        Some(header)
+    }
+
+    fn compression_header_size(&self) -> usize {
+        match self.format {
+            BitFormat::Bit32 => 12,
+            BitFormat::Bit64 => 24,
+        }
+    }
+
+    /// Whether the section's file bytes are compressed, either by `SHF_COMPRESSED` or as a GNU
+    /// style `.zdebug_*` section.
+    pub fn is_compressed(&self, section: &SectionHeader) -> bool {
+        section.flags & SHF_COMPRESSED != 0
+            || (section.name.starts_with(".zdebug")
+                && self
+                    .section_data(section)
+                    .is_some_and(|data| data.starts_with(ZDEBUG_MAGIC)))
+    }
+
+    fn decompress_section(&self, section: &SectionHeader) -> Result<Vec<u8>> {
+        let data = self.section_data(section).unwrap_or_default();
+
+        let (compression_type, size, stream) = if section.flags & SHF_COMPRESSED != 0 {
+            let header = self.compression_header(section).ok_or(Error::Truncated {
+                what: "compression header",
+                offset: section.offset,
+                needed: self.compression_header_size() as u64,
+                available: data.len() as u64,
+            })?;
+
+            (
+                header.compression_type,
+                header.size,
+                &data[self.compression_header_size()..],
+            )
+        } else {
+            // "ZLIB" and the size as 8 big endian bytes, whatever the file's byte order
+            let size = read_value::<u64>(Endianness::BigEndian, data, ZDEBUG_MAGIC.len()).ok_or(
+                Error::Truncated {
+                    what: "compression header",
+                    offset: section.offset,
+                    needed: 12,
+                    available: data.len() as u64,
+                },
+            )?;
+
+            (ELFCOMPRESS_ZLIB, size, &data[12..])
+        };
+
+        let maximum_size = match compression_type {
+            ELFCOMPRESS_ZLIB => (stream.len() as u64)
+                .saturating_add(1)
+                .saturating_mul(MAXIMUM_ZLIB_RATIO),
+            _ => MAXIMUM_ZSTD_SIZE,
+        };
+
+        if size > maximum_size {
+            return Err(Error::InvalidValue {
+                what: "decompressed section size",
+                value: size,
+            });
+        }
+
+        match compression_type {
+            ELFCOMPRESS_ZLIB => read_exactly(ZlibDecoder::new(stream), size, "zlib stream"),
+            ELFCOMPRESS_ZSTD => {
+                let decoder =
+                    StreamingDecoder::new(stream).map_err(|error| Error::Decompression {
+                        what: "zstd stream",
+                        message: error.to_string(),
+                    })?;
+
+                read_exactly(decoder, size, "zstd stream")
+            }
+            compression_type => Err(Error::InvalidValue {
+                what: "compression type",
+                value: compression_type as u64,
+            }),
+        }
+    }
+
+    /// Notes which sections are compressed, leaving the decompression until their bytes are used.
+    pub(super) fn find_compressed_sections(&mut self) {
+        let compressed = self
+            .sections
+            .iter()
+            .filter(|section| self.is_compressed(section))
+            .map(|section| (section.index, OnceCell::new()))
+            .collect();
+
+        self.decompressed = compressed;
+    }
+
+    /// The decompressed bytes of a compressed section, or `None` for one that is not compressed
+    /// or could not be decompressed.
+    pub fn decompressed_data(&self, section: &SectionHeader) -> Option<&[u8]> {
+        let contents = self.decompressed.get(&section.index)?.get_or_init(|| {
+            self.decompress_section(section)
+                .map_err(|error| Diagnostic {
+                    table: COMPRESSED_SECTION,
+                    index: Some(section.index),
+                    error,
+                })
+        });
+
+        contents.as_ref().ok().map(Vec::as_slice)
+    }
+
+    /// The bytes of the section as its consumers see them: decompressed for compressed sections,
+    /// straight from the file otherwise. Compressed sections that failed to decompress have none.
+    pub fn section_contents(&self, section: &SectionHeader) -> Option<&[u8]> {
+        if self.is_compressed(section) {
+            return self.decompressed_data(section);
+        }
+
+        self.section_data(section)
+    }
+}
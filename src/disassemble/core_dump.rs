@@ -0,0 +1,517 @@
+use super::{
+    BitFormat, EM_AARCH64, EM_X86_64, ET_CORE, ElfHeader, NT_AUXV, NT_FILE, NT_PRPSINFO,
+    NT_PRSTATUS, Note,
+};
+use crate::decode_byte::*;
+use crate::output::OutputSink;
+
+pub const AT_NULL: u64 = 0;
+pub const AT_PHDR: u64 = 3;
+pub const AT_PAGESZ: u64 = 6;
+pub const AT_ENTRY: u64 = 9;
+pub const AT_PLATFORM: u64 = 15;
+pub const AT_BASE_PLATFORM: u64 = 24;
+pub const AT_RANDOM: u64 = 25;
+pub const AT_EXECFN: u64 = 31;
+pub const AT_SYSINFO_EHDR: u64 = 33;
+
+/// `pr_reg` of a 64-bit Linux x86-64 `NT_PRSTATUS`, in `user_regs_struct` order.
+const X86_64_REGISTERS: [&str; 27] = [
+    "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx", "rdx", "rsi",
+    "rdi", "orig_rax", "rip", "cs", "eflags", "rsp", "ss", "fs_base", "gs_base", "ds", "es", "fs",
+    "gs",
+];
+
+/// `pr_reg` of an AArch64 `NT_PRSTATUS`, in `user_pt_regs` order.
+const AARCH64_REGISTERS: [&str; 34] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "x29", "x30", "sp", "pc", "pstate",
+];
+
+/// Offset of `pr_reg` in a 64-bit `elf_prstatus`, past the siginfo, signal masks, ids and times.
+const PRSTATUS_REGISTERS: usize = 112;
+
+/// Size of a 64-bit `elf_prpsinfo`.
+const PRPSINFO_SIZE: usize = 136;
+
+/// One thread's `NT_PRSTATUS`: why it stopped and its general purpose registers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessStatus {
+    /// `pr_cursig`, the signal that killed the process.
+    pub signal: u16,
+    pub pending: u64,
+    pub held: u64,
+    pub pid: u32,
+    pub ppid: u32,
+    pub pgrp: u32,
+    pub sid: u32,
+    /// The registers by name, empty for machines whose register set is not decoded.
+    pub registers: Vec<(&'static str, u64)>,
+}
+
+impl ProcessStatus {
+    pub fn register(&self, name: &str) -> Option<u64> {
+        self.registers
+            .iter()
+            .find(|(register, _)| *register == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// The `NT_PRPSINFO` of a core: the process that dumped it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessInfo {
+    /// `pr_sname`, the state letter `ps` shows, e.g. `R` or `S`.
+    pub state: char,
+    pub zombie: bool,
+    pub nice: i8,
+    pub flags: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: u32,
+    pub ppid: u32,
+    pub pgrp: u32,
+    pub sid: u32,
+    /// `pr_fname`, the executable's name cut to 15 bytes.
+    pub name: String,
+    /// `pr_psargs`, the start of the command line with its arguments.
+    pub arguments: String,
+}
+
+/// One file mapped into the process, from `NT_FILE`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileMapping {
+    pub start: u64,
+    pub end: u64,
+    /// Offset of the mapping in the file, in pages.
+    pub page_offset: u64,
+    pub path: String,
+}
+
+/// The `NT_FILE` note: every file mapped into the process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MappedFiles {
+    pub page_size: u64,
+    pub mappings: Vec<FileMapping>,
+}
+
+impl MappedFiles {
+    /// The file mapped at `address`, with the offset in that file `address` was loaded from.
+    pub fn file_at(&self, address: u64) -> Option<(&FileMapping, u64)> {
+        let mapping = self
+            .mappings
+            .iter()
+            .find(|mapping| address >= mapping.start && address < mapping.end)?;
+
+        let offset = mapping
+            .page_offset
+            .saturating_mul(self.page_size)
+            .saturating_add(address - mapping.start);
+
+        Some((mapping, offset))
+    }
+}
+
+/// An `a_type`/`a_val` pair of the auxiliary vector the kernel passed to the process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuxiliaryEntry {
+    pub key: u64,
+    pub value: u64,
+}
+
+/// The name `LD_SHOW_AUXV` prints for an auxiliary vector type.
+pub fn auxiliary_type_name(key: u64) -> String {
+    let name = match key {
+        AT_NULL => "AT_NULL",
+        1 => "AT_IGNORE",
+        2 => "AT_EXECFD",
+        AT_PHDR => "AT_PHDR",
+        4 => "AT_PHENT",
+        5 => "AT_PHNUM",
+        AT_PAGESZ => "AT_PAGESZ",
+        7 => "AT_BASE",
+        8 => "AT_FLAGS",
+        AT_ENTRY => "AT_ENTRY",
+        10 => "AT_NOTELF",
+        11 => "AT_UID",
+        12 => "AT_EUID",
+        13 => "AT_GID",
+        14 => "AT_EGID",
+        AT_PLATFORM => "AT_PLATFORM",
+        16 => "AT_HWCAP",
+        17 => "AT_CLKTCK",
+        23 => "AT_SECURE",
+        AT_BASE_PLATFORM => "AT_BASE_PLATFORM",
+        AT_RANDOM => "AT_RANDOM",
+        26 => "AT_HWCAP2",
+        27 => "AT_RSEQ_FEATURE_SIZE",
+        28 => "AT_RSEQ_ALIGN",
+        29 => "AT_HWCAP3",
+        30 => "AT_HWCAP4",
+        AT_EXECFN => "AT_EXECFN",
+        32 => "AT_SYSINFO",
+        AT_SYSINFO_EHDR => "AT_SYSINFO_EHDR",
+        51 => "AT_MINSIGSTKSZ",
+        _ => return format!("AT_??? ({key})"),
+    };
+
+    String::from(name)
+}
+
+/// Whether the auxiliary vector value of `key` is a count or id rather than an address or mask.
+fn auxiliary_value_is_decimal(key: u64) -> bool {
+    matches!(
+        key,
+        2 | 4 | 5 | AT_PAGESZ | 11..=14 | 17 | 23 | 27 | 28 | 51
+    )
+}
+
+/// The Linux name of a signal number, as on x86-64 and AArch64.
+pub fn signal_name(signal: u16) -> String {
+    let name = match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        10 => "SIGUSR1",
+        11 => "SIGSEGV",
+        12 => "SIGUSR2",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        16 => "SIGSTKFLT",
+        17 => "SIGCHLD",
+        18 => "SIGCONT",
+        19 => "SIGSTOP",
+        20 => "SIGTSTP",
+        21 => "SIGTTIN",
+        22 => "SIGTTOU",
+        23 => "SIGURG",
+        24 => "SIGXCPU",
+        25 => "SIGXFSZ",
+        26 => "SIGVTALRM",
+        27 => "SIGPROF",
+        28 => "SIGWINCH",
+        29 => "SIGIO",
+        30 => "SIGPWR",
+        31 => "SIGSYS",
+        _ => return format!("signal {signal}"),
+    };
+
+    String::from(name)
+}
+
+/// The text of a NUL padded fixed size string field.
+fn fixed_string(bytes: &[u8]) -> String {
+    let length = bytes
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..length]).into_owned()
+}
+
+impl ElfHeader {
+    fn core_notes(&self, note_type: u32) -> impl Iterator<Item = &Note> {
+        self.notes()
+            .filter(move |note| note.owner == "CORE" && note.note_type == note_type)
+    }
+
+    fn parse_process_status(&self, descriptor: &[u8]) -> Option<ProcessStatus> {
+        // only the 64-bit layout, which x86-64 and AArch64 share up to pr_reg, is decoded
+        if !matches!(self.format, BitFormat::Bit64) {
+            return Option::None;
+        }
+
+        let names = match self.header.machine {
+            EM_X86_64 => X86_64_REGISTERS.as_slice(),
+            EM_AARCH64 => AARCH64_REGISTERS.as_slice(),
+            _ => &[],
+        };
+
+        let u32_at = |offset: usize| read_value::<u32>(self.endianness, descriptor, offset);
+        let u64_at = |offset: usize| read_value::<u64>(self.endianness, descriptor, offset);
+
+        let registers = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| Some((*name, u64_at(PRSTATUS_REGISTERS + index * 8)?)))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(ProcessStatus {
+            signal: read_value::<u16>(self.endianness, descriptor, 12)?,
+            pending: u64_at(16)?,
+            held: u64_at(24)?,
+            pid: u32_at(32)?,
+            ppid: u32_at(36)?,
+            pgrp: u32_at(40)?,
+            sid: u32_at(44)?,
+            registers,
+        })
+    }
+
+    /// The `NT_PRSTATUS` of every thread in a core, the one that took the signal first.
+    pub fn process_statuses(&self) -> Vec<ProcessStatus> {
+        self.core_notes(NT_PRSTATUS)
+            .filter_map(|note| self.parse_process_status(&note.descriptor))
+            .collect()
+    }
+
+    /// The `NT_PRPSINFO` of a 64-bit core.
+    pub fn process_info(&self) -> Option<ProcessInfo> {
+        let descriptor = self.core_notes(NT_PRPSINFO).next()?.descriptor.as_slice();
+
+        if !matches!(self.format, BitFormat::Bit64) || descriptor.len() < PRPSINFO_SIZE {
+            return Option::None;
+        }
+
+        let u32_at = |offset: usize| read_value::<u32>(self.endianness, descriptor, offset);
+
+        Some(ProcessInfo {
+            state: descriptor[1] as char,
+            zombie: descriptor[2] != 0,
+            nice: descriptor[3] as i8,
+            flags: read_value::<u64>(self.endianness, descriptor, 8)?,
+            uid: u32_at(16)?,
+            gid: u32_at(20)?,
+            pid: u32_at(24)?,
+            ppid: u32_at(28)?,
+            pgrp: u32_at(32)?,
+            sid: u32_at(36)?,
+            name: fixed_string(&descriptor[40..56]),
+            arguments: fixed_string(&descriptor[56..PRPSINFO_SIZE]),
+        })
+    }
+
+    /// Splits an `NT_FILE` descriptor: the count and page size, a start, end and page offset for
+    /// each file, then their NUL terminated paths.
+    pub(super) fn parse_mapped_files(&self, descriptor: &[u8]) -> Option<MappedFiles> {
+        let word_size = self.format.address_size();
+        let word = |index: usize| match self.format {
+            BitFormat::Bit32 => {
+                read_value::<u32>(self.endianness, descriptor, index * word_size).map(u64::from)
+            }
+            BitFormat::Bit64 => read_value::<u64>(self.endianness, descriptor, index * word_size),
+        };
+
+        let count = usize::try_from(word(0)?).ok()?;
+        let page_size = word(1)?;
+        let paths_start = count
+            .checked_mul(3)?
+            .checked_add(2)?
+            .checked_mul(word_size)?;
+        let mut paths = descriptor.get(paths_start..)?.split(|byte| *byte == 0);
+
+        let mappings = (0..count)
+            .map(|index| {
+                Some(FileMapping {
+                    start: word(2 + index * 3)?,
+                    end: word(3 + index * 3)?,
+                    page_offset: word(4 + index * 3)?,
+                    path: String::from_utf8_lossy(paths.next()?).into_owned(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(MappedFiles {
+            page_size,
+            mappings,
+        })
+    }
+
+    /// The files mapped into the process, from `NT_FILE`.
+    pub fn mapped_files(&self) -> Option<MappedFiles> {
+        let note = self.core_notes(NT_FILE).next()?;
+        self.parse_mapped_files(&note.descriptor)
+    }
+
+    /// The auxiliary vector from `NT_AUXV`, up to but not including `AT_NULL`.
+    pub fn auxiliary_vector(&self) -> Vec<AuxiliaryEntry> {
+        let Some(note) = self.core_notes(NT_AUXV).next() else {
+            return Vec::new();
+        };
+
+        let word_size = self.format.address_size();
+
+        note.descriptor
+            .chunks_exact(word_size * 2)
+            .map(|pair| AuxiliaryEntry {
+                key: super::read_address(self.format, self.endianness, pair, 0),
+                value: super::read_address(self.format, self.endianness, pair, word_size),
+            })
+            .take_while(|entry| entry.key != AT_NULL)
+            .collect()
+    }
+
+    /// The bytes of the process's memory from `address` to the end of the `PT_LOAD` segment
+    /// holding it, if the core includes them.
+    pub fn memory_at(&self, address: u64) -> Option<&[u8]> {
+        let (offset, length) = self.file_range_at_vaddr(address).ok()?;
+        self.file_range(offset, length)
+    }
+
+    /// The NUL terminated string at `address` in the process's memory.
+    fn string_in_memory(&self, address: u64) -> Option<String> {
+        let memory = self.memory_at(address)?;
+        let length = memory.iter().position(|byte| *byte == 0)?;
+
+        Some(String::from_utf8_lossy(&memory[..length]).into_owned())
+    }
+
+    /// The `NT_FILE` table the way `readelf -n` prints it.
+    pub(super) fn mapped_file_lines(&self, files: &MappedFiles) -> Vec<String> {
+        let width = self.format.address_size() * 2;
+
+        let mut lines = vec![
+            format!("    Page size: {}", files.page_size),
+            format!(
+                "    {:>start$}{:>end$}{:>end$}",
+                "Start",
+                "End",
+                "Page Offset",
+                start = width + 2,
+                end = width + 4
+            ),
+        ];
+
+        for mapping in &files.mappings {
+            lines.push(format!(
+                "    {:#0w$x}  {:#0w$x}  {:#0w$x}",
+                mapping.start,
+                mapping.end,
+                mapping.page_offset,
+                w = width + 2
+            ));
+            lines.push(format!("        {}", mapping.path));
+        }
+
+        lines
+    }
+
+    fn auxiliary_value(&self, entry: &AuxiliaryEntry) -> String {
+        if auxiliary_value_is_decimal(entry.key) {
+            return entry.value.to_string();
+        }
+
+        let value = format!("{:#x}", entry.value);
+
+        match entry.key {
+            AT_PLATFORM | AT_BASE_PLATFORM | AT_EXECFN => {
+                match self.string_in_memory(entry.value) {
+                    Some(string) => format!("{value} ({string})"),
+                    Option::None => value,
+                }
+            }
+            _ => value,
+        }
+    }
+
+    /// Summarises a core dump for crash triage: the process, each thread's signal and registers
+    /// with the file the program counter is in, the auxiliary vector and the mapped files.
+    pub fn core_lines(&self) -> Vec<String> {
+        if self.header.file_type != ET_CORE {
+            return vec![String::new(), String::from("This is not a core file.")];
+        }
+
+        let mut lines = Vec::new();
+        let files = self.mapped_files();
+
+        if let Some(info) = self.process_info() {
+            lines.push(String::new());
+            lines.push(format!(
+                "Core was generated by `{}'.",
+                info.arguments.trim_end()
+            ));
+            lines.push(format!(
+                "  Name: {}  State: {}{}  PID: {}  PPID: {}  PGRP: {}  SID: {}  UID: {}  GID: {}",
+                info.name,
+                info.state,
+                if info.zombie { " (zombie)" } else { "" },
+                info.pid,
+                info.ppid,
+                info.pgrp,
+                info.sid,
+                info.uid,
+                info.gid
+            ));
+        }
+
+        let program_counter = match self.header.machine {
+            EM_X86_64 => "rip",
+            _ => "pc",
+        };
+
+        for status in self.process_statuses() {
+            lines.push(String::new());
+            lines.push(format!(
+                "Thread {}: signal {} ({})",
+                status.pid,
+                status.signal,
+                signal_name(status.signal)
+            ));
+
+            if let Some(pc) = status.register(program_counter) {
+                let location = files
+                    .as_ref()
+                    .and_then(|files| files.file_at(pc))
+                    .map(|(mapping, offset)| format!(" in {} at offset {offset:#x}", mapping.path))
+                    .unwrap_or_default();
+
+                lines.push(format!("  Stopped at {pc:#x}{location}"));
+            }
+
+            for row in status.registers.chunks(3) {
+                let registers = row
+                    .iter()
+                    .map(|(name, value)| format!("{name:<8} {value:#018x}"))
+                    .collect::<Vec<_>>();
+
+                lines.push(format!("  {}", registers.join("  ")));
+            }
+        }
+
+        let auxiliary_vector = self.auxiliary_vector();
+
+        if !auxiliary_vector.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("Auxiliary vector:"));
+
+            for entry in &auxiliary_vector {
+                lines.push(format!(
+                    "  {:<21} {}",
+                    format!("{}:", auxiliary_type_name(entry.key)),
+                    self.auxiliary_value(entry)
+                ));
+            }
+        }
+
+        if let Some(files) = files.as_ref() {
+            lines.push(String::new());
+            lines.push(String::from("Mapped files:"));
+            lines.extend(self.mapped_file_lines(files));
+        }
+
+        if lines.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from(
+                "There are no process notes in this core file.",
+            ));
+        }
+
+        lines
+    }
+
+    pub fn dump_core(&self, out: &mut dyn OutputSink) {
+        out.begin_section("core");
+
+        for line in self.core_lines() {
+            out.write_line(line.as_str());
+        }
+    }
+}
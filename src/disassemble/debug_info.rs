@@ -0,0 +1,1139 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use super::{DwarfEncoding, DwarfReader, ElfHeader, FormValue};
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+const DEBUG_INFO: &str = "debug info";
+
+/// How many references a type name or size follows before giving up, so that a cycle in a corrupt
+/// file cannot recurse forever.
+const MAXIMUM_TYPE_DEPTH: usize = 16;
+
+pub const DW_UT_COMPILE: u8 = 0x01;
+pub const DW_UT_TYPE: u8 = 0x02;
+pub const DW_UT_PARTIAL: u8 = 0x03;
+pub const DW_UT_SKELETON: u8 = 0x04;
+pub const DW_UT_SPLIT_COMPILE: u8 = 0x05;
+pub const DW_UT_SPLIT_TYPE: u8 = 0x06;
+
+pub const DW_TAG_ARRAY_TYPE: u64 = 0x01;
+pub const DW_TAG_CLASS_TYPE: u64 = 0x02;
+pub const DW_TAG_ENUMERATION_TYPE: u64 = 0x04;
+pub const DW_TAG_FORMAL_PARAMETER: u64 = 0x05;
+pub const DW_TAG_MEMBER: u64 = 0x0D;
+pub const DW_TAG_POINTER_TYPE: u64 = 0x0F;
+pub const DW_TAG_REFERENCE_TYPE: u64 = 0x10;
+pub const DW_TAG_COMPILE_UNIT: u64 = 0x11;
+pub const DW_TAG_STRUCTURE_TYPE: u64 = 0x13;
+pub const DW_TAG_SUBROUTINE_TYPE: u64 = 0x15;
+pub const DW_TAG_TYPEDEF: u64 = 0x16;
+pub const DW_TAG_UNION_TYPE: u64 = 0x17;
+pub const DW_TAG_UNSPECIFIED_PARAMETERS: u64 = 0x18;
+pub const DW_TAG_SUBRANGE_TYPE: u64 = 0x21;
+pub const DW_TAG_BASE_TYPE: u64 = 0x24;
+pub const DW_TAG_CONST_TYPE: u64 = 0x26;
+pub const DW_TAG_SUBPROGRAM: u64 = 0x2E;
+pub const DW_TAG_VARIABLE: u64 = 0x34;
+pub const DW_TAG_VOLATILE_TYPE: u64 = 0x35;
+pub const DW_TAG_RESTRICT_TYPE: u64 = 0x37;
+pub const DW_TAG_NAMESPACE: u64 = 0x39;
+pub const DW_TAG_UNSPECIFIED_TYPE: u64 = 0x3B;
+pub const DW_TAG_RVALUE_REFERENCE_TYPE: u64 = 0x42;
+pub const DW_TAG_ATOMIC_TYPE: u64 = 0x47;
+
+pub const DW_AT_NAME: u64 = 0x03;
+pub const DW_AT_BYTE_SIZE: u64 = 0x0B;
+pub const DW_AT_BIT_SIZE: u64 = 0x0D;
+pub const DW_AT_STMT_LIST: u64 = 0x10;
+pub const DW_AT_LOW_PC: u64 = 0x11;
+pub const DW_AT_HIGH_PC: u64 = 0x12;
+pub const DW_AT_LANGUAGE: u64 = 0x13;
+pub const DW_AT_COMP_DIR: u64 = 0x1B;
+pub const DW_AT_PRODUCER: u64 = 0x25;
+pub const DW_AT_UPPER_BOUND: u64 = 0x2F;
+pub const DW_AT_ABSTRACT_ORIGIN: u64 = 0x31;
+pub const DW_AT_COUNT: u64 = 0x37;
+pub const DW_AT_DATA_MEMBER_LOCATION: u64 = 0x38;
+pub const DW_AT_DECL_FILE: u64 = 0x3A;
+pub const DW_AT_DECL_LINE: u64 = 0x3B;
+pub const DW_AT_DECLARATION: u64 = 0x3C;
+pub const DW_AT_SPECIFICATION: u64 = 0x47;
+pub const DW_AT_TYPE: u64 = 0x49;
+pub const DW_AT_DATA_BIT_OFFSET: u64 = 0x6B;
+pub const DW_AT_STR_OFFSETS_BASE: u64 = 0x72;
+pub const DW_AT_ADDR_BASE: u64 = 0x73;
+
+const DW_OP_PLUS_UCONST: u8 = 0x23;
+
+/// The name of a `DW_LANG_*` source language.
+pub fn language_name(language: u64) -> String {
+    let name = match language {
+        0x01 => "C89",
+        0x02 => "C",
+        0x03 => "Ada83",
+        0x04 => "C++",
+        0x05 => "Cobol74",
+        0x06 => "Cobol85",
+        0x07 => "Fortran77",
+        0x08 => "Fortran90",
+        0x09 => "Pascal83",
+        0x0A => "Modula2",
+        0x0B => "Java",
+        0x0C => "C99",
+        0x0D => "Ada95",
+        0x0E => "Fortran95",
+        0x0F => "PLI",
+        0x10 => "ObjC",
+        0x11 => "ObjC++",
+        0x12 => "UPC",
+        0x13 => "D",
+        0x14 => "Python",
+        0x15 => "OpenCL",
+        0x16 => "Go",
+        0x17 => "Modula3",
+        0x18 => "Haskell",
+        0x19 => "C++03",
+        0x1A => "C++11",
+        0x1B => "OCaml",
+        0x1C => "Rust",
+        0x1D => "C11",
+        0x1E => "Swift",
+        0x1F => "Julia",
+        0x20 => "Dylan",
+        0x21 => "C++14",
+        0x22 => "Fortran03",
+        0x23 => "Fortran08",
+        0x24 => "RenderScript",
+        0x25 => "BLISS",
+        0x8001 => "Mips Assembler",
+        _ => return format!("{language:#x}"),
+    };
+
+    String::from(name)
+}
+
+/// One debugging information entry.
+#[derive(Clone, Debug)]
+pub struct DebugEntry {
+    /// Offset of the entry in `.debug_info`.
+    pub offset: u64,
+    pub tag: u64,
+    /// Index of the parent entry in [`CompileUnit::entries`].
+    pub parent: Option<usize>,
+    /// The attributes in file order. String and address indexes are already resolved, so names
+    /// are [`FormValue::String`] and addresses [`FormValue::Address`] whatever their form.
+    pub attributes: Vec<(u64, FormValue)>,
+}
+
+impl DebugEntry {
+    pub fn attribute(&self, name: u64) -> Option<&FormValue> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The value of a string attribute such as `DW_AT_name`.
+    pub fn string(&self, name: u64) -> Option<&str> {
+        match self.attribute(name)? {
+            FormValue::String(string) => Some(string.as_str()),
+            _ => Option::None,
+        }
+    }
+
+    pub fn unsigned(&self, name: u64) -> Option<u64> {
+        self.attribute(name)?.as_unsigned()
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.string(DW_AT_NAME)
+    }
+}
+
+/// One unit of `.debug_info` with its whole entry tree, the unit entry first and every entry
+/// before its children.
+#[derive(Clone, Debug)]
+pub struct CompileUnit {
+    /// Offset of the unit header in `.debug_info`.
+    pub offset: u64,
+    /// Offset just past the end of the unit.
+    pub end: u64,
+    pub version: u16,
+    /// A `DW_UT_*` value; units before DWARF 5 are always [`DW_UT_COMPILE`].
+    pub unit_type: u8,
+    pub address_size: u8,
+    pub entries: Vec<DebugEntry>,
+}
+
+impl CompileUnit {
+    /// The `DW_TAG_compile_unit` (or partial, skeleton or type unit) entry.
+    pub fn root(&self) -> Option<&DebugEntry> {
+        self.entries.first()
+    }
+
+    /// The indexes of the direct children of entry `index`.
+    pub fn children(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .skip(index + 1)
+            .take_while(move |(_, entry)| entry.parent.is_some_and(|parent| parent >= index))
+            .filter(move |(_, entry)| entry.parent == Some(index))
+            .map(|(child, _)| child)
+    }
+
+    /// The index of the entry at `offset` in `.debug_info`.
+    fn entry_index(&self, offset: u64) -> Option<usize> {
+        self.entries
+            .binary_search_by_key(&offset, |entry| entry.offset)
+            .ok()
+    }
+}
+
+/// A named, typed parameter of a function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugParameter {
+    pub name: String,
+    pub type_name: String,
+}
+
+/// A function with code, from a `DW_TAG_subprogram` with an address range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugFunction {
+    /// The name qualified with its enclosing namespaces and types, e.g. `nc::first_value`.
+    pub name: String,
+    pub low_pc: u64,
+    /// The first address past the end of the function.
+    pub high_pc: u64,
+    pub parameters: Vec<DebugParameter>,
+    /// `None` for functions returning nothing.
+    pub return_type: Option<String>,
+}
+
+impl DebugFunction {
+    /// The function as `name(parameter: type, ...) -> type`.
+    pub fn signature(&self) -> String {
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|parameter| format!("{}: {}", parameter.name, parameter.type_name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match self.return_type.as_ref() {
+            Some(return_type) => format!("{}({}) -> {}", self.name, parameters, return_type),
+            Option::None => format!("{}({})", self.name, parameters),
+        }
+    }
+}
+
+/// A data member of a structure, class or union.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructMember {
+    pub name: String,
+    pub type_name: String,
+    /// Byte offset from the start of the enclosing type.
+    pub offset: u64,
+    /// Size of the member's type, when the type has one.
+    pub size: Option<u64>,
+    /// For bit fields, the width in bits and the offset of the first bit within the byte at
+    /// `offset`.
+    pub bits: Option<(u64, u64)>,
+}
+
+impl StructMember {
+    /// How many bytes the member touches, from `offset` on.
+    fn extent(&self) -> u64 {
+        match self.bits {
+            Some((width, first)) => (first + width).div_ceil(8),
+            Option::None => self.size.unwrap_or(0),
+        }
+    }
+}
+
+/// The memory layout of a structure, class or union.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructLayout {
+    /// `struct`, `class` or `union`.
+    pub kind: &'static str,
+    /// The name qualified with its enclosing namespaces and types.
+    pub name: String,
+    pub size: Option<u64>,
+    /// Where the type is declared, as `path:line`.
+    pub declared_at: Option<String>,
+    /// The members ordered by offset.
+    pub members: Vec<StructMember>,
+}
+
+/// An entry of an abbreviation table: the tag and attribute forms of the entries that use its code.
+struct Abbreviation {
+    tag: u64,
+    has_children: bool,
+    /// Attribute name, form and, for `DW_FORM_implicit_const`, the value.
+    attributes: Vec<(u64, u64, i64)>,
+}
+
+/// The debug sections that hold what `.debug_info` attributes point into.
+struct StringSections<'a> {
+    strings: Option<&'a [u8]>,
+    line_strings: Option<&'a [u8]>,
+    string_offsets: Option<&'a [u8]>,
+    addresses: Option<&'a [u8]>,
+}
+
+fn kind_name(tag: u64) -> &'static str {
+    match tag {
+        DW_TAG_CLASS_TYPE => "class",
+        DW_TAG_UNION_TYPE => "union",
+        _ => "struct",
+    }
+}
+
+fn is_aggregate(tag: u64) -> bool {
+    matches!(
+        tag,
+        DW_TAG_STRUCTURE_TYPE | DW_TAG_CLASS_TYPE | DW_TAG_UNION_TYPE
+    )
+}
+
+impl ElfHeader {
+    /// Reads the abbreviation table at `offset` in `.debug_abbrev`, keyed by code.
+    fn parse_abbreviations(&self, data: &[u8], offset: u64) -> Result<HashMap<u64, Abbreviation>> {
+        let position = usize::try_from(offset)
+            .ok()
+            .filter(|position| *position < data.len())
+            .ok_or(Error::OutOfRange {
+                what: "abbreviation table offset",
+                offset,
+                limit: data.len() as u64,
+            })?;
+
+        let mut reader = DwarfReader::new(data, position, self.endianness);
+        let mut abbreviations = HashMap::new();
+
+        loop {
+            let code = reader.uleb128()?;
+
+            if code == 0 {
+                return Ok(abbreviations);
+            }
+
+            let tag = reader.uleb128()?;
+            let has_children = reader.u8()? != 0;
+            let mut attributes = Vec::new();
+
+            loop {
+                let name = reader.uleb128()?;
+                let form = reader.uleb128()?;
+
+                if name == 0 && form == 0 {
+                    break;
+                }
+
+                let implicit = if form == super::DW_FORM_IMPLICIT_CONST {
+                    reader.sleb128()?
+                } else {
+                    0
+                };
+
+                attributes.push((name, form, implicit));
+            }
+
+            abbreviations.insert(
+                code,
+                Abbreviation {
+                    tag,
+                    has_children,
+                    attributes,
+                },
+            );
+        }
+    }
+
+    /// Replaces string offsets and indexes with the strings, and address indexes with the
+    /// addresses, using the bases given by the unit entry.
+    fn resolve_unit_values(
+        &self,
+        entries: &mut [DebugEntry],
+        encoding: DwarfEncoding,
+        sections: &StringSections,
+    ) {
+        let header_size = 2 * encoding.offset_size as u64;
+        let root = entries.first();
+        let string_offsets_base = root
+            .and_then(|root| root.unsigned(DW_AT_STR_OFFSETS_BASE))
+            .unwrap_or(header_size);
+        let address_base = root
+            .and_then(|root| root.unsigned(DW_AT_ADDR_BASE))
+            .unwrap_or(header_size);
+
+        let read = |data: Option<&[u8]>, offset: u64, size: u8| {
+            let data = data?;
+            let offset = usize::try_from(offset).ok()?;
+            DwarfReader::new(data, offset, self.endianness)
+                .sized(size)
+                .ok()
+        };
+
+        let string = |data: Option<&[u8]>, offset: u64| {
+            let data = data?;
+            let offset = usize::try_from(offset).ok()?;
+            DwarfReader::new(data, offset, self.endianness)
+                .string()
+                .ok()
+        };
+
+        for entry in entries.iter_mut() {
+            for (_, value) in entry.attributes.iter_mut() {
+                let resolved = match value {
+                    FormValue::StringOffset(offset) => string(sections.strings, *offset),
+                    FormValue::LineStringOffset(offset) => string(sections.line_strings, *offset),
+                    FormValue::StringIndex(index) => index
+                        .checked_mul(encoding.offset_size as u64)
+                        .and_then(|position| position.checked_add(string_offsets_base))
+                        .and_then(|position| {
+                            read(sections.string_offsets, position, encoding.offset_size)
+                        })
+                        .and_then(|offset| string(sections.strings, offset)),
+                    FormValue::AddressIndex(index) => {
+                        let address = index
+                            .checked_mul(encoding.address_size as u64)
+                            .and_then(|position| position.checked_add(address_base))
+                            .and_then(|position| {
+                                read(sections.addresses, position, encoding.address_size)
+                            });
+
+                        if let Some(address) = address {
+                            *value = FormValue::Address(address);
+                        }
+
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                if let Some(resolved) = resolved {
+                    *value = FormValue::String(resolved);
+                }
+            }
+        }
+    }
+
+    /// Parses the unit whose header starts at `offset`, returning it together with the offset of
+    /// the next unit.
+    fn parse_compile_unit(
+        &self,
+        data: &[u8],
+        offset: usize,
+        abbreviation_data: &[u8],
+        abbreviation_tables: &mut HashMap<u64, HashMap<u64, Abbreviation>>,
+        sections: &StringSections,
+    ) -> Result<(CompileUnit, usize)> {
+        let mut reader = DwarfReader::new(data, offset, self.endianness);
+        let (length, offset_size) = reader.unit_length()?;
+        let end = reader
+            .position()
+            .checked_add(usize::try_from(length).unwrap_or(usize::MAX))
+            .filter(|end| *end <= data.len())
+            .ok_or(Error::Truncated {
+                what: "compilation unit",
+                offset: offset as u64,
+                needed: length,
+                available: data.len() as u64,
+            })?;
+        let mut reader = reader.limited(end);
+
+        let version = reader.u16()?;
+
+        if !(2..=5).contains(&version) {
+            return Err(Error::InvalidValue {
+                what: "compilation unit version",
+                value: version as u64,
+            });
+        }
+
+        let (unit_type, address_size, abbreviation_offset) = if version >= 5 {
+            let unit_type = reader.u8()?;
+            let address_size = reader.u8()?;
+            let abbreviation_offset = reader.sized(offset_size)?;
+
+            match unit_type {
+                DW_UT_SKELETON | DW_UT_SPLIT_COMPILE => {
+                    let _dwo_id = reader.u64()?;
+                }
+                DW_UT_TYPE | DW_UT_SPLIT_TYPE => {
+                    let _type_signature = reader.u64()?;
+                    let _type_offset = reader.sized(offset_size)?;
+                }
+                _ => {}
+            }
+
+            (unit_type, address_size, abbreviation_offset)
+        } else {
+            let abbreviation_offset = reader.sized(offset_size)?;
+            (DW_UT_COMPILE, reader.u8()?, abbreviation_offset)
+        };
+
+        let encoding = DwarfEncoding {
+            version,
+            offset_size,
+            address_size,
+        };
+
+        // units of one object often share a table, so each is only parsed once
+        let abbreviations = match abbreviation_tables.entry(abbreviation_offset) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(self.parse_abbreviations(abbreviation_data, abbreviation_offset)?)
+            }
+        };
+        let mut entries: Vec<DebugEntry> = Vec::new();
+        // the entries whose children are being read, innermost last
+        let mut parents: Vec<usize> = Vec::new();
+
+        while !reader.is_at_end() {
+            let entry_offset = reader.position() as u64;
+            let code = reader.uleb128()?;
+
+            if code == 0 {
+                parents.pop();
+                continue;
+            }
+
+            let abbreviation = abbreviations.get(&code).ok_or(Error::InvalidValue {
+                what: "abbreviation code",
+                value: code,
+            })?;
+
+            let mut attributes = Vec::new();
+
+            for (name, form, implicit) in &abbreviation.attributes {
+                let value = if *form == super::DW_FORM_IMPLICIT_CONST {
+                    FormValue::Signed(*implicit)
+                } else {
+                    reader.form(*form, encoding)?
+                };
+
+                attributes.push((*name, value));
+            }
+
+            entries.push(DebugEntry {
+                offset: entry_offset,
+                tag: abbreviation.tag,
+                parent: parents.last().copied(),
+                attributes,
+            });
+
+            if abbreviation.has_children {
+                parents.push(entries.len() - 1);
+            }
+        }
+
+        self.resolve_unit_values(&mut entries, encoding, sections);
+
+        let unit = CompileUnit {
+            offset: offset as u64,
+            end: end as u64,
+            version,
+            unit_type,
+            address_size,
+            entries,
+        };
+
+        Ok((unit, end))
+    }
+
+    fn parse_debug_info(&self) -> (Vec<CompileUnit>, Vec<Diagnostic>) {
+        let Some(data) = self.debug_section_data(".debug_info") else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let abbreviation_data = self.debug_section_data(".debug_abbrev").unwrap_or_default();
+        let strings = self.debug_section_data(".debug_str");
+        let line_strings = self.debug_section_data(".debug_line_str");
+        let string_offsets = self.debug_section_data(".debug_str_offsets");
+        let addresses = self.debug_section_data(".debug_addr");
+
+        let sections = StringSections {
+            strings: strings.as_deref(),
+            line_strings: line_strings.as_deref(),
+            string_offsets: string_offsets.as_deref(),
+            addresses: addresses.as_deref(),
+        };
+
+        let mut abbreviation_tables = HashMap::new();
+        let mut units = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut offset = 0;
+
+        while offset < data.len() {
+            match self.parse_compile_unit(
+                &data,
+                offset,
+                &abbreviation_data,
+                &mut abbreviation_tables,
+                &sections,
+            ) {
+                Ok((unit, next)) => {
+                    units.push(unit);
+                    offset = next;
+                }
+                Err(error) => {
+                    diagnostics.push(Diagnostic {
+                        table: DEBUG_INFO,
+                        index: Some(units.len()),
+                        error,
+                    });
+                    break;
+                }
+            }
+        }
+
+        (units, diagnostics)
+    }
+
+    /// Every unit parsed from `.debug_info`, which is parsed the first time this is called.
+    pub fn compile_units(&self) -> &[CompileUnit] {
+        &self.compile_units.get_or_init(|| self.parse_debug_info()).0
+    }
+
+    /// The compilation directory of the unit whose line table starts at `offset` in `.debug_line`.
+    pub(super) fn compilation_directory(&self, line_table_offset: u64) -> Option<&str> {
+        self.compile_units()
+            .iter()
+            .filter_map(CompileUnit::root)
+            .find(|root| root.unsigned(DW_AT_STMT_LIST) == Some(line_table_offset))?
+            .string(DW_AT_COMP_DIR)
+    }
+
+    /// The unit and entry index a reference attribute of `unit` points at.
+    fn referenced_entry(
+        &self,
+        unit: &CompileUnit,
+        value: &FormValue,
+    ) -> Option<(&CompileUnit, usize)> {
+        let offset = match value {
+            FormValue::Reference(offset) => unit.offset.checked_add(*offset)?,
+            FormValue::ReferenceAddress(offset) => *offset,
+            _ => return Option::None,
+        };
+
+        let target = self
+            .compile_units()
+            .iter()
+            .find(|candidate| candidate.offset <= offset && offset < candidate.end)?;
+
+        Some((target, target.entry_index(offset)?))
+    }
+
+    /// Follows `DW_AT_specification` and `DW_AT_abstract_origin` from an entry to the one that
+    /// holds its name and type, as out-of-line definitions and inlined copies leave those out.
+    fn declaration_of<'a>(
+        &'a self,
+        mut unit: &'a CompileUnit,
+        mut index: usize,
+    ) -> (&'a CompileUnit, usize) {
+        for _ in 0..MAXIMUM_TYPE_DEPTH {
+            let entry = &unit.entries[index];
+
+            if entry.name().is_some() {
+                break;
+            }
+
+            let Some(next) = entry
+                .attribute(DW_AT_SPECIFICATION)
+                .or_else(|| entry.attribute(DW_AT_ABSTRACT_ORIGIN))
+                .and_then(|value| self.referenced_entry(unit, value))
+            else {
+                break;
+            };
+
+            (unit, index) = next;
+        }
+
+        (unit, index)
+    }
+
+    /// The entry's name prefixed with those of the namespaces and types it is nested in.
+    fn qualified_name(&self, unit: &CompileUnit, index: usize) -> Option<String> {
+        let (unit, index) = self.declaration_of(unit, index);
+        let mut parts = vec![unit.entries[index].name()?];
+        let mut parent = unit.entries[index].parent;
+
+        while let Some(current) = parent {
+            let entry = &unit.entries[current];
+
+            if entry.tag == DW_TAG_NAMESPACE || is_aggregate(entry.tag) {
+                parts.push(entry.name().unwrap_or("{anonymous}"));
+            }
+
+            parent = entry.parent;
+        }
+
+        parts.reverse();
+        Some(parts.join("::"))
+    }
+
+    /// The name of the type `value` refers to, in C-like syntax; `void` when there is none.
+    fn type_name(&self, unit: &CompileUnit, value: Option<&FormValue>, depth: usize) -> String {
+        let Some((unit, index)) = value.and_then(|value| self.referenced_entry(unit, value)) else {
+            return String::from("void");
+        };
+
+        if depth >= MAXIMUM_TYPE_DEPTH {
+            return String::from("...");
+        }
+
+        let entry = &unit.entries[index];
+        let inner = || self.type_name(unit, entry.attribute(DW_AT_TYPE), depth + 1);
+
+        match entry.tag {
+            DW_TAG_POINTER_TYPE | DW_TAG_REFERENCE_TYPE | DW_TAG_RVALUE_REFERENCE_TYPE
+                if entry.name().is_some() =>
+            {
+                // Rust names its pointers, e.g. *const u8 or &str
+                String::from(entry.name().unwrap_or_default())
+            }
+            DW_TAG_POINTER_TYPE => {
+                let inner = inner();
+
+                if inner.ends_with('*') {
+                    format!("{inner}*")
+                } else {
+                    format!("{inner} *")
+                }
+            }
+            DW_TAG_REFERENCE_TYPE => format!("{} &", inner()),
+            DW_TAG_RVALUE_REFERENCE_TYPE => format!("{} &&", inner()),
+            DW_TAG_CONST_TYPE => format!("const {}", inner()),
+            DW_TAG_VOLATILE_TYPE => format!("volatile {}", inner()),
+            DW_TAG_RESTRICT_TYPE => format!("{} restrict", inner()),
+            DW_TAG_ATOMIC_TYPE => format!("_Atomic {}", inner()),
+            DW_TAG_ARRAY_TYPE => {
+                let mut name = inner();
+
+                for child in unit.children(index) {
+                    let subrange = &unit.entries[child];
+
+                    if subrange.tag != DW_TAG_SUBRANGE_TYPE {
+                        continue;
+                    }
+
+                    match ElfHeader::subrange_count(subrange) {
+                        Some(count) => name.push_str(&format!("[{count}]")),
+                        Option::None => name.push_str("[]"),
+                    }
+                }
+
+                name
+            }
+            DW_TAG_SUBROUTINE_TYPE => {
+                let parameters = unit
+                    .children(index)
+                    .filter_map(|child| {
+                        let parameter = &unit.entries[child];
+
+                        match parameter.tag {
+                            DW_TAG_FORMAL_PARAMETER => Some(self.type_name(
+                                unit,
+                                parameter.attribute(DW_AT_TYPE),
+                                depth + 1,
+                            )),
+                            DW_TAG_UNSPECIFIED_PARAMETERS => Some(String::from("...")),
+                            _ => Option::None,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                format!("{} ({})", inner(), parameters)
+            }
+            _ => self
+                .qualified_name(unit, index)
+                .unwrap_or_else(|| String::from("{anonymous}")),
+        }
+    }
+
+    /// The element count of a `DW_TAG_subrange_type`.
+    fn subrange_count(subrange: &DebugEntry) -> Option<u64> {
+        subrange
+            .unsigned(DW_AT_COUNT)
+            .or_else(|| match subrange.attribute(DW_AT_UPPER_BOUND)? {
+                FormValue::Signed(-1) => Some(0),
+                bound => bound.as_unsigned()?.checked_add(1),
+            })
+    }
+
+    /// The size in bytes of the type `value` refers to.
+    fn type_size(
+        &self,
+        unit: &CompileUnit,
+        value: Option<&FormValue>,
+        depth: usize,
+    ) -> Option<u64> {
+        let (unit, index) = self.referenced_entry(unit, value?)?;
+
+        if depth >= MAXIMUM_TYPE_DEPTH {
+            return Option::None;
+        }
+
+        let entry = &unit.entries[index];
+
+        if let Some(size) = entry.unsigned(DW_AT_BYTE_SIZE) {
+            return Some(size);
+        }
+
+        match entry.tag {
+            DW_TAG_POINTER_TYPE | DW_TAG_REFERENCE_TYPE | DW_TAG_RVALUE_REFERENCE_TYPE => {
+                Some(unit.address_size as u64)
+            }
+            DW_TAG_ARRAY_TYPE => {
+                let mut size = self.type_size(unit, entry.attribute(DW_AT_TYPE), depth + 1)?;
+
+                for child in unit.children(index) {
+                    if unit.entries[child].tag == DW_TAG_SUBRANGE_TYPE {
+                        size =
+                            size.checked_mul(ElfHeader::subrange_count(&unit.entries[child])?)?;
+                    }
+                }
+
+                Some(size)
+            }
+            DW_TAG_TYPEDEF | DW_TAG_CONST_TYPE | DW_TAG_VOLATILE_TYPE | DW_TAG_RESTRICT_TYPE
+            | DW_TAG_ATOMIC_TYPE => self.type_size(unit, entry.attribute(DW_AT_TYPE), depth + 1),
+            _ => Option::None,
+        }
+    }
+
+    /// `path:line` for an entry's `DW_AT_decl_file` and `DW_AT_decl_line`, resolved through the
+    /// unit's line table.
+    fn declared_at(&self, unit: &CompileUnit, entry: &DebugEntry) -> Option<String> {
+        let line = entry.unsigned(DW_AT_DECL_LINE)?;
+        let file = entry.unsigned(DW_AT_DECL_FILE)?;
+        let line_table = unit.root()?.unsigned(DW_AT_STMT_LIST)?;
+        let path = self
+            .line_programs()
+            .iter()
+            .find(|program| program.offset == line_table)?
+            .file_path(file)?;
+
+        Some(format!("{path}:{line}"))
+    }
+
+    /// The functions of one unit that have code, in entry order.
+    fn unit_functions(&self, unit: &CompileUnit) -> Vec<DebugFunction> {
+        let mut functions = Vec::new();
+
+        for (index, entry) in unit.entries.iter().enumerate() {
+            if entry.tag != DW_TAG_SUBPROGRAM {
+                continue;
+            }
+
+            let Some(FormValue::Address(low_pc)) = entry.attribute(DW_AT_LOW_PC) else {
+                continue;
+            };
+
+            // DWARF 4 made DW_AT_high_pc a length unless it has an address form
+            let high_pc = match entry.attribute(DW_AT_HIGH_PC) {
+                Some(FormValue::Address(high_pc)) => *high_pc,
+                Some(length) => low_pc.wrapping_add(length.as_unsigned().unwrap_or(0)),
+                Option::None => *low_pc,
+            };
+
+            let (declaration_unit, declaration) = self.declaration_of(unit, index);
+            let declaration = &declaration_unit.entries[declaration];
+            let return_type = declaration
+                .attribute(DW_AT_TYPE)
+                .map(|value| self.type_name(declaration_unit, Some(value), 0));
+
+            let parameters = unit
+                .children(index)
+                .filter(|child| unit.entries[*child].tag == DW_TAG_FORMAL_PARAMETER)
+                .map(|child| {
+                    let (unit, child) = self.declaration_of(unit, child);
+                    let parameter = &unit.entries[child];
+
+                    DebugParameter {
+                        name: String::from(parameter.name().unwrap_or("_")),
+                        type_name: self.type_name(unit, parameter.attribute(DW_AT_TYPE), 0),
+                    }
+                })
+                .collect();
+
+            functions.push(DebugFunction {
+                name: self
+                    .qualified_name(unit, index)
+                    .unwrap_or_else(|| String::from("{anonymous}")),
+                low_pc: *low_pc,
+                high_pc,
+                parameters,
+                return_type,
+            });
+        }
+
+        functions
+    }
+
+    /// Every function with code described by `.debug_info`.
+    pub fn debug_functions(&self) -> Vec<DebugFunction> {
+        self.compile_units()
+            .iter()
+            .flat_map(|unit| self.unit_functions(unit))
+            .collect()
+    }
+
+    fn layout_of(&self, unit: &CompileUnit, index: usize) -> StructLayout {
+        let entry = &unit.entries[index];
+        let mut members = Vec::new();
+
+        for child in unit.children(index) {
+            let member = &unit.entries[child];
+
+            if member.tag != DW_TAG_MEMBER {
+                continue;
+            }
+
+            let type_value = member.attribute(DW_AT_TYPE);
+            let bit_size = member.unsigned(DW_AT_BIT_SIZE);
+
+            let (offset, bits) = match (member.unsigned(DW_AT_DATA_BIT_OFFSET), bit_size) {
+                (Some(bit_offset), width) => {
+                    (bit_offset / 8, Some((width.unwrap_or(1), bit_offset % 8)))
+                }
+                (Option::None, width) => {
+                    let offset = match member.attribute(DW_AT_DATA_MEMBER_LOCATION) {
+                        // DWARF 2 only had location expressions, which compilers filled with
+                        // DW_OP_plus_uconst
+                        Some(FormValue::Block(expression) | FormValue::Expression(expression))
+                            if expression.first() == Some(&DW_OP_PLUS_UCONST) =>
+                        {
+                            DwarfReader::new(expression, 1, self.endianness)
+                                .uleb128()
+                                .unwrap_or(0)
+                        }
+                        Some(value) => value.as_unsigned().unwrap_or(0),
+                        Option::None => 0,
+                    };
+
+                    (offset, width.map(|width| (width, 0)))
+                }
+            };
+
+            members.push(StructMember {
+                name: String::from(member.name().unwrap_or("{anonymous}")),
+                type_name: self.type_name(unit, type_value, 0),
+                offset,
+                size: self.type_size(unit, type_value, 0),
+                bits,
+            });
+        }
+
+        members.sort_by_key(|member| member.offset);
+
+        StructLayout {
+            kind: kind_name(entry.tag),
+            name: self
+                .qualified_name(unit, index)
+                .unwrap_or_else(|| String::from("{anonymous}")),
+            size: entry.unsigned(DW_AT_BYTE_SIZE),
+            declared_at: self.declared_at(unit, entry),
+            members,
+        }
+    }
+
+    /// The layouts of the structures, classes and unions defined in one unit.
+    fn unit_types(&self, unit: &CompileUnit) -> Vec<StructLayout> {
+        unit.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                is_aggregate(entry.tag) && entry.attribute(DW_AT_DECLARATION).is_none()
+            })
+            .map(|(index, _)| self.layout_of(unit, index))
+            .collect()
+    }
+
+    /// The layout of the structure, class or union called `name`, which may be qualified with
+    /// its namespaces like `nc::MyStruct`. The first definition found wins.
+    pub fn struct_layout(&self, name: &str) -> Option<StructLayout> {
+        self.compile_units().iter().find_map(|unit| {
+            unit.entries
+                .iter()
+                .enumerate()
+                .find(|(index, entry)| {
+                    is_aggregate(entry.tag)
+                        && entry.attribute(DW_AT_DECLARATION).is_none()
+                        && (entry.name() == Some(name)
+                            || self.qualified_name(unit, *index).as_deref() == Some(name))
+                })
+                .map(|(index, _)| self.layout_of(unit, index))
+        })
+    }
+
+    /// The compilation units with their functions and types, like a condensed
+    /// `llvm-dwarfdump --debug-info`.
+    pub fn debug_info_lines(&self) -> Vec<String> {
+        if self.compile_units().is_empty() {
+            return vec![
+                String::new(),
+                String::from("There is no .debug_info section in this file."),
+            ];
+        }
+
+        let width = self.format.address_size() * 2;
+        let mut lines = Vec::new();
+
+        for unit in self.compile_units() {
+            lines.push(String::new());
+            lines.push(format!(
+                "Compilation unit at offset {:#x} (DWARF {}):",
+                unit.offset, unit.version
+            ));
+
+            if let Some(root) = unit.root() {
+                let fields = [
+                    ("Name:", root.name().map(String::from)),
+                    ("Producer:", root.string(DW_AT_PRODUCER).map(String::from)),
+                    (
+                        "Language:",
+                        root.unsigned(DW_AT_LANGUAGE).map(language_name),
+                    ),
+                    ("Directory:", root.string(DW_AT_COMP_DIR).map(String::from)),
+                ];
+
+                for (label, value) in fields {
+                    if let Some(value) = value {
+                        lines.push(format!("  {label:<11}{value}"));
+                    }
+                }
+            }
+
+            let functions = self.unit_functions(unit);
+
+            if !functions.is_empty() {
+                lines.push(String::from("  Functions:"));
+            }
+
+            for function in functions {
+                lines.push(format!(
+                    "    0x{:0w$x}-0x{:0w$x}  {}",
+                    function.low_pc,
+                    function.high_pc,
+                    function.signature(),
+                    w = width
+                ));
+            }
+
+            let types = self.unit_types(unit);
+
+            if !types.is_empty() {
+                lines.push(String::from("  Types:"));
+            }
+
+            for layout in types {
+                let size = match layout.size {
+                    Some(1) => String::from(" (1 byte)"),
+                    Some(size) => format!(" ({size} bytes)"),
+                    Option::None => String::new(),
+                };
+
+                lines.push(format!("    {} {}{}", layout.kind, layout.name, size));
+            }
+        }
+
+        lines
+    }
+
+    pub fn dump_debug_info(&self, out: &mut dyn OutputSink) {
+        out.begin_section("debug info");
+
+        for line in self.debug_info_lines() {
+            out.write_line(line.as_str());
+        }
+    }
+
+    /// The layout of the type called `name`, one member per line with its offset and size and
+    /// the padding between members, to lay over a hex dump of an instance.
+    pub fn struct_layout_lines(&self, name: &str) -> Result<Vec<String>> {
+        let layout = self
+            .struct_layout(name)
+            .ok_or_else(|| Error::TypeNotFound(String::from(name)))?;
+
+        let mut heading = format!("{} {}", layout.kind, layout.name);
+
+        if let Some(size) = layout.size {
+            heading.push_str(&format!(", {size} bytes"));
+        }
+
+        if let Some(declared_at) = layout.declared_at.as_ref() {
+            heading.push_str(&format!(", declared at {declared_at}"));
+        }
+
+        let type_width = layout
+            .members
+            .iter()
+            .map(|member| member.type_name.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+
+        let mut lines = vec![
+            String::new(),
+            heading,
+            format!("  Offset  Size  {:<tw$}  Name", "Type", tw = type_width),
+        ];
+
+        let padding = |lines: &mut Vec<String>, from: u64, to: u64| {
+            if to > from {
+                lines.push(format!("  {:#06x} {:>5}  <padding>", from, to - from));
+            }
+        };
+
+        let mut end = 0;
+
+        for member in &layout.members {
+            padding(&mut lines, end, member.offset);
+
+            let size = member
+                .size
+                .map(|size| size.to_string())
+                .unwrap_or_else(|| String::from("?"));
+            let name = match member.bits {
+                Some((width, 0)) => format!("{}:{}", member.name, width),
+                Some((width, first)) => format!("{}:{} (from bit {})", member.name, width, first),
+                Option::None => member.name.clone(),
+            };
+
+            lines.push(format!(
+                "  {:#06x} {:>5}  {:<tw$}  {}",
+                member.offset,
+                size,
+                member.type_name,
+                name,
+                tw = type_width
+            ));
+
+            // union members all start at 0, so only ever move the end forward
+            end = end.max(member.offset.saturating_add(member.extent()));
+        }
+
+        if let Some(size) = layout.size {
+            padding(&mut lines, end, size);
+        }
+
+        Ok(lines)
+    }
+
+    pub fn dump_struct_layout(&self, name: &str, out: &mut dyn OutputSink) -> Result<()> {
+        let lines = self.struct_layout_lines(name)?;
+        out.begin_section("type layout");
+
+        for line in lines {
+            out.write_line(line.as_str());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,507 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+use super::{DwarfEncoding, DwarfReader, ElfHeader};
+use crate::error::{Diagnostic, Error, Result};
+
+const LINE_TABLE: &str = "line table";
+
+pub const DW_LNS_COPY: u8 = 0x01;
+pub const DW_LNS_ADVANCE_PC: u8 = 0x02;
+pub const DW_LNS_ADVANCE_LINE: u8 = 0x03;
+pub const DW_LNS_SET_FILE: u8 = 0x04;
+pub const DW_LNS_SET_COLUMN: u8 = 0x05;
+pub const DW_LNS_NEGATE_STMT: u8 = 0x06;
+pub const DW_LNS_SET_BASIC_BLOCK: u8 = 0x07;
+pub const DW_LNS_CONST_ADD_PC: u8 = 0x08;
+pub const DW_LNS_FIXED_ADVANCE_PC: u8 = 0x09;
+pub const DW_LNS_SET_PROLOGUE_END: u8 = 0x0A;
+pub const DW_LNS_SET_EPILOGUE_BEGIN: u8 = 0x0B;
+pub const DW_LNS_SET_ISA: u8 = 0x0C;
+
+pub const DW_LNE_END_SEQUENCE: u8 = 0x01;
+pub const DW_LNE_SET_ADDRESS: u8 = 0x02;
+pub const DW_LNE_DEFINE_FILE: u8 = 0x03;
+pub const DW_LNE_SET_DISCRIMINATOR: u8 = 0x04;
+
+pub const DW_LNCT_PATH: u64 = 0x1;
+pub const DW_LNCT_DIRECTORY_INDEX: u64 = 0x2;
+
+/// An entry of a line table's file name table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineFile {
+    pub name: String,
+    /// Index into [`LineProgram::directories`].
+    pub directory: u64,
+}
+
+/// One row of the line number matrix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineRow {
+    pub address: u64,
+    /// Index into the file name table; 1-based before DWARF 5, 0-based from it on.
+    pub file: u64,
+    pub line: u64,
+    pub column: u64,
+    pub is_statement: bool,
+    /// The first address past the end of a sequence; the row's other fields mean nothing.
+    pub end_sequence: bool,
+}
+
+/// One unit of `.debug_line`: its header tables and the decoded rows.
+#[derive(Clone, Debug)]
+pub struct LineProgram {
+    /// Offset of the unit in `.debug_line`.
+    pub offset: u64,
+    pub version: u16,
+    /// The include directories. Entry 0 is the compilation directory, which DWARF 4 and earlier
+    /// leave out of the line table, so for them it comes from the unit's `DW_AT_comp_dir` and is
+    /// empty without `.debug_info`.
+    pub directories: Vec<String>,
+    pub files: Vec<LineFile>,
+    pub rows: Vec<LineRow>,
+}
+
+impl LineProgram {
+    /// The path of file `index`, joined with its directory and the compilation directory.
+    pub fn file_path(&self, index: u64) -> Option<String> {
+        let index = if self.version >= 5 {
+            index
+        } else {
+            index.checked_sub(1)?
+        };
+
+        let file = self.files.get(usize::try_from(index).ok()?)?;
+        let directory = usize::try_from(file.directory)
+            .ok()
+            .and_then(|directory| self.directories.get(directory))
+            .map(String::as_str)
+            .unwrap_or_default();
+        let compilation_directory = self.directories.first().map(String::as_str);
+
+        let mut path = String::new();
+
+        for part in [
+            compilation_directory.unwrap_or_default(),
+            directory,
+            &file.name,
+        ] {
+            if part.is_empty() {
+                continue;
+            }
+
+            if part.starts_with('/') {
+                path.clear();
+            } else if !path.is_empty() && !path.ends_with('/') {
+                path.push('/');
+            }
+
+            path.push_str(part);
+        }
+
+        Some(path)
+    }
+}
+
+/// The source position an address was compiled from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub path: String,
+    pub line: u64,
+    pub column: u64,
+}
+
+/// Source files read from disk on demand, so each is read once however many of its lines are shown.
+#[derive(Default)]
+pub struct SourceFiles {
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl SourceFiles {
+    /// The text of the line at `location`, or `None` if its file cannot be read or is too short.
+    pub fn line(&mut self, location: &SourceLocation) -> Option<&str> {
+        let lines = self
+            .files
+            .entry(location.path.clone())
+            .or_insert_with(|| {
+                // only regular files, so a path like /dev/zero from a corrupt table cannot hang us
+                let is_file = fs::metadata(&location.path).is_ok_and(|metadata| metadata.is_file());
+
+                is_file
+                    .then(|| fs::read(&location.path).ok())
+                    .flatten()
+                    .map(|bytes| {
+                        String::from_utf8_lossy(&bytes)
+                            .lines()
+                            .map(String::from)
+                            .collect()
+                    })
+            })
+            .as_ref()?;
+
+        let index = usize::try_from(location.line.checked_sub(1)?).ok()?;
+        lines.get(index).map(String::as_str)
+    }
+}
+
+/// The line number state machine registers, as laid out in DWARF 5 section 6.2.2.
+struct LineState {
+    address: u64,
+    op_index: u64,
+    file: u64,
+    line: u64,
+    column: u64,
+    is_statement: bool,
+}
+
+impl LineState {
+    fn new(default_is_statement: bool) -> LineState {
+        LineState {
+            address: 0,
+            op_index: 0,
+            file: 1,
+            line: 1,
+            column: 0,
+            is_statement: default_is_statement,
+        }
+    }
+
+    fn row(&self, end_sequence: bool) -> LineRow {
+        LineRow {
+            address: self.address,
+            file: self.file,
+            line: self.line,
+            column: self.column,
+            is_statement: self.is_statement,
+            end_sequence,
+        }
+    }
+}
+
+impl ElfHeader {
+    /// Reads one DWARF 5 directory or file name table: a list of content type and form pairs, then
+    /// the entries laid out in that format.
+    fn parse_entry_table(
+        &self,
+        reader: &mut DwarfReader,
+        encoding: DwarfEncoding,
+    ) -> Result<Vec<LineFile>> {
+        let format_count = reader.u8()?;
+        let mut format = Vec::new();
+
+        for _ in 0..format_count {
+            format.push((reader.uleb128()?, reader.uleb128()?));
+        }
+
+        let count = reader.uleb128()?;
+        let mut entries = Vec::new();
+
+        for _ in 0..count {
+            let mut entry = LineFile {
+                name: String::new(),
+                directory: 0,
+            };
+
+            for (content_type, form) in &format {
+                let value = reader.form(*form, encoding)?;
+
+                match *content_type {
+                    DW_LNCT_PATH => entry.name = self.dwarf_string(&value).unwrap_or_default(),
+                    DW_LNCT_DIRECTORY_INDEX => entry.directory = value.as_unsigned().unwrap_or(0),
+                    _ => {}
+                }
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Parses the line number program whose unit starts at `offset`, returning it together with
+    /// the offset of the next unit.
+    fn parse_line_program(&self, data: &[u8], offset: usize) -> Result<(LineProgram, usize)> {
+        let mut reader = DwarfReader::new(data, offset, self.endianness);
+        let (length, offset_size) = reader.unit_length()?;
+        let end = reader
+            .position()
+            .checked_add(usize::try_from(length).unwrap_or(usize::MAX))
+            .filter(|end| *end <= data.len())
+            .ok_or(Error::Truncated {
+                what: "line table",
+                offset: offset as u64,
+                needed: length,
+                available: data.len() as u64,
+            })?;
+        let mut reader = reader.limited(end);
+
+        let version = reader.u16()?;
+
+        if !(2..=5).contains(&version) {
+            return Err(Error::InvalidValue {
+                what: "line table version",
+                value: version as u64,
+            });
+        }
+
+        let mut encoding = DwarfEncoding {
+            version,
+            offset_size,
+            address_size: self.format.address_size() as u8,
+        };
+
+        if version >= 5 {
+            encoding.address_size = reader.u8()?;
+            let _segment_selector_size = reader.u8()?;
+        }
+
+        let header_length = reader.sized(offset_size)?;
+        let program_start = reader.position().saturating_add(header_length as usize);
+
+        let minimum_instruction_length = reader.u8()? as u64;
+        let maximum_operations_per_instruction = if version >= 4 {
+            (reader.u8()? as u64).max(1)
+        } else {
+            1
+        };
+        let default_is_statement = reader.u8()? != 0;
+        let line_base = reader.u8()? as i8 as i64;
+        let line_range = reader.u8()? as u64;
+        let opcode_base = reader.u8()?;
+
+        if line_range == 0 {
+            return Err(Error::InvalidValue {
+                what: "line range",
+                value: 0,
+            });
+        }
+
+        let mut standard_opcode_lengths = Vec::new();
+
+        for _ in 1..opcode_base {
+            standard_opcode_lengths.push(reader.u8()?);
+        }
+
+        let (directories, mut files) = if version >= 5 {
+            let directories = self
+                .parse_entry_table(&mut reader, encoding)?
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect();
+
+            (directories, self.parse_entry_table(&mut reader, encoding)?)
+        } else {
+            // DWARF 4 and earlier leave the compilation directory to the unit in .debug_info
+            let compilation_directory = self
+                .compilation_directory(offset as u64)
+                .map(String::from)
+                .unwrap_or_default();
+            let mut directories = vec![compilation_directory];
+
+            loop {
+                let directory = reader.string()?;
+
+                if directory.is_empty() {
+                    break;
+                }
+
+                directories.push(directory);
+            }
+
+            let mut files = Vec::new();
+
+            loop {
+                let name = reader.string()?;
+
+                if name.is_empty() {
+                    break;
+                }
+
+                let directory = reader.uleb128()?;
+                let _modification_time = reader.uleb128()?;
+                let _length = reader.uleb128()?;
+                files.push(LineFile { name, directory });
+            }
+
+            (directories, files)
+        };
+
+        let mut reader = DwarfReader::new(&data[..end], program_start, self.endianness);
+        let mut rows = Vec::new();
+        let mut state = LineState::new(default_is_statement);
+
+        let advance = |state: &mut LineState, operation_advance: u64| {
+            let operations = state.op_index.wrapping_add(operation_advance);
+            state.address = state.address.wrapping_add(
+                minimum_instruction_length
+                    .wrapping_mul(operations / maximum_operations_per_instruction),
+            );
+            state.op_index = operations % maximum_operations_per_instruction;
+        };
+
+        while !reader.is_at_end() {
+            let opcode = reader.u8()?;
+
+            if opcode >= opcode_base {
+                let adjusted = (opcode - opcode_base) as u64;
+                advance(&mut state, adjusted / line_range);
+                state.line = state
+                    .line
+                    .wrapping_add_signed(line_base + (adjusted % line_range) as i64);
+                rows.push(state.row(false));
+                continue;
+            }
+
+            match opcode {
+                0 => {
+                    let length = reader.uleb128()?;
+                    let instruction_end = reader.position().saturating_add(length as usize);
+
+                    if length == 0 {
+                        continue;
+                    }
+
+                    match reader.u8()? {
+                        DW_LNE_END_SEQUENCE => {
+                            rows.push(state.row(true));
+                            state = LineState::new(default_is_statement);
+                        }
+                        DW_LNE_SET_ADDRESS => {
+                            let size = (length - 1).min(8) as u8;
+                            state.address = reader.sized(size)?;
+                            state.op_index = 0;
+                        }
+                        DW_LNE_DEFINE_FILE => {
+                            let name = reader.string()?;
+                            let directory = reader.uleb128()?;
+                            files.push(LineFile { name, directory });
+                        }
+                        _ => {}
+                    }
+
+                    reader = DwarfReader::new(&data[..end], instruction_end, self.endianness);
+                }
+                DW_LNS_COPY => rows.push(state.row(false)),
+                DW_LNS_ADVANCE_PC => {
+                    let operation_advance = reader.uleb128()?;
+                    advance(&mut state, operation_advance);
+                }
+                DW_LNS_ADVANCE_LINE => {
+                    let delta = reader.sleb128()?;
+                    state.line = state.line.wrapping_add_signed(delta);
+                }
+                DW_LNS_SET_FILE => state.file = reader.uleb128()?,
+                DW_LNS_SET_COLUMN => state.column = reader.uleb128()?,
+                DW_LNS_NEGATE_STMT => state.is_statement = !state.is_statement,
+                DW_LNS_CONST_ADD_PC => advance(&mut state, (255 - opcode_base as u64) / line_range),
+                DW_LNS_FIXED_ADVANCE_PC => {
+                    state.address = state.address.wrapping_add(reader.u16()? as u64);
+                    state.op_index = 0;
+                }
+                _ => {
+                    // skip the ULEB128 operands of opcodes this reader has no use for
+                    let operands = standard_opcode_lengths[opcode as usize - 1];
+
+                    for _ in 0..operands {
+                        reader.uleb128()?;
+                    }
+                }
+            }
+        }
+
+        let program = LineProgram {
+            offset: offset as u64,
+            version,
+            directories,
+            files,
+            rows,
+        };
+
+        Ok((program, end))
+    }
+
+    fn parse_line_programs(&self) -> (Vec<LineProgram>, Vec<Diagnostic>) {
+        let Some(data) = self.debug_section_data(".debug_line") else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let mut programs = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut offset = 0;
+
+        while offset < data.len() {
+            match self.parse_line_program(&data, offset) {
+                Ok((program, next)) => {
+                    programs.push(program);
+                    offset = next;
+                }
+                Err(error) => {
+                    diagnostics.push(Diagnostic {
+                        table: LINE_TABLE,
+                        index: Some(programs.len()),
+                        error,
+                    });
+                    break;
+                }
+            }
+        }
+
+        (programs, diagnostics)
+    }
+
+    /// Every unit parsed from `.debug_line`, which is parsed the first time this is called.
+    pub fn line_programs(&self) -> &[LineProgram] {
+        &self
+            .line_programs
+            .get_or_init(|| self.parse_line_programs())
+            .0
+    }
+
+    /// The start of every row, keyed by address, with `None` where a sequence ends and no other
+    /// one starts.
+    fn line_index(&self) -> BTreeMap<u64, Option<(&LineProgram, &LineRow)>> {
+        let mut index = BTreeMap::new();
+
+        for program in self.line_programs() {
+            for row in &program.rows {
+                if row.end_sequence {
+                    index.entry(row.address).or_insert(Option::None);
+                } else {
+                    index.insert(row.address, Some((program, row)));
+                }
+            }
+        }
+
+        index
+    }
+
+    fn location_in(
+        index: &BTreeMap<u64, Option<(&LineProgram, &LineRow)>>,
+        address: u64,
+    ) -> Option<SourceLocation> {
+        let (program, row) = (*index.range(..=address).next_back()?.1)?;
+
+        Some(SourceLocation {
+            path: program.file_path(row.file)?,
+            line: row.line,
+            column: row.column,
+        })
+    }
+
+    /// The source file and line `address` was compiled from, according to `.debug_line`.
+    pub fn source_location(&self, address: u64) -> Option<SourceLocation> {
+        ElfHeader::location_in(&self.line_index(), address)
+    }
+
+    /// The source location of each of `addresses`, looked up in one pass over the line tables.
+    pub fn source_locations(
+        &self,
+        addresses: impl Iterator<Item = u64>,
+    ) -> Vec<Option<SourceLocation>> {
+        let index = self.line_index();
+
+        addresses
+            .map(|address| ElfHeader::location_in(&index, address))
+            .collect()
+    }
+}
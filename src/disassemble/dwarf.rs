@@ -0,0 +1,455 @@
+use std::borrow::Cow;
+
+use super::{ET_REL, ElfHeader, absolute_relocation_size};
+use crate::decode_byte::*;
+use crate::error::{Error, Result};
+
+pub const DW_FORM_ADDR: u64 = 0x01;
+pub const DW_FORM_BLOCK2: u64 = 0x03;
+pub const DW_FORM_BLOCK4: u64 = 0x04;
+pub const DW_FORM_DATA2: u64 = 0x05;
+pub const DW_FORM_DATA4: u64 = 0x06;
+pub const DW_FORM_DATA8: u64 = 0x07;
+pub const DW_FORM_STRING: u64 = 0x08;
+pub const DW_FORM_BLOCK: u64 = 0x09;
+pub const DW_FORM_BLOCK1: u64 = 0x0A;
+pub const DW_FORM_DATA1: u64 = 0x0B;
+pub const DW_FORM_FLAG: u64 = 0x0C;
+pub const DW_FORM_SDATA: u64 = 0x0D;
+pub const DW_FORM_STRP: u64 = 0x0E;
+pub const DW_FORM_UDATA: u64 = 0x0F;
+pub const DW_FORM_REF_ADDR: u64 = 0x10;
+pub const DW_FORM_REF1: u64 = 0x11;
+pub const DW_FORM_REF2: u64 = 0x12;
+pub const DW_FORM_REF4: u64 = 0x13;
+pub const DW_FORM_REF8: u64 = 0x14;
+pub const DW_FORM_REF_UDATA: u64 = 0x15;
+pub const DW_FORM_INDIRECT: u64 = 0x16;
+pub const DW_FORM_SEC_OFFSET: u64 = 0x17;
+pub const DW_FORM_EXPRLOC: u64 = 0x18;
+pub const DW_FORM_FLAG_PRESENT: u64 = 0x19;
+pub const DW_FORM_STRX: u64 = 0x1A;
+pub const DW_FORM_ADDRX: u64 = 0x1B;
+pub const DW_FORM_REF_SUP4: u64 = 0x1C;
+pub const DW_FORM_STRP_SUP: u64 = 0x1D;
+pub const DW_FORM_DATA16: u64 = 0x1E;
+pub const DW_FORM_LINE_STRP: u64 = 0x1F;
+pub const DW_FORM_REF_SIG8: u64 = 0x20;
+pub const DW_FORM_IMPLICIT_CONST: u64 = 0x21;
+pub const DW_FORM_LOCLISTX: u64 = 0x22;
+pub const DW_FORM_RNGLISTX: u64 = 0x23;
+pub const DW_FORM_REF_SUP8: u64 = 0x24;
+pub const DW_FORM_STRX1: u64 = 0x25;
+pub const DW_FORM_STRX2: u64 = 0x26;
+pub const DW_FORM_STRX3: u64 = 0x27;
+pub const DW_FORM_STRX4: u64 = 0x28;
+pub const DW_FORM_ADDRX1: u64 = 0x29;
+pub const DW_FORM_ADDRX2: u64 = 0x2A;
+pub const DW_FORM_ADDRX3: u64 = 0x2B;
+pub const DW_FORM_ADDRX4: u64 = 0x2C;
+
+/// A decoded attribute or line table entry value, before any string or address indirection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormValue {
+    Address(u64),
+    Unsigned(u64),
+    Signed(i64),
+    Flag(bool),
+    Block(Vec<u8>),
+    /// A DWARF expression (`DW_FORM_exprloc`).
+    Expression(Vec<u8>),
+    /// A string stored inline (`DW_FORM_string`).
+    String(String),
+    /// An offset into `.debug_str`.
+    StringOffset(u64),
+    /// An offset into `.debug_line_str`.
+    LineStringOffset(u64),
+    /// An index into the unit's `.debug_str_offsets` entries.
+    StringIndex(u64),
+    /// An index into the unit's `.debug_addr` entries.
+    AddressIndex(u64),
+    /// An offset from the start of the unit that holds the attribute.
+    Reference(u64),
+    /// An offset from the start of `.debug_info`.
+    ReferenceAddress(u64),
+    /// An offset into some other debug section, such as `.debug_line` or `.debug_ranges`.
+    SectionOffset(u64),
+}
+
+impl FormValue {
+    /// The value as an unsigned integer, for the forms where that is meaningful.
+    pub fn as_unsigned(&self) -> Option<u64> {
+        match self {
+            FormValue::Address(value)
+            | FormValue::Unsigned(value)
+            | FormValue::Reference(value)
+            | FormValue::ReferenceAddress(value)
+            | FormValue::SectionOffset(value) => Some(*value),
+            FormValue::Signed(value) => u64::try_from(*value).ok(),
+            FormValue::Flag(value) => Some(*value as u64),
+            _ => Option::None,
+        }
+    }
+}
+
+/// The unit parameters that decide how wide forms are.
+#[derive(Clone, Copy, Debug)]
+pub struct DwarfEncoding {
+    pub version: u16,
+    /// 4 for 32-bit DWARF, 8 for 64-bit DWARF.
+    pub offset_size: u8,
+    pub address_size: u8,
+}
+
+/// A cursor over the bytes of a DWARF section.
+pub(super) struct DwarfReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    endianness: Endianness,
+}
+
+impl<'a> DwarfReader<'a> {
+    pub(super) fn new(data: &'a [u8], position: usize, endianness: Endianness) -> DwarfReader<'a> {
+        DwarfReader {
+            data,
+            position,
+            endianness,
+        }
+    }
+
+    pub(super) fn position(&self) -> usize {
+        self.position
+    }
+
+    pub(super) fn is_at_end(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    /// A reader over the same bytes that stops at `end`, for walking one unit at a time.
+    pub(super) fn limited(&self, end: usize) -> DwarfReader<'a> {
+        DwarfReader::new(
+            &self.data[..end.min(self.data.len())],
+            self.position,
+            self.endianness,
+        )
+    }
+
+    fn truncated(&self, needed: u64) -> Error {
+        Error::Truncated {
+            what: "DWARF data",
+            offset: self.position as u64,
+            needed,
+            available: self.data.len() as u64,
+        }
+    }
+
+    pub(super) fn bytes(&mut self, length: u64) -> Result<&'a [u8]> {
+        let bytes = usize::try_from(length)
+            .ok()
+            .and_then(|length| self.position.checked_add(length))
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(|| self.truncated(length))?;
+
+        self.position += bytes.len();
+        Ok(bytes)
+    }
+
+    fn value<T: FromBytes>(&mut self) -> Result<T> {
+        let value = read_value::<T>(self.endianness, self.data, self.position)
+            .ok_or_else(|| self.truncated(size_of::<T>() as u64))?;
+
+        self.position += size_of::<T>();
+        Ok(value)
+    }
+
+    pub(super) fn u8(&mut self) -> Result<u8> {
+        self.value::<u8>()
+    }
+
+    pub(super) fn u16(&mut self) -> Result<u16> {
+        self.value::<u16>()
+    }
+
+    pub(super) fn u32(&mut self) -> Result<u32> {
+        self.value::<u32>()
+    }
+
+    pub(super) fn u64(&mut self) -> Result<u64> {
+        self.value::<u64>()
+    }
+
+    /// Reads an unsigned integer of `size` bytes, which must be 1, 2, 3, 4 or 8.
+    pub(super) fn sized(&mut self, size: u8) -> Result<u64> {
+        match size {
+            1 => Ok(self.u8()? as u64),
+            2 => Ok(self.u16()? as u64),
+            3 => {
+                let bytes = self.bytes(3)?;
+                let mut value = [0; 4];
+
+                match self.endianness {
+                    Endianness::LittleEndian => value[..3].copy_from_slice(bytes),
+                    Endianness::BigEndian => value[1..].copy_from_slice(bytes),
+                }
+
+                Ok(get_value::<u32>(self.endianness, &value).unwrap_or(0) as u64)
+            }
+            4 => Ok(self.u32()? as u64),
+            8 => self.u64(),
+            size => Err(Error::InvalidValue {
+                what: "DWARF field size",
+                value: size as u64,
+            }),
+        }
+    }
+
+    pub(super) fn uleb128(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.u8()?;
+
+            if shift < 64 {
+                value |= ((byte & 0x7F) as u64) << shift;
+            }
+
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    pub(super) fn sleb128(&mut self) -> Result<i64> {
+        let mut value = 0i64;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.u8()?;
+
+            if shift < 64 {
+                value |= ((byte & 0x7F) as i64) << shift;
+            }
+
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Reads a NUL terminated string, without the terminator.
+    pub(super) fn string(&mut self) -> Result<String> {
+        let rest = self.data.get(self.position..).unwrap_or_default();
+        let length = rest
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or_else(|| self.truncated(rest.len() as u64 + 1))?;
+
+        let bytes = self.bytes(length as u64 + 1)?;
+        Ok(String::from_utf8_lossy(&bytes[..length]).into_owned())
+    }
+
+    /// Reads a unit's initial length, returning the length and whether the unit is 64-bit DWARF.
+    pub(super) fn unit_length(&mut self) -> Result<(u64, u8)> {
+        match self.u32()? {
+            0xFFFF_FFFF => Ok((self.u64()?, 8)),
+            length => Ok((length as u64, 4)),
+        }
+    }
+
+    /// Reads a value of the given form; `DW_FORM_implicit_const` values live in the abbreviation,
+    /// so callers handle that form themselves.
+    pub(super) fn form(&mut self, form: u64, encoding: DwarfEncoding) -> Result<FormValue> {
+        let value = match form {
+            DW_FORM_ADDR => FormValue::Address(self.sized(encoding.address_size)?),
+            DW_FORM_DATA1 => FormValue::Unsigned(self.u8()? as u64),
+            DW_FORM_DATA2 => FormValue::Unsigned(self.u16()? as u64),
+            DW_FORM_DATA4 => FormValue::Unsigned(self.u32()? as u64),
+            DW_FORM_DATA8 | DW_FORM_REF_SIG8 => FormValue::Unsigned(self.u64()?),
+            DW_FORM_DATA16 => FormValue::Block(self.bytes(16)?.to_vec()),
+            DW_FORM_UDATA => FormValue::Unsigned(self.uleb128()?),
+            DW_FORM_SDATA => FormValue::Signed(self.sleb128()?),
+            DW_FORM_FLAG => FormValue::Flag(self.u8()? != 0),
+            DW_FORM_FLAG_PRESENT => FormValue::Flag(true),
+            DW_FORM_BLOCK1 => {
+                let length = self.u8()? as u64;
+                FormValue::Block(self.bytes(length)?.to_vec())
+            }
+            DW_FORM_BLOCK2 => {
+                let length = self.u16()? as u64;
+                FormValue::Block(self.bytes(length)?.to_vec())
+            }
+            DW_FORM_BLOCK4 => {
+                let length = self.u32()? as u64;
+                FormValue::Block(self.bytes(length)?.to_vec())
+            }
+            DW_FORM_BLOCK => {
+                let length = self.uleb128()?;
+                FormValue::Block(self.bytes(length)?.to_vec())
+            }
+            DW_FORM_EXPRLOC => {
+                let length = self.uleb128()?;
+                FormValue::Expression(self.bytes(length)?.to_vec())
+            }
+            DW_FORM_STRING => FormValue::String(self.string()?),
+            DW_FORM_STRP => FormValue::StringOffset(self.sized(encoding.offset_size)?),
+            DW_FORM_LINE_STRP => FormValue::LineStringOffset(self.sized(encoding.offset_size)?),
+            DW_FORM_STRP_SUP | DW_FORM_REF_SUP4 | DW_FORM_REF_SUP8 | DW_FORM_SEC_OFFSET => {
+                let size = match form {
+                    DW_FORM_REF_SUP4 => 4,
+                    DW_FORM_REF_SUP8 => 8,
+                    _ => encoding.offset_size,
+                };
+                FormValue::SectionOffset(self.sized(size)?)
+            }
+            DW_FORM_STRX | DW_FORM_STRX1 | DW_FORM_STRX2 | DW_FORM_STRX3 | DW_FORM_STRX4 => {
+                let index = match form {
+                    DW_FORM_STRX => self.uleb128()?,
+                    _ => self.sized((form - DW_FORM_STRX1 + 1) as u8)?,
+                };
+                FormValue::StringIndex(index)
+            }
+            DW_FORM_ADDRX | DW_FORM_ADDRX1 | DW_FORM_ADDRX2 | DW_FORM_ADDRX3 | DW_FORM_ADDRX4 => {
+                let index = match form {
+                    DW_FORM_ADDRX => self.uleb128()?,
+                    _ => self.sized((form - DW_FORM_ADDRX1 + 1) as u8)?,
+                };
+                FormValue::AddressIndex(index)
+            }
+            DW_FORM_LOCLISTX | DW_FORM_RNGLISTX => FormValue::Unsigned(self.uleb128()?),
+            DW_FORM_REF1 => FormValue::Reference(self.u8()? as u64),
+            DW_FORM_REF2 => FormValue::Reference(self.u16()? as u64),
+            DW_FORM_REF4 => FormValue::Reference(self.u32()? as u64),
+            DW_FORM_REF8 => FormValue::Reference(self.u64()?),
+            DW_FORM_REF_UDATA => FormValue::Reference(self.uleb128()?),
+            DW_FORM_REF_ADDR => {
+                // DWARF 2 sized these like addresses, later versions like offsets
+                let size = if encoding.version <= 2 {
+                    encoding.address_size
+                } else {
+                    encoding.offset_size
+                };
+                FormValue::ReferenceAddress(self.sized(size)?)
+            }
+            DW_FORM_INDIRECT => match self.uleb128()? {
+                // a chain of indirections would only recurse until the stack runs out
+                DW_FORM_INDIRECT => {
+                    return Err(Error::InvalidValue {
+                        what: "DWARF form",
+                        value: DW_FORM_INDIRECT,
+                    });
+                }
+                form => return self.form(form, encoding),
+            },
+            form => {
+                return Err(Error::InvalidValue {
+                    what: "DWARF form",
+                    value: form,
+                });
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+/// `value` as `size` bytes in `endianness` order.
+fn encode(value: u64, size: usize, endianness: Endianness) -> Vec<u8> {
+    let mut bytes = value.to_le_bytes()[..size].to_vec();
+
+    if endianness == Endianness::BigEndian {
+        bytes.reverse();
+    }
+
+    bytes
+}
+
+impl ElfHeader {
+    /// The bytes of the named debug section, decompressed if need be and found under its legacy
+    /// `.zdebug` name too. In relocatable files the absolute relocations against it are applied
+    /// first, since that is where objects keep their string offsets and addresses.
+    pub fn debug_section_data(&self, name: &str) -> Option<Cow<'_, [u8]>> {
+        let section = self.section_by_name(name).or_else(|| {
+            let compressed_name = format!(".zdebug{}", name.strip_prefix(".debug")?);
+            self.section_by_name(&compressed_name)
+        })?;
+        let data = self.section_contents(section)?;
+
+        if self.header.file_type != ET_REL {
+            return Some(Cow::Borrowed(data));
+        }
+
+        let mut relocations = self
+            .relocations
+            .iter()
+            .filter(|relocation| relocation.target_section == section.index)
+            .peekable();
+
+        if relocations.peek().is_none() {
+            return Some(Cow::Borrowed(data));
+        }
+
+        let mut patched = data.to_vec();
+
+        for relocation in relocations {
+            let Some(size) =
+                absolute_relocation_size(self.header.machine, relocation.relocation_type)
+            else {
+                continue;
+            };
+
+            let Some(start) = usize::try_from(relocation.offset).ok() else {
+                continue;
+            };
+
+            let Some(field) = start
+                .checked_add(size)
+                .and_then(|end| patched.get_mut(start..end))
+            else {
+                continue;
+            };
+
+            // SHT_REL entries keep their addend in the field being patched
+            let addend = match relocation.addend {
+                Some(addend) => addend as u64,
+                Option::None => DwarfReader::new(field, 0, self.endianness)
+                    .sized(size as u8)
+                    .unwrap_or(0),
+            };
+
+            let symbol = self
+                .relocation_symbol(relocation)
+                .map(|symbol| self.symbol_address(symbol))
+                .unwrap_or(0);
+
+            field.copy_from_slice(&encode(symbol.wrapping_add(addend), size, self.endianness));
+        }
+
+        Some(Cow::Owned(patched))
+    }
+
+    /// Resolves a string form against `.debug_str` or `.debug_line_str`.
+    pub fn dwarf_string(&self, value: &FormValue) -> Option<String> {
+        let (section, offset) = match value {
+            FormValue::String(string) => return Some(string.clone()),
+            FormValue::StringOffset(offset) => (".debug_str", *offset),
+            FormValue::LineStringOffset(offset) => (".debug_line_str", *offset),
+            _ => return Option::None,
+        };
+
+        let data = self.debug_section_data(section)?;
+        let offset = usize::try_from(offset).ok()?;
+
+        DwarfReader::new(&data, offset, self.endianness)
+            .string()
+            .ok()
+    }
+}
@@ -0,0 +1,469 @@
+use super::{BitFormat, EM_AARCH64, ElfHeader, PT_DYNAMIC, SHT_DYNAMIC};
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+pub const DT_NULL: u64 = 0;
+pub const DT_NEEDED: u64 = 1;
+pub const DT_PLTRELSZ: u64 = 2;
+pub const DT_PLTGOT: u64 = 3;
+pub const DT_HASH: u64 = 4;
+pub const DT_STRTAB: u64 = 5;
+pub const DT_SYMTAB: u64 = 6;
+pub const DT_RELA: u64 = 7;
+pub const DT_RELASZ: u64 = 8;
+pub const DT_RELAENT: u64 = 9;
+pub const DT_STRSZ: u64 = 10;
+pub const DT_SYMENT: u64 = 11;
+pub const DT_INIT: u64 = 12;
+pub const DT_FINI: u64 = 13;
+pub const DT_SONAME: u64 = 14;
+pub const DT_RPATH: u64 = 15;
+pub const DT_SYMBOLIC: u64 = 16;
+pub const DT_REL: u64 = 17;
+pub const DT_RELSZ: u64 = 18;
+pub const DT_RELENT: u64 = 19;
+pub const DT_PLTREL: u64 = 20;
+pub const DT_DEBUG: u64 = 21;
+pub const DT_TEXTREL: u64 = 22;
+pub const DT_JMPREL: u64 = 23;
+pub const DT_BIND_NOW: u64 = 24;
+pub const DT_INIT_ARRAY: u64 = 25;
+pub const DT_FINI_ARRAY: u64 = 26;
+pub const DT_INIT_ARRAYSZ: u64 = 27;
+pub const DT_FINI_ARRAYSZ: u64 = 28;
+pub const DT_RUNPATH: u64 = 29;
+pub const DT_FLAGS: u64 = 30;
+pub const DT_PREINIT_ARRAY: u64 = 32;
+pub const DT_PREINIT_ARRAYSZ: u64 = 33;
+pub const DT_SYMTAB_SHNDX: u64 = 34;
+pub const DT_RELRSZ: u64 = 35;
+pub const DT_RELR: u64 = 36;
+pub const DT_RELRENT: u64 = 37;
+pub const DT_GNU_HASH: u64 = 0x6FFFFEF5;
+pub const DT_TLSDESC_PLT: u64 = 0x6FFFFEF6;
+pub const DT_TLSDESC_GOT: u64 = 0x6FFFFEF7;
+pub const DT_VERSYM: u64 = 0x6FFFFFF0;
+pub const DT_RELACOUNT: u64 = 0x6FFFFFF9;
+pub const DT_RELCOUNT: u64 = 0x6FFFFFFA;
+pub const DT_FLAGS_1: u64 = 0x6FFFFFFB;
+pub const DT_VERDEF: u64 = 0x6FFFFFFC;
+pub const DT_VERDEFNUM: u64 = 0x6FFFFFFD;
+pub const DT_VERNEED: u64 = 0x6FFFFFFE;
+pub const DT_VERNEEDNUM: u64 = 0x6FFFFFFF;
+pub const DT_AUXILIARY: u64 = 0x7FFFFFFD;
+pub const DT_FILTER: u64 = 0x7FFFFFFF;
+
+pub const DF_ORIGIN: u64 = 0x1;
+pub const DF_SYMBOLIC: u64 = 0x2;
+pub const DF_TEXTREL: u64 = 0x4;
+pub const DF_BIND_NOW: u64 = 0x8;
+pub const DF_STATIC_TLS: u64 = 0x10;
+
+pub const DF_1_NOW: u64 = 0x1;
+pub const DF_1_PIE: u64 = 0x0800_0000;
+
+const DYNAMIC_TABLE: &str = "dynamic section";
+
+const FLAG_NAMES: [(u64, &str); 5] = [
+    (DF_ORIGIN, "ORIGIN"),
+    (DF_SYMBOLIC, "SYMBOLIC"),
+    (DF_TEXTREL, "TEXTREL"),
+    (DF_BIND_NOW, "BIND_NOW"),
+    (DF_STATIC_TLS, "STATIC_TLS"),
+];
+
+const FLAG_1_NAMES: [&str; 28] = [
+    "NOW",
+    "GLOBAL",
+    "GROUP",
+    "NODELETE",
+    "LOADFLTR",
+    "INITFIRST",
+    "NOOPEN",
+    "ORIGIN",
+    "DIRECT",
+    "TRANS",
+    "INTERPOSE",
+    "NODEFLIB",
+    "NODUMP",
+    "CONFALT",
+    "ENDFILTEE",
+    "DISPRELDNE",
+    "DISPRELPND",
+    "NODIRECT",
+    "IGNMULDEF",
+    "NOKSYMS",
+    "NOHDR",
+    "EDITED",
+    "NORELOC",
+    "SYMINTPOSE",
+    "GLOBAUDIT",
+    "SINGLETON",
+    "STUB",
+    "PIE",
+];
+
+/// One `Elf_Dyn` entry of the dynamic section.
+#[derive(Clone, Debug)]
+pub struct DynamicEntry {
+    pub index: usize,
+    pub tag: u64,
+    pub value: u64,
+}
+
+pub fn dynamic_tag_name(tag: u64, machine: u16) -> String {
+    let name = match tag {
+        DT_NULL => "NULL",
+        DT_NEEDED => "NEEDED",
+        DT_PLTRELSZ => "PLTRELSZ",
+        DT_PLTGOT => "PLTGOT",
+        DT_HASH => "HASH",
+        DT_STRTAB => "STRTAB",
+        DT_SYMTAB => "SYMTAB",
+        DT_RELA => "RELA",
+        DT_RELASZ => "RELASZ",
+        DT_RELAENT => "RELAENT",
+        DT_STRSZ => "STRSZ",
+        DT_SYMENT => "SYMENT",
+        DT_INIT => "INIT",
+        DT_FINI => "FINI",
+        DT_SONAME => "SONAME",
+        DT_RPATH => "RPATH",
+        DT_SYMBOLIC => "SYMBOLIC",
+        DT_REL => "REL",
+        DT_RELSZ => "RELSZ",
+        DT_RELENT => "RELENT",
+        DT_PLTREL => "PLTREL",
+        DT_DEBUG => "DEBUG",
+        DT_TEXTREL => "TEXTREL",
+        DT_JMPREL => "JMPREL",
+        DT_BIND_NOW => "BIND_NOW",
+        DT_INIT_ARRAY => "INIT_ARRAY",
+        DT_FINI_ARRAY => "FINI_ARRAY",
+        DT_INIT_ARRAYSZ => "INIT_ARRAYSZ",
+        DT_FINI_ARRAYSZ => "FINI_ARRAYSZ",
+        DT_RUNPATH => "RUNPATH",
+        DT_FLAGS => "FLAGS",
+        DT_PREINIT_ARRAY => "PREINIT_ARRAY",
+        DT_PREINIT_ARRAYSZ => "PREINIT_ARRAYSZ",
+        DT_SYMTAB_SHNDX => "SYMTAB_SHNDX",
+        DT_RELRSZ => "RELRSZ",
+        DT_RELR => "RELR",
+        DT_RELRENT => "RELRENT",
+        DT_GNU_HASH => "GNU_HASH",
+        DT_TLSDESC_PLT => "TLSDESC_PLT",
+        DT_TLSDESC_GOT => "TLSDESC_GOT",
+        DT_VERSYM => "VERSYM",
+        DT_RELACOUNT => "RELACOUNT",
+        DT_RELCOUNT => "RELCOUNT",
+        DT_FLAGS_1 => "FLAGS_1",
+        DT_VERDEF => "VERDEF",
+        DT_VERDEFNUM => "VERDEFNUM",
+        DT_VERNEED => "VERNEED",
+        DT_VERNEEDNUM => "VERNEEDNUM",
+        DT_AUXILIARY => "AUXILIARY",
+        DT_FILTER => "FILTER",
+        0x70000001 if machine == EM_AARCH64 => "AARCH64_BTI_PLT",
+        0x70000003 if machine == EM_AARCH64 => "AARCH64_PAC_PLT",
+        0x70000005 if machine == EM_AARCH64 => "AARCH64_VARIANT_PCS",
+        0x6000000D..=0x6FFFF000 => return format!("Operating System specific: {tag:x}"),
+        0x70000000..=0x7FFFFFFF => return format!("Processor Specific: {tag:x}"),
+        _ => return format!("<unknown>: {tag:x}"),
+    };
+
+    String::from(name)
+}
+
+/// The names of the `DT_FLAGS` bits set in `flags`, with any unknown bits in hex.
+pub fn dynamic_flags_string(flags: u64) -> String {
+    let mut names = Vec::new();
+    let mut remaining = flags;
+
+    for (bit, name) in FLAG_NAMES {
+        if flags & bit != 0 {
+            names.push(String::from(name));
+            remaining &= !bit;
+        }
+    }
+
+    if remaining != 0 {
+        names.push(format!("{remaining:#x}"));
+    }
+
+    names.join(" ")
+}
+
+/// The names of the `DT_FLAGS_1` bits set in `flags`, with any unknown bits in hex.
+pub fn dynamic_flags_1_string(flags: u64) -> String {
+    let mut names = Vec::new();
+    let mut remaining = flags;
+
+    for (bit, name) in FLAG_1_NAMES.iter().enumerate() {
+        if flags & (1 << bit) != 0 {
+            names.push(String::from(*name));
+            remaining &= !(1 << bit);
+        }
+    }
+
+    if remaining != 0 {
+        names.push(format!("{remaining:#x}"));
+    }
+
+    names.join(" ")
+}
+
+impl ElfHeader {
+    fn dynamic_entry_size(&self) -> u64 {
+        match self.format {
+            BitFormat::Bit32 => 0x08,
+            BitFormat::Bit64 => 0x10,
+        }
+    }
+
+    /// Where the dynamic table lives in the file as `(offset, size)`, preferring the `.dynamic`
+    /// section and falling back to the `PT_DYNAMIC` segment when section headers are missing.
+    pub fn dynamic_table_location(&self) -> Option<(u64, u64)> {
+        if let Some(section) = self
+            .sections
+            .iter()
+            .find(|section| section.section_type == SHT_DYNAMIC)
+        {
+            return Some((section.offset, section.size));
+        }
+
+        self.segments
+            .iter()
+            .find(|segment| segment.segment_type == PT_DYNAMIC)
+            .map(|segment| (segment.offset, segment.file_size))
+    }
+
+    pub(super) fn parse_dynamic_section(&mut self) {
+        let Some((offset, size)) = self.dynamic_table_location() else {
+            return;
+        };
+
+        let entry_size = self.dynamic_entry_size();
+
+        for index in 0..(size / entry_size) as usize {
+            let entry_offset = offset.saturating_add(index as u64 * entry_size);
+
+            let entry = match self.format {
+                BitFormat::Bit32 => {
+                    self.read::<u32>("dynamic entry", entry_offset)
+                        .and_then(|tag| {
+                            let value = self.read::<u32>("dynamic entry", entry_offset + 4)?;
+                            Ok((tag as u64, value as u64))
+                        })
+                }
+                BitFormat::Bit64 => {
+                    self.read::<u64>("dynamic entry", entry_offset)
+                        .and_then(|tag| {
+                            let value = self.read::<u64>("dynamic entry", entry_offset + 8)?;
+                            Ok((tag, value))
+                        })
+                }
+            };
+
+            match entry {
+                Ok((tag, value)) => {
+                    self.dynamic.push(DynamicEntry { index, tag, value });
+
+                    // anything after the first DT_NULL is padding
+                    if tag == DT_NULL {
+                        return;
+                    }
+                }
+                Err(error) => {
+                    self.diagnostics.push(Diagnostic {
+                        table: DYNAMIC_TABLE,
+                        index: Some(index),
+                        error,
+                    });
+                    return;
+                }
+            }
+        }
+    }
+
+    /// The entries of the dynamic section up to and including the terminating `DT_NULL`.
+    pub fn dynamic_entries(&self) -> &[DynamicEntry] {
+        &self.dynamic
+    }
+
+    /// Where the dynamic string table lives in the file as `(offset, size)`: the section
+    /// `.dynamic` links to, or `DT_STRTAB` and `DT_STRSZ` when section headers are missing.
+    fn dynamic_string_table_location(&self) -> Option<(u64, u64)> {
+        let linked = self
+            .sections
+            .iter()
+            .find(|section| section.section_type == SHT_DYNAMIC)
+            .and_then(|section| self.section(section.link as usize));
+
+        if let Some(table) = linked.or_else(|| self.section_by_name(".dynstr")) {
+            return Some((table.offset, table.size));
+        }
+
+        let offset = self.vaddr_to_offset(self.dynamic_value(DT_STRTAB)?)?;
+        Some((offset, self.dynamic_value(DT_STRSZ)?))
+    }
+
+    /// Reads a string referenced by a dynamic entry, such as a `DT_NEEDED` library name.
+    pub fn dynamic_string(&self, offset: u64) -> Result<String> {
+        let (table_offset, table_size) = self
+            .dynamic_string_table_location()
+            .ok_or(Error::SectionNotFound(String::from(".dynstr")))?;
+
+        if offset >= table_size {
+            return Err(Error::OutOfRange {
+                what: "string table offset",
+                offset,
+                limit: table_size,
+            });
+        }
+
+        let table = self
+            .file_range(table_offset, table_size)
+            .ok_or(Error::Truncated {
+                what: "dynamic string table",
+                offset: table_offset,
+                needed: table_size,
+                available: self.data.len() as u64,
+            })?;
+
+        let bytes = &table[offset as usize..];
+
+        let Some(length) = bytes.iter().position(|byte| *byte == 0) else {
+            return Err(Error::Truncated {
+                what: "string",
+                offset: table_offset.saturating_add(offset),
+                needed: bytes.len() as u64 + 1,
+                available: self.data.len() as u64,
+            });
+        };
+
+        Ok(String::from_utf8_lossy(&bytes[..length]).into_owned())
+    }
+
+    fn dynamic_strings(&self, tag: u64) -> Vec<String> {
+        self.dynamic
+            .iter()
+            .filter(|entry| entry.tag == tag)
+            .filter_map(|entry| self.dynamic_string(entry.value).ok())
+            .collect()
+    }
+
+    pub(super) fn dynamic_value(&self, tag: u64) -> Option<u64> {
+        self.dynamic
+            .iter()
+            .find(|entry| entry.tag == tag)
+            .map(|entry| entry.value)
+    }
+
+    /// The `DT_NEEDED` libraries, in load order.
+    pub fn needed_libraries(&self) -> Vec<String> {
+        self.dynamic_strings(DT_NEEDED)
+    }
+
+    pub fn soname(&self) -> Option<String> {
+        self.dynamic_strings(DT_SONAME).into_iter().next()
+    }
+
+    /// The `DT_RPATH` search path, which unlike `DT_RUNPATH` also applies to dependencies.
+    pub fn rpath(&self) -> Option<String> {
+        self.dynamic_strings(DT_RPATH).into_iter().next()
+    }
+
+    pub fn runpath(&self) -> Option<String> {
+        self.dynamic_strings(DT_RUNPATH).into_iter().next()
+    }
+
+    /// Whether the dynamic linker is asked to resolve every symbol at load time.
+    pub fn is_bind_now(&self) -> bool {
+        self.dynamic_value(DT_BIND_NOW).is_some()
+            || self.dynamic_value(DT_FLAGS).unwrap_or(0) & DF_BIND_NOW != 0
+            || self.dynamic_value(DT_FLAGS_1).unwrap_or(0) & DF_1_NOW != 0
+    }
+
+    fn dynamic_value_string(&self, entry: &DynamicEntry) -> String {
+        let string = |label: &str| match self.dynamic_string(entry.value) {
+            Ok(name) => format!("{label}: [{name}]"),
+            Err(_) => format!("{label}: <corrupt: {:#x}>", entry.value),
+        };
+
+        match entry.tag {
+            DT_NEEDED => string("Shared library"),
+            DT_SONAME => string("Library soname"),
+            DT_RPATH => string("Library rpath"),
+            DT_RUNPATH => string("Library runpath"),
+            DT_AUXILIARY => string("Auxiliary library"),
+            DT_FILTER => string("Filter library"),
+            DT_FLAGS => dynamic_flags_string(entry.value),
+            DT_FLAGS_1 => format!("Flags: {}", dynamic_flags_1_string(entry.value)),
+            DT_PLTREL => match entry.value {
+                DT_RELA => String::from("RELA"),
+                DT_REL => String::from("REL"),
+                value => format!("{value:#x}"),
+            },
+            DT_PLTRELSZ | DT_RELASZ | DT_RELAENT | DT_STRSZ | DT_SYMENT | DT_RELSZ | DT_RELENT
+            | DT_INIT_ARRAYSZ | DT_FINI_ARRAYSZ | DT_PREINIT_ARRAYSZ | DT_RELRSZ | DT_RELRENT => {
+                format!("{} (bytes)", entry.value)
+            }
+            DT_RELACOUNT | DT_RELCOUNT | DT_VERDEFNUM | DT_VERNEEDNUM => entry.value.to_string(),
+            DT_BIND_NOW | DT_SYMBOLIC | DT_TEXTREL => String::new(),
+            _ => format!("{:#x}", entry.value),
+        }
+    }
+
+    /// Formats the dynamic section the way `readelf -d -W` does.
+    pub fn dynamic_lines(&self) -> Vec<String> {
+        let Some((offset, _)) = self.dynamic_table_location() else {
+            return vec![
+                String::new(),
+                String::from("There is no dynamic section in this file."),
+            ];
+        };
+
+        let tag_width = self.format.address_size() * 2;
+
+        let mut lines = vec![
+            String::new(),
+            format!(
+                "Dynamic section at offset {:#x} contains {} {}:",
+                offset,
+                self.dynamic.len(),
+                if self.dynamic.len() == 1 {
+                    "entry"
+                } else {
+                    "entries"
+                }
+            ),
+            String::from("  Tag        Type                         Name/Value"),
+        ];
+
+        // the type column widens in 32 bit files so the values line up with the 64 bit layout
+        let type_width = 20 + 16 - tag_width;
+
+        for entry in &self.dynamic {
+            lines.push(format!(
+                " 0x{:0tw$x} {:<yw$} {}",
+                entry.tag,
+                format!("({})", dynamic_tag_name(entry.tag, self.header.machine)),
+                self.dynamic_value_string(entry),
+                tw = tag_width,
+                yw = type_width
+            ));
+        }
+
+        lines
+    }
+
+    pub fn dump_dynamic(&self, out: &mut dyn OutputSink) {
+        out.begin_section("dynamic");
+
+        for line in self.dynamic_lines() {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+}
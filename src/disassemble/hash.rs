@@ -0,0 +1,400 @@
+use super::{BitFormat, ElfHeader, SHT_GNU_HASH, SHT_HASH, SectionHeader, Symbol, SymbolVersion};
+use crate::decode_byte::*;
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+const HASH_TABLE: &str = "hash table";
+
+/// The System V ELF hash used by `.hash`.
+pub fn elf_hash(name: &str) -> u32 {
+    let mut hash = 0u32;
+
+    for byte in name.bytes() {
+        hash = (hash << 4).wrapping_add(byte as u32);
+        let high = hash & 0xF000_0000;
+
+        if high != 0 {
+            hash ^= high >> 24;
+        }
+
+        hash &= !high;
+    }
+
+    hash
+}
+
+/// The DJB hash used by `.gnu.hash`.
+pub fn gnu_hash(name: &str) -> u32 {
+    name.bytes().fold(5381u32, |hash, byte| {
+        hash.wrapping_mul(33).wrapping_add(byte as u32)
+    })
+}
+
+/// A System V `.hash` section: symbol indexes chained from buckets by `elf_hash`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashTable {
+    /// Index of the section the table came from.
+    pub section: usize,
+    pub buckets: Vec<u32>,
+    /// The next symbol index in the chain of each symbol, 0 ending it.
+    pub chains: Vec<u32>,
+}
+
+impl HashTable {
+    /// The symbol indexes in `bucket`, stopping at a loop in a corrupt chain.
+    fn chain(&self, bucket: usize) -> Vec<u32> {
+        let mut indexes = Vec::new();
+        let mut index = self.buckets.get(bucket).copied().unwrap_or(0);
+
+        while index != 0 && indexes.len() < self.chains.len() {
+            indexes.push(index);
+            index = self.chains.get(index as usize).copied().unwrap_or(0);
+        }
+
+        indexes
+    }
+}
+
+/// A GNU `.gnu.hash` section: a Bloom filter, then buckets of symbols sorted by hash from
+/// `symbol_offset` on, each chain ending at the hash value whose low bit is set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GnuHashTable {
+    /// Index of the section the table came from.
+    pub section: usize,
+    /// Index of the first dynamic symbol the table covers.
+    pub symbol_offset: u32,
+    pub bloom_shift: u32,
+    /// The Bloom filter words, 32 or 64 bits wide with the file's class.
+    pub bloom: Vec<u64>,
+    pub buckets: Vec<u32>,
+    /// The hash of each covered symbol with bit 0 replaced by an end of chain marker.
+    pub chain: Vec<u32>,
+}
+
+impl GnuHashTable {
+    /// The symbol indexes in `bucket`, stopping where a corrupt `symbol_offset` would take them
+    /// past the largest index.
+    fn chain(&self, bucket: usize) -> Vec<u32> {
+        let mut indexes = Vec::new();
+        let start = self.buckets.get(bucket).copied().unwrap_or(0);
+
+        if start < self.symbol_offset {
+            return indexes;
+        }
+
+        for (position, value) in self
+            .chain
+            .iter()
+            .enumerate()
+            .skip((start - self.symbol_offset) as usize)
+        {
+            let Some(index) = u32::try_from(position)
+                .ok()
+                .and_then(|position| self.symbol_offset.checked_add(position))
+            else {
+                break;
+            };
+
+            indexes.push(index);
+
+            if value & 1 != 0 {
+                break;
+            }
+        }
+
+        indexes
+    }
+
+    /// Whether the Bloom filter lets through `hash`; `false` means the name is certainly absent.
+    fn may_contain(&self, hash: u32, word_bits: u32) -> bool {
+        if self.bloom.is_empty() {
+            return true;
+        }
+
+        let word = self.bloom[(hash / word_bits) as usize % self.bloom.len()];
+        let first = 1u64 << (hash % word_bits);
+        let second = 1u64 << ((hash >> self.bloom_shift.min(31)) % word_bits);
+
+        word & first != 0 && word & second != 0
+    }
+}
+
+/// The length of each chain, summarised the way `readelf -I` prints it.
+fn histogram_lines(heading: String, lengths: &[usize]) -> Vec<String> {
+    let bucket_count = lengths.len();
+
+    let mut lines = vec![
+        String::new(),
+        format!(
+            "{heading} (total of {bucket_count} {}):",
+            if bucket_count == 1 {
+                "bucket"
+            } else {
+                "buckets"
+            }
+        ),
+        String::from(" Length  Number     % of total  Coverage"),
+    ];
+
+    let longest = lengths.iter().copied().max().unwrap_or(0);
+    let mut counts = vec![0usize; longest + 1];
+
+    for length in lengths {
+        counts[*length] += 1;
+    }
+
+    let symbol_count = lengths.iter().sum::<usize>().max(1) as f64;
+    let percent = |count: usize| count as f64 * 100.0 / bucket_count as f64;
+
+    lines.push(format!(
+        "      0  {:<10} ({:5.1}%)",
+        counts[0],
+        percent(counts[0])
+    ));
+
+    let mut covered = 0;
+
+    for (length, count) in counts.iter().enumerate().skip(1) {
+        covered += length * count;
+        lines.push(format!(
+            "{:7}  {:<10} ({:5.1}%)    {:5.1}%",
+            length,
+            count,
+            percent(*count),
+            covered as f64 * 100.0 / symbol_count
+        ));
+    }
+
+    lines
+}
+
+impl ElfHeader {
+    fn hash_words(&self, table: &SectionHeader) -> Result<Vec<u32>> {
+        let data = self
+            .section_data(table)
+            .ok_or_else(|| Error::SectionOutOfBounds {
+                name: table.name.clone(),
+                offset: table.offset,
+                size: table.size,
+                available: self.data.len() as u64,
+            })?;
+
+        Ok(data
+            .chunks_exact(4)
+            .filter_map(|word| get_value::<u32>(self.endianness, word))
+            .collect())
+    }
+
+    fn parse_hash_table(&self, table: &SectionHeader) -> Result<HashTable> {
+        let words = self.hash_words(table)?;
+        let truncated = |needed: u64| Error::Truncated {
+            what: "hash table",
+            offset: table.offset,
+            needed,
+            available: table.size,
+        };
+
+        let [bucket_count, chain_count, ..] = words[..] else {
+            return Err(truncated(8));
+        };
+
+        let bucket_end = 2usize.saturating_add(bucket_count as usize);
+        let chain_end = bucket_end.saturating_add(chain_count as usize);
+
+        if chain_end > words.len() {
+            return Err(truncated((chain_end as u64).saturating_mul(4)));
+        }
+
+        Ok(HashTable {
+            section: table.index,
+            buckets: words[2..bucket_end].to_vec(),
+            chains: words[bucket_end..chain_end].to_vec(),
+        })
+    }
+
+    fn parse_gnu_hash_table(&self, table: &SectionHeader) -> Result<GnuHashTable> {
+        let words = self.hash_words(table)?;
+        let truncated = |needed: u64| Error::Truncated {
+            what: "GNU hash table",
+            offset: table.offset,
+            needed,
+            available: table.size,
+        };
+
+        let [bucket_count, symbol_offset, bloom_size, bloom_shift, ..] = words[..] else {
+            return Err(truncated(16));
+        };
+
+        // Bloom words are address sized, so two 32-bit words each in 64-bit files
+        let bloom_words = match self.format {
+            BitFormat::Bit32 => bloom_size as usize,
+            BitFormat::Bit64 => (bloom_size as usize).saturating_mul(2),
+        };
+        let bloom_end = 4usize.saturating_add(bloom_words);
+        let bucket_end = bloom_end.saturating_add(bucket_count as usize);
+
+        if bucket_end > words.len() {
+            return Err(truncated((bucket_end as u64).saturating_mul(4)));
+        }
+
+        let bloom = match self.format {
+            BitFormat::Bit32 => words[4..bloom_end]
+                .iter()
+                .map(|word| *word as u64)
+                .collect(),
+            BitFormat::Bit64 => {
+                let data = self.section_data(table).unwrap_or_default();
+
+                (0..bloom_size as usize)
+                    .filter_map(|index| read_value::<u64>(self.endianness, data, 16 + index * 8))
+                    .collect()
+            }
+        };
+
+        Ok(GnuHashTable {
+            section: table.index,
+            symbol_offset,
+            bloom_shift,
+            bloom,
+            buckets: words[bloom_end..bucket_end].to_vec(),
+            chain: words[bucket_end..].to_vec(),
+        })
+    }
+
+    pub(super) fn parse_hash_tables(&mut self) {
+        let mut hash_table = Option::None;
+        let mut gnu_hash_table = Option::None;
+        let mut diagnostics = Vec::new();
+
+        for table in &self.sections {
+            // only the first of each kind is used, as the dynamic linker only knows one
+            let result = match table.section_type {
+                SHT_HASH if hash_table.is_none() => self
+                    .parse_hash_table(table)
+                    .map(|hash| hash_table = Some(hash)),
+                SHT_GNU_HASH if gnu_hash_table.is_none() => self
+                    .parse_gnu_hash_table(table)
+                    .map(|hash| gnu_hash_table = Some(hash)),
+                _ => continue,
+            };
+
+            if let Err(error) = result {
+                diagnostics.push(Diagnostic {
+                    table: HASH_TABLE,
+                    index: Some(table.index),
+                    error,
+                });
+            }
+        }
+
+        self.hash_table = hash_table;
+        self.gnu_hash_table = gnu_hash_table;
+        self.diagnostics.extend(diagnostics);
+    }
+
+    pub fn hash_table(&self) -> Option<&HashTable> {
+        self.hash_table.as_ref()
+    }
+
+    pub fn gnu_hash_table(&self) -> Option<&GnuHashTable> {
+        self.gnu_hash_table.as_ref()
+    }
+
+    /// The dynamic symbol the hash tables point at for `name`, the way the dynamic linker finds
+    /// it: through `.gnu.hash` when there is one and `.hash` otherwise, skipping hidden versions.
+    pub fn hashed_symbol(&self, name: &str) -> Option<&Symbol> {
+        let (table, candidates) = if let Some(gnu) = self.gnu_hash_table.as_ref() {
+            let hash = gnu_hash(name);
+            let word_bits = self.format.address_size() as u32 * 8;
+
+            if gnu.buckets.is_empty() || !gnu.may_contain(hash, word_bits) {
+                return Option::None;
+            }
+
+            let candidates = gnu
+                .chain(hash as usize % gnu.buckets.len())
+                .into_iter()
+                .filter(|index| {
+                    gnu.chain
+                        .get((index - gnu.symbol_offset) as usize)
+                        .is_some_and(|value| value | 1 == hash | 1)
+                })
+                .collect::<Vec<_>>();
+
+            (gnu.section, candidates)
+        } else {
+            let sysv = self.hash_table.as_ref()?;
+
+            if sysv.buckets.is_empty() {
+                return Option::None;
+            }
+
+            let bucket = elf_hash(name) as usize % sysv.buckets.len();
+            (sysv.section, sysv.chain(bucket))
+        };
+
+        let symbols = self.section(table)?.link as usize;
+
+        candidates.into_iter().find_map(|index| {
+            self.symbols_in(symbols)
+                .find(|symbol| symbol.index == index as usize && symbol.name == name)
+                .filter(|symbol| {
+                    !matches!(self.symbol_version(symbol), Some(SymbolVersion::Hidden(_)))
+                })
+        })
+    }
+
+    /// Formats the bucket list length histograms of the hash tables the way `readelf -I` does.
+    pub fn histogram_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if let Some(sysv) = self
+            .hash_table
+            .as_ref()
+            .filter(|sysv| !sysv.buckets.is_empty())
+        {
+            let lengths = (0..sysv.buckets.len())
+                .map(|bucket| sysv.chain(bucket).len())
+                .collect::<Vec<_>>();
+
+            lines.extend(histogram_lines(
+                String::from("Histogram for bucket list length"),
+                &lengths,
+            ));
+        }
+
+        if let Some(gnu) = self
+            .gnu_hash_table
+            .as_ref()
+            .filter(|gnu| !gnu.buckets.is_empty())
+        {
+            let name = self
+                .section(gnu.section)
+                .map(|section| section.name.as_str())
+                .unwrap_or_default();
+            let lengths = (0..gnu.buckets.len())
+                .map(|bucket| gnu.chain(bucket).len())
+                .collect::<Vec<_>>();
+
+            lines.extend(histogram_lines(
+                format!("Histogram for `{name}' bucket list length"),
+                &lengths,
+            ));
+        }
+
+        if lines.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("There are no hash tables in this file."));
+        }
+
+        lines
+    }
+
+    pub fn dump_histogram(&self, out: &mut dyn OutputSink) {
+        out.begin_section("histogram");
+
+        for line in self.histogram_lines() {
+            out.write_line(line.as_str());
+        }
+    }
+}
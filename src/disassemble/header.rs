@@ -0,0 +1,577 @@
+use super::{BitFormat, DF_1_PIE, DT_FLAGS_1, ElfHeader, SHN_XINDEX, read_address};
+use crate::decode_byte::*;
+use crate::output::OutputSink;
+
+pub const ET_NONE: u16 = 0;
+pub const ET_REL: u16 = 1;
+pub const ET_EXEC: u16 = 2;
+pub const ET_DYN: u16 = 3;
+pub const ET_CORE: u16 = 4;
+
+const EV_CURRENT: u32 = 1;
+
+/// Every field of the ELF file header, as stored in the file.
+#[derive(Clone, Debug)]
+pub struct FileHeader {
+    pub identification: [u8; 16],
+    pub class: u8,
+    pub data_encoding: u8,
+    pub identification_version: u8,
+    pub os_abi: u8,
+    pub abi_version: u8,
+    pub file_type: u16,
+    pub machine: u16,
+    pub version: u32,
+    pub entry: u64,
+    pub program_header_offset: u64,
+    pub section_header_offset: u64,
+    pub flags: u32,
+    pub header_size: u16,
+    pub program_header_entry_size: u16,
+    pub program_header_entry_count: u16,
+    pub section_header_entry_size: u16,
+    pub section_header_entry_count: u16,
+    pub section_header_names_index: u16,
+}
+
+impl FileHeader {
+    pub(super) fn parse(format: BitFormat, endianness: Endianness, bytes: &[u8]) -> FileHeader {
+        let read_u16 = |offset| read_value::<u16>(endianness, bytes, offset).unwrap_or(0);
+        let read_u32 = |offset| read_value::<u32>(endianness, bytes, offset).unwrap_or(0);
+        let read_address = |offset| read_address(format, endianness, bytes, offset);
+
+        // everything after e_version is shifted by the width of the three address sized fields
+        let address_size = format.address_size();
+        let sizes = 0x18 + address_size * 3 + 4;
+
+        let mut identification = [0; 16];
+        identification.copy_from_slice(&bytes[..16]);
+
+        FileHeader {
+            identification,
+            class: bytes[4],
+            data_encoding: bytes[5],
+            identification_version: bytes[6],
+            os_abi: bytes[7],
+            abi_version: bytes[8],
+            file_type: read_u16(0x10),
+            machine: read_u16(0x12),
+            version: read_u32(0x14),
+            entry: read_address(0x18),
+            program_header_offset: read_address(0x18 + address_size),
+            section_header_offset: read_address(0x18 + address_size * 2),
+            flags: read_u32(0x18 + address_size * 3),
+            header_size: read_u16(sizes),
+            program_header_entry_size: read_u16(sizes + 2),
+            program_header_entry_count: read_u16(sizes + 4),
+            section_header_entry_size: read_u16(sizes + 6),
+            section_header_entry_count: read_u16(sizes + 8),
+            section_header_names_index: read_u16(sizes + 10),
+        }
+    }
+}
+
+pub fn class_name(class: u8) -> String {
+    match class {
+        0 => String::from("none"),
+        1 => String::from("ELF32"),
+        2 => String::from("ELF64"),
+        _ => format!("<unknown: {class:x}>"),
+    }
+}
+
+pub fn data_encoding_name(encoding: u8) -> String {
+    match encoding {
+        0 => String::from("none"),
+        1 => String::from("2's complement, little endian"),
+        2 => String::from("2's complement, big endian"),
+        _ => format!("<unknown: {encoding:x}>"),
+    }
+}
+
+pub const ELFOSABI_SYSV: u8 = 0;
+pub const ELFOSABI_GNU: u8 = 3;
+pub const ELFOSABI_ARM: u8 = 97;
+
+pub const EM_386: u16 = 0x03;
+pub const EM_PPC: u16 = 0x14;
+pub const EM_PPC64: u16 = 0x15;
+pub const EM_ARM: u16 = 0x28;
+pub const EM_X86_64: u16 = 0x3E;
+pub const EM_TI_C6000: u16 = 0x8C;
+pub const EM_AARCH64: u16 = 0xB7;
+
+/// The `EI_OSABI` names `readelf -h` uses; values from 64 up are machine specific.
+pub fn os_abi_name(os_abi: u8, machine: u16) -> String {
+    let name = match (os_abi, machine) {
+        (ELFOSABI_SYSV, _) => "UNIX - System V",
+        (1, _) => "UNIX - HP-UX",
+        (2, _) => "UNIX - NetBSD",
+        (ELFOSABI_GNU, _) => "UNIX - GNU",
+        (6, _) => "UNIX - Solaris",
+        (7, _) => "UNIX - AIX",
+        (8, _) => "UNIX - IRIX",
+        (9, _) => "UNIX - FreeBSD",
+        (10, _) => "UNIX - TRU64",
+        (11, _) => "Novell - Modesto",
+        (12, _) => "UNIX - OpenBSD",
+        (13, _) => "VMS - OpenVMS",
+        (14, _) => "HP - Non-Stop Kernel",
+        (15, _) => "AROS",
+        (16, _) => "FenixOS",
+        (17, _) => "Nuxi CloudABI",
+        (18, _) => "Stratus Technologies OpenVOS",
+        (64, EM_TI_C6000) => "Bare-metal C6000",
+        (65, EM_ARM) => "ARM FDPIC",
+        (65, EM_TI_C6000) => "Linux C6000",
+        (ELFOSABI_ARM, EM_ARM) => "ARM",
+        _ => return format!("<unknown: {os_abi:x}>"),
+    };
+
+    String::from(name)
+}
+
+/// The `e_machine` names `readelf -h` uses.
+pub fn machine_name(machine: u16) -> String {
+    let name = match machine {
+        0x0000 => "None",
+        0x0001 => "WE32100",
+        0x0002 => "Sparc",
+        0x0003 => "Intel 80386",
+        0x0004 => "MC68000",
+        0x0005 => "MC88000",
+        0x0006 => "Intel MCU",
+        0x0007 => "Intel 80860",
+        0x0008 => "MIPS R3000",
+        0x0009 => "IBM System/370",
+        0x000A => "MIPS R4000 big-endian",
+        0x000B => "Sparc v9 (old)",
+        0x000F => "HPPA",
+        0x0011 => "Fujitsu VPP500",
+        0x0012 => "Sparc v8+",
+        0x0013 => "Intel 80960",
+        0x0014 => "PowerPC",
+        0x0015 => "PowerPC64",
+        0x0016 => "IBM S/390",
+        0x0017 => "SPU",
+        0x0024 => "Renesas V850 (using RH850 ABI)",
+        0x0025 => "Fujitsu FR20",
+        0x0026 => "TRW RH32",
+        0x0027 => "MCORE",
+        0x0028 => "ARM",
+        0x0029 => "Digital Alpha (old)",
+        0x002A => "Renesas / SuperH SH",
+        0x002B => "Sparc v9",
+        0x002C => "Siemens Tricore",
+        0x002D => "ARC",
+        0x002E => "Renesas H8/300",
+        0x002F => "Renesas H8/300H",
+        0x0030 => "Renesas H8S",
+        0x0031 => "Renesas H8/500",
+        0x0032 => "Intel IA-64",
+        0x0033 => "Stanford MIPS-X",
+        0x0034 => "Motorola Coldfire",
+        0x0035 => "Motorola MC68HC12 Microcontroller",
+        0x0036 => "Fujitsu Multimedia Accelerator",
+        0x0037 => "Siemens PCP",
+        0x0038 => "Sony nCPU embedded RISC processor",
+        0x0039 => "Denso NDR1 microprocesspr",
+        0x003A => "Motorola Star*Core processor",
+        0x003B => "Toyota ME16 processor",
+        0x003C => "STMicroelectronics ST100 processor",
+        0x003D => "Advanced Logic Corp. TinyJ embedded processor",
+        0x003E => "Advanced Micro Devices X86-64",
+        0x003F => "Sony DSP processor",
+        0x0040 => "Digital Equipment Corp. PDP-10",
+        0x0041 => "Digital Equipment Corp. PDP-11",
+        0x0042 => "Siemens FX66 microcontroller",
+        0x0043 => "STMicroelectronics ST9+ 8/16 bit microcontroller",
+        0x0044 => "STMicroelectronics ST7 8-bit microcontroller",
+        0x0045 => "Motorola MC68HC16 Microcontroller",
+        0x0046 => "Motorola MC68HC11 Microcontroller",
+        0x0047 => "Motorola MC68HC08 Microcontroller",
+        0x0048 => "Motorola MC68HC05 Microcontroller",
+        0x0049 => "Silicon Graphics SVx",
+        0x004A => "STMicroelectronics ST19 8-bit microcontroller",
+        0x004B => "Digital VAX",
+        0x004C => "Axis Communications 32-bit embedded processor",
+        0x004D => "Infineon Technologies 32-bit embedded cpu",
+        0x004E => "Element 14 64-bit DSP processor",
+        0x004F => "LSI Logic's 16-bit DSP processor",
+        0x0050 => "Donald Knuth's educational 64-bit processor",
+        0x0051 => "Harvard Universitys's machine-independent object format",
+        0x0052 => "Vitesse Prism",
+        0x0053 => "Atmel AVR 8-bit microcontroller",
+        0x0054 => "Fujitsu FR30",
+        0x0055 => "d10v",
+        0x0056 => "d30v",
+        0x0057 => "Renesas V850",
+        0x0058 => "Renesas M32R (formerly Mitsubishi M32r)",
+        0x0059 => "mn10300",
+        0x005A => "mn10200",
+        0x005B => "picoJava",
+        0x005C => "OpenRISC 1000",
+        0x005D => "ARCompact",
+        0x005E => "Tensilica Xtensa Processor",
+        0x005F => "Alphamosaic VideoCore processor",
+        0x0060 => "Thompson Multimedia General Purpose Processor",
+        0x0061 => "National Semiconductor 32000 series",
+        0x0062 => "Tenor Network TPC processor",
+        0x0063 => "Trebia SNP 1000 processor",
+        0x0064 => "STMicroelectronics ST200 microcontroller",
+        0x0065 => "Ubicom IP2xxx 8-bit microcontrollers",
+        0x0066 => "MAX Processor",
+        0x0067 => "National Semiconductor CompactRISC",
+        0x0068 => "Fujitsu F2MC16",
+        0x0069 => "Texas Instruments msp430 microcontroller",
+        0x006A => "Analog Devices Blackfin",
+        0x006B => "S1C33 Family of Seiko Epson processors",
+        0x006C => "Sharp embedded microprocessor",
+        0x006D => "Arca RISC microprocessor",
+        0x006E => "Unicore",
+        0x006F => "eXcess 16/32/64-bit configurable embedded CPU",
+        0x0070 => "Icera Semiconductor Inc. Deep Execution Processor",
+        0x0071 => "Altera Nios II",
+        0x0072 => "National Semiconductor CRX microprocessor",
+        0x0073 => "Motorola XGATE embedded processor",
+        0x0074 => "Infineon Technologies xc16x",
+        0x0075 => "Renesas M16C series microprocessors",
+        0x0076 => "Microchip Technology dsPIC30F Digital Signal Controller",
+        0x0077 => "Freescale Communication Engine RISC core",
+        0x0078 => "Renesas M32c",
+        0x0083 => "Altium TSK3000 core",
+        0x0084 => "Freescale RS08 embedded processor",
+        0x0086 => "Cyan Technology eCOG2 microprocessor",
+        0x0087 => "SUNPLUS S+Core",
+        0x0088 => "New Japan Radio (NJR) 24-bit DSP Processor",
+        0x0089 => "Broadcom VideoCore III processor",
+        0x008A => "Lattice Mico32",
+        0x008B => "Seiko Epson C17 family",
+        0x008C => "Texas Instruments TMS320C6000 DSP family",
+        0x008D => "Texas Instruments TMS320C2000 DSP family",
+        0x008E => "Texas Instruments TMS320C55x DSP family",
+        0x0090 => "TI PRU I/O processor",
+        0x00A0 => "STMicroelectronics 64bit VLIW Data Signal Processor",
+        0x00A1 => "Cypress M8C microprocessor",
+        0x00A2 => "Renesas R32C series microprocessors",
+        0x00A3 => "NXP Semiconductors TriMedia architecture family",
+        0x00A4 => "QUALCOMM DSP6 Processor",
+        0x00A5 => "Intel 8051 and variants",
+        0x00A6 => "STMicroelectronics STxP7x family",
+        0x00A7 => "Andes Technology compact code size embedded RISC processor family",
+        0x00A8 => "Cyan Technology eCOG1X family",
+        0x00A9 => "Dallas Semiconductor MAXQ30 Core microcontrollers",
+        0x00AA => "New Japan Radio (NJR) 16-bit DSP Processor",
+        0x00AB => "M2000 Reconfigurable RISC Microprocessor",
+        0x00AC => "Cray Inc. NV2 vector architecture",
+        0x00AD => "Renesas RX",
+        0x00AE => "Imagination Technologies Meta processor architecture",
+        0x00AF => "MCST Elbrus general purpose hardware architecture",
+        0x00B0 => "Cyan Technology eCOG16 family",
+        0x00B1 => "Xilinx MicroBlaze",
+        0x00B2 => "Freescale Extended Time Processing Unit",
+        0x00B3 => "Infineon Technologies SLE9X core",
+        0x00B4 => "Intel L1OM",
+        0x00B5 => "Intel K1OM",
+        0x00B6 => "Intel (reserved)",
+        0x00B7 => "AArch64",
+        0x00B8 => "ARM (reserved)",
+        0x00B9 => "Atmel Corporation 32-bit microprocessor",
+        0x00BA => "STMicroeletronics STM8 8-bit microcontroller",
+        0x00BB => "Tilera TILE64 multicore architecture family",
+        0x00BC => "Tilera TILEPro multicore architecture family",
+        0x00BD => "Xilinx MicroBlaze",
+        0x00BE => "NVIDIA CUDA architecture",
+        0x00BF => "Tilera TILE-Gx multicore architecture family",
+        0x00C0 => "CloudShield architecture family",
+        0x00C1 => "KIPO-KAIST Core-A 1st generation processor family",
+        0x00C2 => "KIPO-KAIST Core-A 2nd generation processor family",
+        0x00C3 => "ARCv2",
+        0x00C4 => "Open8 8-bit RISC soft processor core",
+        0x00C5 => "Renesas RL78",
+        0x00C6 => "Broadcom VideoCore V processor",
+        0x00C7 => "Renesas 78K0R",
+        0x00C8 => "Freescale 56800EX Digital Signal Controller (DSC)",
+        0x00C9 => "Beyond BA1 CPU architecture",
+        0x00CA => "Beyond BA2 CPU architecture",
+        0x00CB => "XMOS xCORE processor family",
+        0x00CC => "Microchip 8-bit PIC(r) family",
+        0x00CD => "Intel Graphics Technology",
+        0x00D2 => "KM211 KM32 32-bit processor",
+        0x00D3 => "KM211 KMX32 32-bit processor",
+        0x00D4 => "KM211 KMX16 16-bit processor",
+        0x00D5 => "KM211 KMX8 8-bit processor",
+        0x00D6 => "KM211 KVARC processor",
+        0x00D7 => "Paneve CDP architecture family",
+        0x00D8 => "Cognitive Smart Memory Processor",
+        0x00D9 => "Bluechip Systems CoolEngine",
+        0x00DA => "Nanoradio Optimized RISC",
+        0x00DB => "CSR Kalimba architecture family",
+        0x00DC => "Zilog Z80",
+        0x00DD => "CDS VISIUMcore processor",
+        0x00DE => "FTDI Chip FT32",
+        0x00DF => "Moxie",
+        0x00E0 => "AMD GPU",
+        0x00F3 => "RISC-V",
+        0x00F4 => "Lanai 32-bit processor",
+        0x00F5 => "CEVA Processor Architecture Family",
+        0x00F6 => "CEVA X2 Processor Family",
+        0x00F7 => "Linux BPF",
+        0x00F8 => "Graphcore Intelligent Processing Unit",
+        0x00F9 => "Imagination Technologies",
+        0x00FA => "Netronome Flow Processor",
+        0x00FB => "NEC Vector Engine",
+        0x00FC => "C-SKY",
+        0x00FD => "Synopsys ARCv2.3 64-bit",
+        0x00FE => "MOS Technology MCS 6502 processor",
+        0x00FF => "Synopsys ARCv2.3 32-bit",
+        0x0100 => "Kalray VLIW core of the MPPA processor family",
+        0x0101 => "WDC 65816/65C816",
+        0x0102 => "LoongArch",
+        0x0103 => "ChipON KungFu32",
+        0x1057 => "Atmel AVR 8-bit microcontroller",
+        0x1223 => "Adapteva EPIPHANY",
+        0x2530 => "Morpho Techologies MT processor",
+        0x3330 => "Fujitsu FR30",
+        0x4157 => "Web Assembly",
+        0x4688 => "Infineon Technologies xc16x",
+        0x4DEF => "Freescale S12Z",
+        0x5AA5 => "OpenDLX",
+        0x7650 => "d10v",
+        0x7676 => "d30v",
+        0x8217 => "Ubicom IP2xxx 8-bit microcontrollers",
+        0x9026 => "Alpha",
+        0x9041 => "Renesas M32R (formerly Mitsubishi M32r)",
+        0x9080 => "Renesas V850",
+        0xA390 => "IBM S/390",
+        0xABC7 => "Tensilica Xtensa Processor",
+        0xAD45 => "Sanyo XStormy16 CPU core",
+        0xBAAB => "Xilinx MicroBlaze",
+        0xBEEF => "mn10300",
+        0xDEAD => "mn10200",
+        0xF00D => "Toshiba MeP Media Engine",
+        0xFEB0 => "Altera Nios",
+        0xFEBA => "Vitesse IQ2000",
+        0xFEBB => "Altera Nios",
+        _ => return format!("<unknown>: {machine:#x}"),
+    };
+
+    String::from(name)
+}
+
+impl ElfHeader {
+    pub fn file_header(&self) -> &FileHeader {
+        &self.header
+    }
+
+    /// Whether an `ET_DYN` file is a position independent executable rather than a shared object,
+    /// going by `DF_1_PIE` as readelf does. `PT_INTERP` is no guide, as libc.so.6 has one too.
+    pub fn is_position_independent_executable(&self) -> bool {
+        self.header.file_type == ET_DYN
+            && self.dynamic_value(DT_FLAGS_1).unwrap_or(0) & DF_1_PIE != 0
+    }
+
+    pub fn file_type_name(&self) -> String {
+        match self.header.file_type {
+            ET_NONE => String::from("NONE (None)"),
+            ET_REL => String::from("REL (Relocatable file)"),
+            ET_EXEC => String::from("EXEC (Executable file)"),
+            ET_DYN if self.is_position_independent_executable() => {
+                String::from("DYN (Position-Independent Executable file)")
+            }
+            ET_DYN => String::from("DYN (Shared object file)"),
+            ET_CORE => String::from("CORE (Core file)"),
+            value @ 0xFE00..=0xFEFF => format!("OS Specific: ({value:x})"),
+            value @ 0xFF00..=0xFFFF => format!("Processor Specific: ({value:x})"),
+            value => format!("<unknown>: {value:x}"),
+        }
+    }
+
+    /// Inconsistencies between header fields, and between the header and the file it describes.
+    pub fn header_warnings(&self) -> Vec<String> {
+        let header = &self.header;
+        let file_size = self.data.len() as u64;
+        let mut warnings = Vec::new();
+
+        if header.identification_version as u32 != EV_CURRENT {
+            warnings.push(format!(
+                "EI_VERSION is {} but only {EV_CURRENT} (current) is defined",
+                header.identification_version
+            ));
+        }
+
+        if header.version != EV_CURRENT {
+            warnings.push(format!(
+                "e_version is {:#x} but only {EV_CURRENT:#x} (current) is defined",
+                header.version
+            ));
+        }
+
+        if header.identification[9..].iter().any(|byte| *byte != 0) {
+            warnings.push(String::from("EI_PAD bytes are not zero"));
+        }
+
+        if header.header_size as usize != self.format.header_size() {
+            warnings.push(format!(
+                "e_ehsize is {} but an {} header is {} bytes",
+                header.header_size,
+                class_name(header.class),
+                self.format.header_size()
+            ));
+        }
+
+        if header.program_header_entry_count > 0 {
+            if header.program_header_entry_size as u64 != self.format.program_header_size() {
+                warnings.push(format!(
+                    "e_phentsize is {} but an {} program header is {} bytes",
+                    header.program_header_entry_size,
+                    class_name(header.class),
+                    self.format.program_header_size()
+                ));
+            }
+
+            let end = header.program_header_offset.saturating_add(
+                header.program_header_entry_size as u64 * header.program_header_entry_count as u64,
+            );
+
+            if end > file_size {
+                warnings.push(format!(
+                    "program header table ends at {end:#x}, past the end of the file ({file_size:#x})"
+                ));
+            }
+        }
+
+        if self.section_count > 0 {
+            if header.section_header_entry_size as u64 != self.format.section_header_size() {
+                warnings.push(format!(
+                    "e_shentsize is {} but an {} section header is {} bytes",
+                    header.section_header_entry_size,
+                    class_name(header.class),
+                    self.format.section_header_size()
+                ));
+            }
+
+            let end = header.section_header_offset.saturating_add(
+                (header.section_header_entry_size as u64).saturating_mul(self.section_count),
+            );
+
+            if end > file_size {
+                warnings.push(format!(
+                    "section header table ends at {end:#x}, past the end of the file ({file_size:#x})"
+                ));
+            }
+
+            if self.section_names_index as u64 >= self.section_count {
+                warnings.push(format!(
+                    "e_shstrndx {} is not below the section header count {}",
+                    self.section_names_index, self.section_count
+                ));
+            }
+        }
+
+        if header.file_type == ET_EXEC && header.entry == 0 {
+            warnings.push(String::from("executable has no entry point"));
+        }
+
+        if matches!(header.file_type, ET_EXEC | ET_DYN) && header.program_header_entry_count == 0 {
+            warnings.push(String::from(
+                "executable or shared object has no program headers",
+            ));
+        }
+
+        warnings
+    }
+
+    /// Formats the header the way `readelf -h` does, one field per line.
+    pub fn header_lines(&self) -> Vec<String> {
+        let header = &self.header;
+
+        let magic = header
+            .identification
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let identification_version = if header.identification_version as u32 == EV_CURRENT {
+            format!("{} (current)", header.identification_version)
+        } else {
+            format!("{} <unknown>", header.identification_version)
+        };
+
+        let fields = [
+            ("Class", class_name(header.class)),
+            ("Data", data_encoding_name(header.data_encoding)),
+            ("Version", identification_version),
+            ("OS/ABI", os_abi_name(header.os_abi, header.machine)),
+            ("ABI Version", header.abi_version.to_string()),
+            ("Type", self.file_type_name()),
+            ("Machine", machine_name(header.machine)),
+            ("Version", format!("{:#x}", header.version)),
+            ("Entry point address", format!("{:#x}", header.entry)),
+            (
+                "Start of program headers",
+                format!("{} (bytes into file)", header.program_header_offset),
+            ),
+            (
+                "Start of section headers",
+                format!("{} (bytes into file)", header.section_header_offset),
+            ),
+            ("Flags", format!("{:#x}", header.flags)),
+            (
+                "Size of this header",
+                format!("{} (bytes)", header.header_size),
+            ),
+            (
+                "Size of program headers",
+                format!("{} (bytes)", header.program_header_entry_size),
+            ),
+            (
+                "Number of program headers",
+                header.program_header_entry_count.to_string(),
+            ),
+            (
+                "Size of section headers",
+                format!("{} (bytes)", header.section_header_entry_size),
+            ),
+            (
+                "Number of section headers",
+                if header.section_header_entry_count == 0 && self.section_count != 0 {
+                    format!("0 ({})", self.section_count)
+                } else {
+                    header.section_header_entry_count.to_string()
+                },
+            ),
+            (
+                "Section header string table index",
+                if header.section_header_names_index == SHN_XINDEX {
+                    format!("{} ({})", SHN_XINDEX, self.section_names_index)
+                } else {
+                    header.section_header_names_index.to_string()
+                },
+            ),
+        ];
+
+        let mut lines = vec![String::from("ELF Header:"), format!("  Magic:   {magic}")];
+
+        for (name, value) in fields {
+            lines.push(format!("  {:<35}{}", format!("{name}:"), value));
+        }
+
+        lines
+    }
+
+    pub fn dump_header(&self, out: &mut dyn OutputSink) {
+        out.begin_section("elf header");
+
+        for line in self.header_lines() {
+            out.write_line(line.as_str());
+        }
+
+        for warning in self.header_warnings() {
+            out.write_line(format!("  Warning: {warning}").as_str());
+        }
+
+        out.write_line("");
+    }
+}
@@ -0,0 +1,556 @@
+use super::{BitFormat, EM_AARCH64, EM_X86_64, ElfHeader, PT_NOTE, SHT_NOTE};
+use crate::decode_byte::*;
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+pub const NT_GNU_ABI_TAG: u32 = 1;
+pub const NT_GNU_HWCAP: u32 = 2;
+pub const NT_GNU_BUILD_ID: u32 = 3;
+pub const NT_GNU_GOLD_VERSION: u32 = 4;
+pub const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+
+pub const NT_PRSTATUS: u32 = 1;
+pub const NT_FPREGSET: u32 = 2;
+pub const NT_PRPSINFO: u32 = 3;
+pub const NT_TASKSTRUCT: u32 = 4;
+pub const NT_AUXV: u32 = 6;
+pub const NT_SIGINFO: u32 = 0x53494749;
+pub const NT_FILE: u32 = 0x46494C45;
+pub const NT_X86_XSTATE: u32 = 0x202;
+pub const NT_ARM_TLS: u32 = 0x401;
+pub const NT_ARM_HW_BREAK: u32 = 0x402;
+pub const NT_ARM_HW_WATCH: u32 = 0x403;
+pub const NT_ARM_SYSTEM_CALL: u32 = 0x404;
+pub const NT_ARM_SVE: u32 = 0x405;
+pub const NT_ARM_PAC_MASK: u32 = 0x406;
+
+pub const NT_FDO_PACKAGING_METADATA: u32 = 0xCAFE1A7E;
+pub const NT_GO_BUILDID: u32 = 4;
+
+pub const GNU_PROPERTY_STACK_SIZE: u32 = 1;
+pub const GNU_PROPERTY_NO_COPY_ON_PROTECTED: u32 = 2;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xC0000000;
+pub const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xC0000002;
+pub const GNU_PROPERTY_X86_ISA_1_NEEDED: u32 = 0xC0008002;
+pub const GNU_PROPERTY_X86_FEATURE_2_USED: u32 = 0xC0010001;
+pub const GNU_PROPERTY_X86_ISA_1_USED: u32 = 0xC0010002;
+
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_BTI: u32 = 0x1;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_PAC: u32 = 0x2;
+
+const NOTE_TABLE: &str = "note";
+
+/// One entry of a note section or segment.
+#[derive(Clone, Debug)]
+pub struct Note {
+    /// The note's name, such as `GNU`, `CORE` or `Go`, without its terminating NUL.
+    pub owner: String,
+    pub note_type: u32,
+    pub descriptor: Vec<u8>,
+}
+
+/// The notes of one `SHT_NOTE` section, or of one `PT_NOTE` segment when there are no sections.
+#[derive(Clone, Debug)]
+pub struct NoteTable {
+    /// The section name; `None` for notes read from a segment.
+    pub name: Option<String>,
+    pub offset: u64,
+    pub size: u64,
+    pub notes: Vec<Note>,
+}
+
+/// A single `pr_type`/`pr_data` pair from an `NT_GNU_PROPERTY_TYPE_0` note.
+#[derive(Clone, Debug)]
+pub struct GnuProperty {
+    pub property_type: u32,
+    pub data: Vec<u8>,
+}
+
+/// The description readelf prints for a note type, which depends on who owns the note.
+pub fn note_type_name(owner: &str, note_type: u32) -> String {
+    let name = match (owner, note_type) {
+        ("GNU", NT_GNU_ABI_TAG) => "NT_GNU_ABI_TAG (ABI version tag)",
+        ("GNU", NT_GNU_HWCAP) => "NT_GNU_HWCAP (DSO-supplied software HWCAP info)",
+        ("GNU", NT_GNU_BUILD_ID) => "NT_GNU_BUILD_ID (unique build ID bitstring)",
+        ("GNU", NT_GNU_GOLD_VERSION) => "NT_GNU_GOLD_VERSION (gold version)",
+        ("GNU", NT_GNU_PROPERTY_TYPE_0) => "NT_GNU_PROPERTY_TYPE_0",
+        ("FDO", NT_FDO_PACKAGING_METADATA) => "FDO_PACKAGING_METADATA",
+        ("Go", 1) => "GO_PKGLIST",
+        ("Go", 2) => "GO_ABIHASH",
+        ("Go", 3) => "GO_DEPS",
+        ("Go", NT_GO_BUILDID) => "GO BUILDID",
+        ("CORE" | "LINUX", NT_PRSTATUS) => "NT_PRSTATUS (prstatus structure)",
+        ("CORE" | "LINUX", NT_FPREGSET) => "NT_FPREGSET (floating point registers)",
+        ("CORE" | "LINUX", NT_PRPSINFO) => "NT_PRPSINFO (prpsinfo structure)",
+        ("CORE" | "LINUX", NT_TASKSTRUCT) => "NT_TASKSTRUCT (task structure)",
+        ("CORE" | "LINUX", NT_AUXV) => "NT_AUXV (auxiliary vector)",
+        ("CORE" | "LINUX", NT_SIGINFO) => "NT_SIGINFO (siginfo_t data)",
+        ("CORE" | "LINUX", NT_FILE) => "NT_FILE (mapped files)",
+        ("CORE" | "LINUX", NT_X86_XSTATE) => "NT_X86_XSTATE (x86 XSAVE extended state)",
+        ("CORE" | "LINUX", NT_ARM_TLS) => "NT_ARM_TLS (AArch TLS registers)",
+        ("CORE" | "LINUX", NT_ARM_HW_BREAK) => {
+            "NT_ARM_HW_BREAK (AArch hardware breakpoint registers)"
+        }
+        ("CORE" | "LINUX", NT_ARM_HW_WATCH) => {
+            "NT_ARM_HW_WATCH (AArch hardware watchpoint registers)"
+        }
+        ("CORE" | "LINUX", NT_ARM_SYSTEM_CALL) => "NT_ARM_SYSTEM_CALL (AArch system call number)",
+        ("CORE" | "LINUX", NT_ARM_SVE) => "NT_ARM_SVE (AArch SVE registers)",
+        ("CORE" | "LINUX", NT_ARM_PAC_MASK) => {
+            "NT_ARM_PAC_MASK (AArch pointer authentication code masks)"
+        }
+        _ => return format!("Unknown note type: ({note_type:#010x})"),
+    };
+
+    String::from(name)
+}
+
+fn abi_tag_os_name(os: u32) -> String {
+    match os {
+        0 => String::from("Linux"),
+        1 => String::from("Hurd"),
+        2 => String::from("Solaris"),
+        3 => String::from("FreeBSD"),
+        4 => String::from("NetBSD"),
+        5 => String::from("Syllable"),
+        6 => String::from("NaCl"),
+        _ => format!("Unknown OS {os}"),
+    }
+}
+
+fn bit_names(bits: u32, names: &[&str]) -> String {
+    let mut found = names
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| bits & (1 << bit) != 0)
+        .map(|(_, name)| String::from(*name))
+        .collect::<Vec<_>>();
+
+    let unknown = bits & !((1u32 << names.len()) - 1);
+
+    if unknown != 0 {
+        found.push(format!("<unknown: {unknown:x}>"));
+    }
+
+    if found.is_empty() {
+        return String::from("<None>");
+    }
+
+    found.join(", ")
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The text of a NUL terminated (or unterminated) string descriptor.
+fn descriptor_string(bytes: &[u8]) -> String {
+    let length = bytes
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(bytes.len());
+
+    String::from_utf8_lossy(&bytes[..length]).into_owned()
+}
+
+impl ElfHeader {
+    /// Reads the note at `position`, returning it with the offset of the next one.
+    fn parse_note(&self, position: u64, end: u64, padding: u64) -> Result<(Note, u64)> {
+        let align = |value: u64, to: u64| value.saturating_add(to - 1) & !(to - 1);
+
+        let name_size = self.read::<u32>("note header", position)? as u64;
+        let descriptor_size = self.read::<u32>("note header", position.saturating_add(4))? as u64;
+        let note_type = self.read::<u32>("note header", position.saturating_add(8))?;
+
+        let name_start = position.saturating_add(12);
+        let descriptor_start = align(name_start.saturating_add(name_size), 4);
+        let descriptor_end = descriptor_start.saturating_add(descriptor_size);
+
+        if descriptor_end > end {
+            return Err(Error::OutOfRange {
+                what: "note descriptor end",
+                offset: descriptor_end,
+                limit: end,
+            });
+        }
+
+        let truncated = || Error::Truncated {
+            what: "note",
+            offset: position,
+            needed: descriptor_end - position,
+            available: self.data.len() as u64,
+        };
+
+        let name = self
+            .file_range(name_start, name_size)
+            .ok_or_else(truncated)?;
+        let descriptor = self
+            .file_range(descriptor_start, descriptor_size)
+            .ok_or_else(truncated)?;
+
+        let note = Note {
+            owner: descriptor_string(name),
+            note_type,
+            descriptor: descriptor.to_vec(),
+        };
+
+        Ok((note, align(descriptor_end, padding)))
+    }
+
+    /// Splits `length` bytes of notes at `offset`; in sections and segments aligned to 8 bytes
+    /// descriptors are padded to 8 rather than 4 bytes.
+    fn parse_notes(&mut self, offset: u64, length: u64, alignment: u64) -> Vec<Note> {
+        let padding = if alignment == 8 { 8 } else { 4 };
+        let end = offset.saturating_add(length);
+        let mut position = offset;
+        let mut notes = Vec::new();
+
+        while position.saturating_add(12) <= end {
+            match self.parse_note(position, end, padding) {
+                Ok((note, next)) => {
+                    notes.push(note);
+                    position = next;
+                }
+                Err(error) => {
+                    self.diagnostics.push(Diagnostic {
+                        table: NOTE_TABLE,
+                        index: Some(notes.len()),
+                        error,
+                    });
+                    break;
+                }
+            }
+        }
+
+        notes
+    }
+
+    pub(super) fn file_range(&self, offset: u64, length: u64) -> Option<&[u8]> {
+        let start = usize::try_from(offset).ok()?;
+        let length = usize::try_from(length).ok()?;
+
+        self.data.get(start..start.checked_add(length)?)
+    }
+
+    pub(super) fn parse_note_tables(&mut self) {
+        let sections = self
+            .sections
+            .iter()
+            .filter(|section| section.section_type == SHT_NOTE)
+            .map(|section| {
+                (
+                    Some(section.name.clone()),
+                    section.offset,
+                    section.size,
+                    section.alignment,
+                )
+            });
+
+        let segments = self
+            .segments
+            .iter()
+            .filter(|segment| segment.segment_type == PT_NOTE)
+            .map(|segment| {
+                (
+                    Option::None,
+                    segment.offset,
+                    segment.file_size,
+                    segment.alignment,
+                )
+            });
+
+        // readelf prefers sections and only walks PT_NOTE segments when there are none
+        let locations = if self.sections.is_empty() {
+            segments.collect::<Vec<_>>()
+        } else {
+            sections.collect::<Vec<_>>()
+        };
+
+        for (name, offset, size, alignment) in locations {
+            let notes = self.parse_notes(offset, size, alignment);
+
+            self.notes.push(NoteTable {
+                name,
+                offset,
+                size,
+                notes,
+            });
+        }
+    }
+
+    pub fn note_tables(&self) -> &[NoteTable] {
+        &self.notes
+    }
+
+    /// Every note in the file, in file order.
+    pub fn notes(&self) -> impl Iterator<Item = &Note> {
+        self.notes.iter().flat_map(|table| table.notes.iter())
+    }
+
+    fn find_note(&self, owner: &str, note_type: u32) -> Option<&Note> {
+        self.notes()
+            .find(|note| note.owner == owner && note.note_type == note_type)
+    }
+
+    /// The `NT_GNU_BUILD_ID` as lowercase hex, the key crash reports are matched on.
+    pub fn build_id(&self) -> Option<String> {
+        self.find_note("GNU", NT_GNU_BUILD_ID)
+            .map(|note| hex_string(&note.descriptor))
+    }
+
+    /// The `--build-id` of a Go binary, which the Go linker records in its own note.
+    pub fn go_build_id(&self) -> Option<String> {
+        self.find_note("Go", NT_GO_BUILDID)
+            .map(|note| descriptor_string(&note.descriptor))
+    }
+
+    /// The `.note.package` JSON written by distribution packaging tools.
+    pub fn package_metadata(&self) -> Option<String> {
+        self.find_note("FDO", NT_FDO_PACKAGING_METADATA)
+            .map(|note| descriptor_string(&note.descriptor))
+    }
+
+    /// The properties of every `NT_GNU_PROPERTY_TYPE_0` note.
+    pub fn gnu_properties(&self) -> Vec<GnuProperty> {
+        self.notes()
+            .filter(|note| note.owner == "GNU" && note.note_type == NT_GNU_PROPERTY_TYPE_0)
+            .flat_map(|note| self.split_gnu_properties(&note.descriptor))
+            .collect()
+    }
+
+    /// The bits of the `GNU_PROPERTY_AARCH64_FEATURE_1_AND` property, if there is one.
+    pub fn aarch64_feature_1(&self) -> Option<u32> {
+        self.gnu_properties()
+            .iter()
+            .find(|property| property.property_type == GNU_PROPERTY_AARCH64_FEATURE_1_AND)
+            .and_then(|property| get_value::<u32>(self.endianness, &property.data))
+    }
+
+    fn split_gnu_properties(&self, descriptor: &[u8]) -> Vec<GnuProperty> {
+        let padding = match self.format {
+            BitFormat::Bit32 => 4,
+            BitFormat::Bit64 => 8,
+        };
+
+        let mut properties = Vec::new();
+        let mut position = 0;
+
+        while position + 8 <= descriptor.len() {
+            let property_type = read_value::<u32>(self.endianness, descriptor, position);
+            let size = read_value::<u32>(self.endianness, descriptor, position + 4);
+
+            let (Some(property_type), Some(size)) = (property_type, size) else {
+                break;
+            };
+
+            let start = position + 8;
+            let Some(data) = descriptor.get(start..start.saturating_add(size as usize)) else {
+                break;
+            };
+
+            properties.push(GnuProperty {
+                property_type,
+                data: data.to_vec(),
+            });
+
+            position = (start + size as usize).div_ceil(padding) * padding;
+        }
+
+        properties
+    }
+
+    fn gnu_property_string(&self, property: &GnuProperty) -> String {
+        let value = get_value::<u32>(self.endianness, &property.data);
+        let machine = self.header.machine;
+
+        match (property.property_type, value) {
+            (GNU_PROPERTY_STACK_SIZE, _) => {
+                let size = match self.format {
+                    BitFormat::Bit32 => value.map(|value| value as u64),
+                    BitFormat::Bit64 => get_value::<u64>(self.endianness, &property.data),
+                };
+
+                match size {
+                    Some(size) => format!("stack size: {size:#x}"),
+                    Option::None => String::from("stack size: <corrupt>"),
+                }
+            }
+            (GNU_PROPERTY_NO_COPY_ON_PROTECTED, _) => String::from("no copy on protected"),
+            (GNU_PROPERTY_AARCH64_FEATURE_1_AND, Some(bits)) if machine == EM_AARCH64 => {
+                format!(
+                    "AArch64 feature: {}",
+                    bit_names(bits, &["BTI", "PAC", "GCS"])
+                )
+            }
+            (GNU_PROPERTY_X86_FEATURE_1_AND, Some(bits)) if machine == EM_X86_64 => format!(
+                "x86 feature: {}",
+                bit_names(bits, &["IBT", "SHSTK", "LAM_U48", "LAM_U57"])
+            ),
+            (GNU_PROPERTY_X86_ISA_1_NEEDED, Some(bits)) if machine == EM_X86_64 => format!(
+                "x86 ISA needed: {}",
+                bit_names(
+                    bits,
+                    &["x86-64-baseline", "x86-64-v2", "x86-64-v3", "x86-64-v4"]
+                )
+            ),
+            (GNU_PROPERTY_X86_ISA_1_USED, Some(bits)) if machine == EM_X86_64 => format!(
+                "x86 ISA used: {}",
+                bit_names(
+                    bits,
+                    &["x86-64-baseline", "x86-64-v2", "x86-64-v3", "x86-64-v4"]
+                )
+            ),
+            (GNU_PROPERTY_X86_FEATURE_2_USED, Some(bits)) if machine == EM_X86_64 => format!(
+                "x86 feature used: {}",
+                bit_names(
+                    bits,
+                    &[
+                        "x86", "x87", "MMX", "XMM", "YMM", "ZMM", "FXSR", "XSAVE", "XSAVEOPT",
+                        "XSAVEC", "TMM", "MASK"
+                    ]
+                )
+            ),
+            (property_type, _) => {
+                let kind = match property_type {
+                    0xC0000000..=0xDFFFFFFF => "processor-specific",
+                    0xE0000000..=0xFFFFFFFF => "application-specific",
+                    _ => "unknown",
+                };
+                let data = property
+                    .data
+                    .iter()
+                    .map(|byte| format!("{byte:02x} "))
+                    .collect::<String>();
+
+                format!("<{kind} type {property_type:#x} data: {data}>")
+            }
+        }
+    }
+
+    /// The decoded description readelf prints after a note's type, including its leading indent.
+    fn note_description(&self, note: &Note) -> String {
+        let descriptor = note.descriptor.as_slice();
+        let word = |index: usize| read_value::<u32>(self.endianness, descriptor, index * 4);
+
+        match (note.owner.as_str(), note.note_type) {
+            ("GNU", NT_GNU_BUILD_ID) => format!("    Build ID: {}", hex_string(descriptor)),
+            ("GNU", NT_GNU_ABI_TAG) => match (word(0), word(1), word(2), word(3)) {
+                (Some(os), Some(major), Some(minor), Some(patch)) => format!(
+                    "    OS: {}, ABI: {major}.{minor}.{patch}",
+                    abi_tag_os_name(os)
+                ),
+                _ => String::from("    <corrupt GNU_ABI_TAG>"),
+            },
+            ("GNU", NT_GNU_GOLD_VERSION) => {
+                format!("    Version: {}", descriptor_string(descriptor))
+            }
+            ("GNU", NT_GNU_PROPERTY_TYPE_0) => {
+                let properties = self
+                    .split_gnu_properties(descriptor)
+                    .iter()
+                    .map(|property| self.gnu_property_string(property))
+                    .collect::<Vec<_>>();
+
+                format!("      Properties: {}", properties.join(", "))
+            }
+            ("FDO", NT_FDO_PACKAGING_METADATA) => {
+                format!("    Packaging Metadata: {}", descriptor_string(descriptor))
+            }
+            ("Go", NT_GO_BUILDID) => {
+                format!("   Go Build ID: {}", descriptor_string(descriptor))
+            }
+            _ if descriptor.is_empty() => String::new(),
+            _ => {
+                let bytes = descriptor
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                format!("   description data: {bytes} ")
+            }
+        }
+    }
+
+    /// The compiler and linker version strings recorded in `.comment`, e.g. `rustc version 1.80.0`.
+    pub fn toolchain_versions(&self) -> Vec<String> {
+        let Some(data) = self
+            .section_by_name(".comment")
+            .and_then(|section| self.section_data(section))
+        else {
+            return Vec::new();
+        };
+
+        data.split(|byte| *byte == 0)
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+            .collect()
+    }
+
+    /// Formats the notes the way `readelf -n -W` does, followed by any toolchain versions from `.comment`.
+    pub fn note_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for table in &self.notes {
+            lines.push(String::new());
+            lines.push(match table.name.as_ref() {
+                Some(name) => format!("Displaying notes found in: {name}"),
+                Option::None => format!(
+                    "Displaying notes found at file offset {:#010x} with length {:#010x}:",
+                    table.offset, table.size
+                ),
+            });
+            lines.push(String::from(
+                "  Owner                Data size \tDescription",
+            ));
+
+            for note in &table.notes {
+                // NT_FILE runs over several lines, the first after the type like the others
+                let mut description = match (note.owner.as_str(), note.note_type) {
+                    ("CORE", NT_FILE) => self
+                        .parse_mapped_files(&note.descriptor)
+                        .map(|files| self.mapped_file_lines(&files)),
+                    _ => Option::None,
+                }
+                .unwrap_or_else(|| vec![self.note_description(note)]);
+
+                lines.push(format!(
+                    "  {:<20} {:#010x}\t{}\t{}",
+                    note.owner,
+                    note.descriptor.len(),
+                    note_type_name(note.owner.as_str(), note.note_type),
+                    description.remove(0)
+                ));
+                lines.extend(description);
+            }
+        }
+
+        let toolchain = self.toolchain_versions();
+
+        if !toolchain.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("Toolchain versions recorded in .comment:"));
+
+            for version in toolchain {
+                lines.push(format!("  {version}"));
+            }
+        }
+
+        if lines.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("There are no notes in this file."));
+        }
+
+        lines
+    }
+
+    pub fn dump_notes(&self, out: &mut dyn OutputSink) {
+        out.begin_section("notes");
+
+        for line in self.note_lines() {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+}
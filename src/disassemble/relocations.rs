@@ -0,0 +1,535 @@
+use std::collections::BTreeMap;
+
+use super::{
+    BitFormat, DisassembledSection, EM_AARCH64, EM_X86_64, ET_REL, ElfHeader, SHT_REL, SHT_RELA,
+    SectionHeader, Symbol,
+};
+use crate::error::{Diagnostic, Result};
+use crate::output::OutputSink;
+
+const RELOCATION_TABLE: &str = "relocation table";
+
+/// One entry of a `SHT_REL` or `SHT_RELA` section.
+#[derive(Clone, Debug)]
+pub struct Relocation {
+    pub index: usize,
+    /// Section header index of the relocation table itself.
+    pub table: usize,
+    /// Section header index of the symbol table the entry refers to (`sh_link`).
+    pub symbol_table: usize,
+    /// Section header index of the section being patched (`sh_info`); only meaningful in relocatable files.
+    pub target_section: usize,
+    /// Section offset in relocatable files, virtual address otherwise.
+    pub offset: u64,
+    pub info: u64,
+    pub relocation_type: u32,
+    pub symbol_index: usize,
+    /// `None` for `SHT_REL` entries, whose addend is stored in the patched location.
+    pub addend: Option<i64>,
+}
+
+/// The `R_*` name of relocation `relocation_type` on `machine`.
+pub fn relocation_type_name(machine: u16, relocation_type: u32) -> String {
+    let name = match machine {
+        EM_AARCH64 => aarch64_relocation_type_name(relocation_type),
+        EM_X86_64 => x86_64_relocation_type_name(relocation_type),
+        _ => Option::None,
+    };
+
+    match name {
+        Some(name) => String::from(name),
+        Option::None => format!("unrecognized: {relocation_type:x}"),
+    }
+}
+
+/// The width of the field an absolute relocation (`S + A`) writes, for the types debug sections use.
+pub(super) fn absolute_relocation_size(machine: u16, relocation_type: u32) -> Option<usize> {
+    match (machine, relocation_type) {
+        // R_AARCH64_ABS64, R_AARCH64_ABS32 and R_AARCH64_P32_ABS32
+        (EM_AARCH64, 257) => Some(8),
+        (EM_AARCH64, 258 | 1) => Some(4),
+        // R_X86_64_64, R_X86_64_32 and R_X86_64_32S
+        (EM_X86_64, 1) => Some(8),
+        (EM_X86_64, 10 | 11) => Some(4),
+        _ => Option::None,
+    }
+}
+
+fn aarch64_relocation_type_name(relocation_type: u32) -> Option<&'static str> {
+    let name = match relocation_type {
+        0 => "R_AARCH64_NONE",
+        1 => "R_AARCH64_P32_ABS32",
+        2 => "R_AARCH64_P32_ABS16",
+        3 => "R_AARCH64_P32_PREL32",
+        4 => "R_AARCH64_P32_PREL16",
+        5 => "R_AARCH64_P32_MOVW_UABS_G0",
+        6 => "R_AARCH64_P32_MOVW_UABS_G0_NC",
+        7 => "R_AARCH64_P32_MOVW_UABS_G1",
+        8 => "R_AARCH64_P32_MOVW_SABS_G0",
+        9 => "R_AARCH64_P32_LD_PREL_LO19",
+        10 => "R_AARCH64_P32_ADR_PREL_LO21",
+        11 => "R_AARCH64_P32_ADR_PREL_PG_HI21",
+        12 => "R_AARCH64_P32_ADD_ABS_LO12_NC",
+        13 => "R_AARCH64_P32_LDST8_ABS_LO12_NC",
+        14 => "R_AARCH64_P32_LDST16_ABS_LO12_NC",
+        15 => "R_AARCH64_P32_LDST32_ABS_LO12_NC",
+        16 => "R_AARCH64_P32_LDST64_ABS_LO12_NC",
+        17 => "R_AARCH64_P32_LDST128_ABS_LO12_NC",
+        18 => "R_AARCH64_P32_TSTBR14",
+        19 => "R_AARCH64_P32_CONDBR19",
+        20 => "R_AARCH64_P32_JUMP26",
+        21 => "R_AARCH64_P32_CALL26",
+        22 => "R_AARCH64_P32_MOVW_PREL_G0",
+        23 => "R_AARCH64_P32_MOVW_PREL_G0_NC",
+        24 => "R_AARCH64_P32_MOVW_PREL_G1",
+        25 => "R_AARCH64_P32_GOT_LD_PREL19",
+        26 => "R_AARCH64_P32_ADR_GOT_PAGE",
+        27 => "R_AARCH64_P32_LD32_GOT_LO12_NC",
+        28 => "R_AARCH64_P32_LD32_GOTPAGE_LO14",
+        180 => "R_AARCH64_P32_COPY",
+        181 => "R_AARCH64_P32_GLOB_DAT",
+        182 => "R_AARCH64_P32_JUMP_SLOT",
+        183 => "R_AARCH64_P32_RELATIVE",
+        184 => "R_AARCH64_P32_TLS_DTPMOD",
+        185 => "R_AARCH64_P32_TLS_DTPREL",
+        186 => "R_AARCH64_P32_TLS_TPREL",
+        187 => "R_AARCH64_P32_TLSDESC",
+        188 => "R_AARCH64_P32_IRELATIVE",
+        257 => "R_AARCH64_ABS64",
+        258 => "R_AARCH64_ABS32",
+        259 => "R_AARCH64_ABS16",
+        260 => "R_AARCH64_PREL64",
+        261 => "R_AARCH64_PREL32",
+        262 => "R_AARCH64_PREL16",
+        263 => "R_AARCH64_MOVW_UABS_G0",
+        264 => "R_AARCH64_MOVW_UABS_G0_NC",
+        265 => "R_AARCH64_MOVW_UABS_G1",
+        266 => "R_AARCH64_MOVW_UABS_G1_NC",
+        267 => "R_AARCH64_MOVW_UABS_G2",
+        268 => "R_AARCH64_MOVW_UABS_G2_NC",
+        269 => "R_AARCH64_MOVW_UABS_G3",
+        270 => "R_AARCH64_MOVW_SABS_G0",
+        271 => "R_AARCH64_MOVW_SABS_G1",
+        272 => "R_AARCH64_MOVW_SABS_G2",
+        273 => "R_AARCH64_LD_PREL_LO19",
+        274 => "R_AARCH64_ADR_PREL_LO21",
+        275 => "R_AARCH64_ADR_PREL_PG_HI21",
+        276 => "R_AARCH64_ADR_PREL_PG_HI21_NC",
+        277 => "R_AARCH64_ADD_ABS_LO12_NC",
+        278 => "R_AARCH64_LDST8_ABS_LO12_NC",
+        279 => "R_AARCH64_TSTBR14",
+        280 => "R_AARCH64_CONDBR19",
+        282 => "R_AARCH64_JUMP26",
+        283 => "R_AARCH64_CALL26",
+        284 => "R_AARCH64_LDST16_ABS_LO12_NC",
+        285 => "R_AARCH64_LDST32_ABS_LO12_NC",
+        286 => "R_AARCH64_LDST64_ABS_LO12_NC",
+        287 => "R_AARCH64_MOVW_PREL_G0",
+        288 => "R_AARCH64_MOVW_PREL_G0_NC",
+        289 => "R_AARCH64_MOVW_PREL_G1",
+        290 => "R_AARCH64_MOVW_PREL_G1_NC",
+        291 => "R_AARCH64_MOVW_PREL_G2",
+        292 => "R_AARCH64_MOVW_PREL_G2_NC",
+        293 => "R_AARCH64_MOVW_PREL_G3",
+        299 => "R_AARCH64_LDST128_ABS_LO12_NC",
+        300 => "R_AARCH64_MOVW_GOTOFF_G0",
+        301 => "R_AARCH64_MOVW_GOTOFF_G0_NC",
+        302 => "R_AARCH64_MOVW_GOTOFF_G1",
+        303 => "R_AARCH64_MOVW_GOTOFF_G1_NC",
+        304 => "R_AARCH64_MOVW_GOTOFF_G2",
+        305 => "R_AARCH64_MOVW_GOTOFF_G2_NC",
+        306 => "R_AARCH64_MOVW_GOTOFF_G3",
+        307 => "R_AARCH64_GOTREL64",
+        308 => "R_AARCH64_GOTREL32",
+        309 => "R_AARCH64_GOT_LD_PREL19",
+        310 => "R_AARCH64_LD64_GOTOFF_LO15",
+        311 => "R_AARCH64_ADR_GOT_PAGE",
+        312 => "R_AARCH64_LD64_GOT_LO12_NC",
+        313 => "R_AARCH64_LD64_GOTPAGE_LO15",
+        512 => "R_AARCH64_TLSGD_ADR_PREL21",
+        513 => "R_AARCH64_TLSGD_ADR_PAGE21",
+        514 => "R_AARCH64_TLSGD_ADD_LO12_NC",
+        515 => "R_AARCH64_TLSGD_MOVW_G1",
+        516 => "R_AARCH64_TLSGD_MOVW_G0_NC",
+        517 => "R_AARCH64_TLSLD_ADR_PREL21",
+        518 => "R_AARCH64_TLSLD_ADR_PAGE21",
+        519 => "R_AARCH64_TLSLD_ADD_LO12_NC",
+        520 => "R_AARCH64_TLSLD_MOVW_G1",
+        521 => "R_AARCH64_TLSLD_MOVW_G0_NC",
+        522 => "R_AARCH64_TLSLD_LD_PREL19",
+        523 => "R_AARCH64_TLSLD_MOVW_DTPREL_G2",
+        524 => "R_AARCH64_TLSLD_MOVW_DTPREL_G1",
+        525 => "R_AARCH64_TLSLD_MOVW_DTPREL_G1_NC",
+        526 => "R_AARCH64_TLSLD_MOVW_DTPREL_G0",
+        527 => "R_AARCH64_TLSLD_MOVW_DTPREL_G0_NC",
+        528 => "R_AARCH64_TLSLD_ADD_DTPREL_HI12",
+        529 => "R_AARCH64_TLSLD_ADD_DTPREL_LO12",
+        530 => "R_AARCH64_TLSLD_ADD_DTPREL_LO12_NC",
+        531 => "R_AARCH64_TLSLD_LDST8_DTPREL_LO12",
+        532 => "R_AARCH64_TLSLD_LDST8_DTPREL_LO12_NC",
+        533 => "R_AARCH64_TLSLD_LDST16_DTPREL_LO12",
+        534 => "R_AARCH64_TLSLD_LDST16_DTPREL_LO12_NC",
+        535 => "R_AARCH64_TLSLD_LDST32_DTPREL_LO12",
+        536 => "R_AARCH64_TLSLD_LDST32_DTPREL_LO12_NC",
+        537 => "R_AARCH64_TLSLD_LDST64_DTPREL_LO12",
+        538 => "R_AARCH64_TLSLD_LDST64_DTPREL_LO12_NC",
+        539 => "R_AARCH64_TLSIE_MOVW_GOTTPREL_G1",
+        540 => "R_AARCH64_TLSIE_MOVW_GOTTPREL_G0_NC",
+        541 => "R_AARCH64_TLSIE_ADR_GOTTPREL_PAGE21",
+        542 => "R_AARCH64_TLSIE_LD64_GOTTPREL_LO12_NC",
+        543 => "R_AARCH64_TLSIE_LD_GOTTPREL_PREL19",
+        544 => "R_AARCH64_TLSLE_MOVW_TPREL_G2",
+        545 => "R_AARCH64_TLSLE_MOVW_TPREL_G1",
+        546 => "R_AARCH64_TLSLE_MOVW_TPREL_G1_NC",
+        547 => "R_AARCH64_TLSLE_MOVW_TPREL_G0",
+        548 => "R_AARCH64_TLSLE_MOVW_TPREL_G0_NC",
+        549 => "R_AARCH64_TLSLE_ADD_TPREL_HI12",
+        550 => "R_AARCH64_TLSLE_ADD_TPREL_LO12",
+        551 => "R_AARCH64_TLSLE_ADD_TPREL_LO12_NC",
+        552 => "R_AARCH64_TLSLE_LDST8_TPREL_LO12",
+        553 => "R_AARCH64_TLSLE_LDST8_TPREL_LO12_NC",
+        554 => "R_AARCH64_TLSLE_LDST16_TPREL_LO12",
+        555 => "R_AARCH64_TLSLE_LDST16_TPREL_LO12_NC",
+        556 => "R_AARCH64_TLSLE_LDST32_TPREL_LO12",
+        557 => "R_AARCH64_TLSLE_LDST32_TPREL_LO12_NC",
+        558 => "R_AARCH64_TLSLE_LDST64_TPREL_LO12",
+        559 => "R_AARCH64_TLSLE_LDST64_TPREL_LO12_NC",
+        560 => "R_AARCH64_TLSDESC_LD_PREL19",
+        561 => "R_AARCH64_TLSDESC_ADR_PREL21",
+        562 => "R_AARCH64_TLSDESC_ADR_PAGE21",
+        563 => "R_AARCH64_TLSDESC_LD64_LO12",
+        564 => "R_AARCH64_TLSDESC_ADD_LO12",
+        565 => "R_AARCH64_TLSDESC_OFF_G1",
+        566 => "R_AARCH64_TLSDESC_OFF_G0_NC",
+        567 => "R_AARCH64_TLSDESC_LDR",
+        568 => "R_AARCH64_TLSDESC_ADD",
+        569 => "R_AARCH64_TLSDESC_CALL",
+        570 => "R_AARCH64_TLSLE_LDST128_TPREL_LO12",
+        571 => "R_AARCH64_TLSLE_LDST128_TPREL_LO12_NC",
+        572 => "R_AARCH64_TLSLD_LDST128_DTPREL_LO12",
+        573 => "R_AARCH64_TLSLD_LDST128_DTPREL_LO12_NC",
+        1024 => "R_AARCH64_COPY",
+        1025 => "R_AARCH64_GLOB_DAT",
+        1026 => "R_AARCH64_JUMP_SLOT",
+        1027 => "R_AARCH64_RELATIVE",
+        1028 => "R_AARCH64_TLS_DTPMOD",
+        1029 => "R_AARCH64_TLS_DTPREL",
+        1030 => "R_AARCH64_TLS_TPREL",
+        1031 => "R_AARCH64_TLSDESC",
+        1032 => "R_AARCH64_IRELATIVE",
+        _ => return Option::None,
+    };
+
+    Some(name)
+}
+
+fn x86_64_relocation_type_name(relocation_type: u32) -> Option<&'static str> {
+    let name = match relocation_type {
+        0 => "R_X86_64_NONE",
+        1 => "R_X86_64_64",
+        2 => "R_X86_64_PC32",
+        3 => "R_X86_64_GOT32",
+        4 => "R_X86_64_PLT32",
+        5 => "R_X86_64_COPY",
+        6 => "R_X86_64_GLOB_DAT",
+        7 => "R_X86_64_JUMP_SLOT",
+        8 => "R_X86_64_RELATIVE",
+        9 => "R_X86_64_GOTPCREL",
+        10 => "R_X86_64_32",
+        11 => "R_X86_64_32S",
+        12 => "R_X86_64_16",
+        13 => "R_X86_64_PC16",
+        14 => "R_X86_64_8",
+        15 => "R_X86_64_PC8",
+        16 => "R_X86_64_DTPMOD64",
+        17 => "R_X86_64_DTPOFF64",
+        18 => "R_X86_64_TPOFF64",
+        19 => "R_X86_64_TLSGD",
+        20 => "R_X86_64_TLSLD",
+        21 => "R_X86_64_DTPOFF32",
+        22 => "R_X86_64_GOTTPOFF",
+        23 => "R_X86_64_TPOFF32",
+        24 => "R_X86_64_PC64",
+        25 => "R_X86_64_GOTOFF64",
+        26 => "R_X86_64_GOTPC32",
+        27 => "R_X86_64_GOT64",
+        28 => "R_X86_64_GOTPCREL64",
+        29 => "R_X86_64_GOTPC64",
+        30 => "R_X86_64_GOTPLT64",
+        31 => "R_X86_64_PLTOFF64",
+        32 => "R_X86_64_SIZE32",
+        33 => "R_X86_64_SIZE64",
+        34 => "R_X86_64_GOTPC32_TLSDESC",
+        35 => "R_X86_64_TLSDESC_CALL",
+        36 => "R_X86_64_TLSDESC",
+        37 => "R_X86_64_IRELATIVE",
+        38 => "R_X86_64_RELATIVE64",
+        41 => "R_X86_64_GOTPCRELX",
+        42 => "R_X86_64_REX_GOTPCRELX",
+        _ => return Option::None,
+    };
+
+    Some(name)
+}
+
+fn signed_hex(value: i64) -> String {
+    if value < 0 {
+        format!("-{:x}", value.unsigned_abs())
+    } else {
+        format!("{value:x}")
+    }
+}
+
+impl ElfHeader {
+    fn relocation_entry_size(&self, with_addend: bool) -> u64 {
+        match (self.format, with_addend) {
+            (BitFormat::Bit32, false) => 0x08,
+            (BitFormat::Bit32, true) => 0x0C,
+            (BitFormat::Bit64, false) => 0x10,
+            (BitFormat::Bit64, true) => 0x18,
+        }
+    }
+
+    fn parse_relocation(&self, table: &SectionHeader, index: usize) -> Result<Relocation> {
+        let with_addend = table.section_type == SHT_RELA;
+        let base = table
+            .offset
+            .saturating_add((index as u64).saturating_mul(self.relocation_entry_size(with_addend)));
+        let what = "relocation";
+        let width = self.format.address_size() as u64;
+
+        let offset = self.read_address(what, base)?;
+        let info = self.read_address(what, base.saturating_add(width))?;
+
+        let addend = if with_addend {
+            let addend = match self.format {
+                BitFormat::Bit32 => self.read::<u32>(what, base.saturating_add(8))? as i32 as i64,
+                BitFormat::Bit64 => self.read::<u64>(what, base.saturating_add(16))? as i64,
+            };
+            Some(addend)
+        } else {
+            Option::None
+        };
+
+        // ELF32 packs the symbol index and type into 24 and 8 bits, ELF64 into 32 and 32
+        let (symbol_index, relocation_type) = match self.format {
+            BitFormat::Bit32 => (info >> 8, info & 0xFF),
+            BitFormat::Bit64 => (info >> 32, info & 0xFFFF_FFFF),
+        };
+
+        Ok(Relocation {
+            index,
+            table: table.index,
+            symbol_table: table.link as usize,
+            target_section: table.info as usize,
+            offset,
+            info,
+            relocation_type: relocation_type as u32,
+            symbol_index: symbol_index as usize,
+            addend,
+        })
+    }
+
+    pub(super) fn parse_relocation_tables(&mut self) {
+        let tables = self
+            .sections
+            .iter()
+            .filter(|section| matches!(section.section_type, SHT_REL | SHT_RELA))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for table in tables {
+            if self.section_data(&table).is_none() {
+                continue;
+            }
+
+            let count = table.size / self.relocation_entry_size(table.section_type == SHT_RELA);
+
+            for index in 0..count as usize {
+                match self.parse_relocation(&table, index) {
+                    Ok(relocation) => self.relocations.push(relocation),
+                    Err(error) => self.diagnostics.push(Diagnostic {
+                        table: RELOCATION_TABLE,
+                        index: Some(index),
+                        error,
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Every relocation from every `SHT_REL` and `SHT_RELA` section, in file order.
+    pub fn relocations(&self) -> &[Relocation] {
+        &self.relocations
+    }
+
+    /// The symbol `relocation` refers to, or `None` for index 0 or a bad index.
+    pub fn relocation_symbol(&self, relocation: &Relocation) -> Option<&Symbol> {
+        if relocation.symbol_index == 0 {
+            return Option::None;
+        }
+
+        // matched by index rather than position, as symbols that failed to parse are left out
+        self.symbols_in(relocation.symbol_table)
+            .find(|symbol| symbol.index == relocation.symbol_index)
+    }
+
+    /// A short description of what `relocation` resolves to, such as `helper+0x4` or `0x1120`.
+    pub fn relocation_target(&self, relocation: &Relocation, demangle: bool) -> String {
+        let addend = relocation.addend.unwrap_or(0);
+
+        let Some(symbol) = self.relocation_symbol(relocation) else {
+            return format!("0x{}", signed_hex(addend));
+        };
+
+        let name = self.symbol_display_name(symbol, demangle);
+
+        match addend {
+            0 => name,
+            addend if addend < 0 => format!("{name}-0x{:x}", addend.unsigned_abs()),
+            addend => format!("{name}+0x{addend:x}"),
+        }
+    }
+
+    /// The relocations that patch `code`, keyed by the virtual address they patch.
+    pub fn relocations_in(&self, code: &DisassembledSection) -> BTreeMap<u64, Vec<&Relocation>> {
+        let mut relocations = BTreeMap::<u64, Vec<&Relocation>>::new();
+        let relocatable = self.header.file_type == ET_REL;
+        let size = self
+            .section(code.index)
+            .map(|section| section.size)
+            .unwrap_or(0);
+
+        for relocation in &self.relocations {
+            let address = if relocatable {
+                if relocation.target_section != code.index {
+                    continue;
+                }
+
+                code.address.wrapping_add(relocation.offset)
+            } else {
+                if relocation.offset.wrapping_sub(code.address) >= size {
+                    continue;
+                }
+
+                relocation.offset
+            };
+
+            relocations.entry(address).or_default().push(relocation);
+        }
+
+        relocations
+    }
+
+    /// Formats every relocation table the way `readelf -r -W` does.
+    pub fn relocation_lines(&self, demangle: bool) -> Vec<String> {
+        let mut lines = Vec::new();
+        let wide = matches!(self.format, BitFormat::Bit64);
+        let address_width = self.format.address_size() * 2;
+
+        let tables = self
+            .sections
+            .iter()
+            .filter(|section| matches!(section.section_type, SHT_REL | SHT_RELA));
+
+        for table in tables {
+            let with_addend = table.section_type == SHT_RELA;
+            let relocations = self
+                .relocations
+                .iter()
+                .filter(|relocation| relocation.table == table.index)
+                .collect::<Vec<_>>();
+
+            lines.push(String::new());
+            lines.push(format!(
+                "Relocation section '{}' at offset {:#x} contains {} {}:",
+                table.name,
+                table.offset,
+                relocations.len(),
+                if relocations.len() == 1 {
+                    "entry"
+                } else {
+                    "entries"
+                }
+            ));
+
+            let heading = match (wide, with_addend) {
+                (true, true) => {
+                    "    Offset             Info             Type               Symbol's Value  Symbol's Name + Addend"
+                }
+                (true, false) => {
+                    "    Offset             Info             Type               Symbol's Value  Symbol's Name"
+                }
+                (false, true) => {
+                    " Offset     Info    Type                Sym. Value  Symbol's Name + Addend"
+                }
+                (false, false) => " Offset     Info    Type            Sym.Value  Sym. Name",
+            };
+            lines.push(String::from(heading));
+
+            let type_width = if wide { 22 } else { 17 };
+
+            for relocation in relocations {
+                let mut line = format!(
+                    "{:0aw$x}  {:0aw$x} {:<tw$}",
+                    relocation.offset,
+                    relocation.info,
+                    relocation_type_name(self.header.machine, relocation.relocation_type),
+                    aw = address_width,
+                    tw = type_width
+                );
+
+                match self.relocation_symbol(relocation) {
+                    Some(symbol) => {
+                        line.push_str(
+                            format!(
+                                " {:0aw$x} {}{}",
+                                symbol.value,
+                                if wide { "" } else { "  " },
+                                self.symbol_display_name(symbol, demangle)
+                                    + &self
+                                        .symbol_version(symbol)
+                                        .map(|version| version.suffix())
+                                        .unwrap_or_default(),
+                                aw = address_width
+                            )
+                            .as_str(),
+                        );
+
+                        if let Some(addend) = relocation.addend {
+                            let sign = if addend < 0 { '-' } else { '+' };
+                            line.push_str(format!(" {sign} {:x}", addend.unsigned_abs()).as_str());
+                        }
+                    }
+                    Option::None => {
+                        if let Some(addend) = relocation.addend {
+                            line.push_str(
+                                format!("{:w$}{}", "", signed_hex(addend), w = address_width + 4)
+                                    .as_str(),
+                            );
+                        }
+                    }
+                }
+
+                lines.push(line);
+            }
+        }
+
+        if lines.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("There are no relocations in this file."));
+        }
+
+        lines
+    }
+
+    pub fn dump_relocations(&self, demangle: bool, out: &mut dyn OutputSink) {
+        out.begin_section("relocations");
+
+        for line in self.relocation_lines(demangle) {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+}
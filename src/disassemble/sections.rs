@@ -0,0 +1,405 @@
+use super::{ElfHeader, SHN_XINDEX};
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+pub const SHT_NULL: u32 = 0;
+pub const SHT_PROGBITS: u32 = 1;
+pub const SHT_SYMTAB: u32 = 2;
+pub const SHT_STRTAB: u32 = 3;
+pub const SHT_RELA: u32 = 4;
+pub const SHT_HASH: u32 = 5;
+pub const SHT_DYNAMIC: u32 = 6;
+pub const SHT_NOTE: u32 = 7;
+pub const SHT_NOBITS: u32 = 8;
+pub const SHT_REL: u32 = 9;
+pub const SHT_SHLIB: u32 = 10;
+pub const SHT_DYNSYM: u32 = 11;
+pub const SHT_INIT_ARRAY: u32 = 14;
+pub const SHT_FINI_ARRAY: u32 = 15;
+pub const SHT_PREINIT_ARRAY: u32 = 16;
+pub const SHT_GROUP: u32 = 17;
+pub const SHT_SYMTAB_SHNDX: u32 = 18;
+pub const SHT_RELR: u32 = 19;
+pub const SHT_GNU_ATTRIBUTES: u32 = 0x6FFFFFF5;
+pub const SHT_GNU_HASH: u32 = 0x6FFFFFF6;
+pub const SHT_GNU_LIBLIST: u32 = 0x6FFFFFF7;
+pub const SHT_GNU_VERDEF: u32 = 0x6FFFFFFD;
+pub const SHT_GNU_VERNEED: u32 = 0x6FFFFFFE;
+pub const SHT_GNU_VERSYM: u32 = 0x6FFFFFFF;
+
+pub const SHF_WRITE: u64 = 0x1;
+pub const SHF_ALLOC: u64 = 0x2;
+pub const SHF_EXECINSTR: u64 = 0x4;
+pub const SHF_MERGE: u64 = 0x10;
+pub const SHF_STRINGS: u64 = 0x20;
+pub const SHF_INFO_LINK: u64 = 0x40;
+pub const SHF_LINK_ORDER: u64 = 0x80;
+pub const SHF_OS_NONCONFORMING: u64 = 0x100;
+pub const SHF_GROUP: u64 = 0x200;
+pub const SHF_TLS: u64 = 0x400;
+pub const SHF_COMPRESSED: u64 = 0x800;
+pub const SHF_GNU_RETAIN: u64 = 0x200000;
+pub const SHF_EXCLUDE: u64 = 0x80000000;
+pub const SHF_MASKOS: u64 = 0x0FF00000;
+pub const SHF_MASKPROC: u64 = 0xF0000000;
+
+const SECTION_HEADER_TABLE: &str = "section header table";
+
+/// One entry of the section header table.
+#[derive(Clone, Debug)]
+pub struct SectionHeader {
+    pub index: usize,
+    pub name: String,
+    pub name_offset: u32,
+    pub section_type: u32,
+    pub flags: u64,
+    pub address: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub link: u32,
+    pub info: u32,
+    pub alignment: u64,
+    pub entry_size: u64,
+}
+
+impl SectionHeader {
+    /// Whether the section occupies bytes in the file (everything except `SHT_NOBITS`).
+    pub fn has_file_data(&self) -> bool {
+        self.section_type != SHT_NOBITS
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.flags & SHF_EXECINSTR != 0
+    }
+}
+
+pub fn section_type_name(section_type: u32) -> String {
+    let name = match section_type {
+        SHT_NULL => "NULL",
+        SHT_PROGBITS => "PROGBITS",
+        SHT_SYMTAB => "SYMTAB",
+        SHT_STRTAB => "STRTAB",
+        SHT_RELA => "RELA",
+        SHT_HASH => "HASH",
+        SHT_DYNAMIC => "DYNAMIC",
+        SHT_NOTE => "NOTE",
+        SHT_NOBITS => "NOBITS",
+        SHT_REL => "REL",
+        SHT_SHLIB => "SHLIB",
+        SHT_DYNSYM => "DYNSYM",
+        SHT_INIT_ARRAY => "INIT_ARRAY",
+        SHT_FINI_ARRAY => "FINI_ARRAY",
+        SHT_PREINIT_ARRAY => "PREINIT_ARRAY",
+        SHT_GROUP => "GROUP",
+        SHT_SYMTAB_SHNDX => "SYMTAB SECTION INDICES",
+        SHT_RELR => "RELR",
+        SHT_GNU_ATTRIBUTES => "GNU_ATTRIBUTES",
+        SHT_GNU_HASH => "GNU_HASH",
+        SHT_GNU_LIBLIST => "GNU_LIBLIST",
+        SHT_GNU_VERDEF => "VERDEF",
+        SHT_GNU_VERNEED => "VERNEED",
+        SHT_GNU_VERSYM => "VERSYM",
+        0x60000000..=0x6FFFFFFF => return format!("LOOS+{:x}", section_type - 0x60000000),
+        0x70000000..=0x7FFFFFFF => return format!("LOPROC+{:x}", section_type - 0x70000000),
+        0x80000000..=0xFFFFFFFF => return format!("LOUSER+{:x}", section_type - 0x80000000),
+        _ => return format!("{section_type:08x}: <unknown>"),
+    };
+
+    String::from(name)
+}
+
+/// Decodes section flags into the letters used by `readelf -S`.
+pub fn section_flags_string(flags: u64) -> String {
+    const LETTERS: [(u64, char); 13] = [
+        (SHF_WRITE, 'W'),
+        (SHF_ALLOC, 'A'),
+        (SHF_EXECINSTR, 'X'),
+        (SHF_MERGE, 'M'),
+        (SHF_STRINGS, 'S'),
+        (SHF_INFO_LINK, 'I'),
+        (SHF_LINK_ORDER, 'L'),
+        (SHF_OS_NONCONFORMING, 'O'),
+        (SHF_GROUP, 'G'),
+        (SHF_TLS, 'T'),
+        (SHF_COMPRESSED, 'C'),
+        (SHF_GNU_RETAIN, 'R'),
+        (SHF_EXCLUDE, 'E'),
+    ];
+
+    let mut letters = LETTERS
+        .iter()
+        .filter(|(flag, _)| flags & flag != 0)
+        .map(|(_, letter)| *letter)
+        .collect::<String>();
+
+    let known = LETTERS.iter().fold(0, |all, (flag, _)| all | flag);
+    let remaining = flags & !known;
+
+    if remaining & SHF_MASKOS != 0 {
+        letters.push('o');
+    }
+
+    if remaining & SHF_MASKPROC != 0 {
+        letters.push('p');
+    }
+
+    if remaining & !(SHF_MASKOS | SHF_MASKPROC) != 0 {
+        letters.push('x');
+    }
+
+    letters
+}
+
+impl ElfHeader {
+    fn parse_section_header(&self, index: usize) -> Result<SectionHeader> {
+        let base = self.section_header_offset_of(index);
+        let field = |offset: usize| base.saturating_add(offset as u64);
+
+        // flags, address, offset and size are address sized, the rest are fixed width
+        let width = self.format.address_size();
+        let address_field = |i: usize| field(0x08 + width * i);
+
+        Ok(SectionHeader {
+            index,
+            name: String::new(),
+            name_offset: self.read::<u32>("section header", field(0x00))?,
+            section_type: self.read::<u32>("section header", field(0x04))?,
+            flags: self.read_address("section header", address_field(0))?,
+            address: self.read_address("section header", address_field(1))?,
+            offset: self.read_address("section header", address_field(2))?,
+            size: self.read_address("section header", address_field(3))?,
+            link: self.read::<u32>("section header", address_field(4))?,
+            info: self.read::<u32>("section header", address_field(4) + 4)?,
+            alignment: self.read_address("section header", field(0x10 + width * 4))?,
+            entry_size: self.read_address("section header", field(0x10 + width * 5))?,
+        })
+    }
+
+    /// Resolves `e_shnum` and `e_shstrndx`. When there are too many sections for the file header,
+    /// `e_shnum` is 0 and the count is in section 0's `sh_size`, and `e_shstrndx` is `SHN_XINDEX`
+    /// with the index in section 0's `sh_link`.
+    fn resolve_section_count(&mut self) {
+        self.section_count = self.header.section_header_entry_count as u64;
+        self.section_names_index = self.header.section_header_names_index as usize;
+
+        let extended = self.header.section_header_entry_count == 0
+            || self.header.section_header_names_index == SHN_XINDEX;
+
+        if !extended
+            || self.header.section_header_offset == 0
+            || (self.header.section_header_entry_size as u64) < self.format.section_header_size()
+        {
+            return;
+        }
+
+        let Ok(first) = self.parse_section_header(0) else {
+            return;
+        };
+
+        if self.header.section_header_entry_count == 0 {
+            self.section_count = first.size;
+        }
+
+        if self.header.section_header_names_index == SHN_XINDEX {
+            self.section_names_index = first.link as usize;
+        }
+    }
+
+    pub(super) fn parse_section_headers(&mut self) {
+        self.resolve_section_count();
+
+        let count = self.readable_entry_count(
+            SECTION_HEADER_TABLE,
+            self.header.section_header_offset,
+            self.header.section_header_entry_size,
+            self.format.section_header_size(),
+            self.section_count,
+        );
+
+        if count == 0 {
+            return;
+        }
+
+        for index in 0..count {
+            match self.parse_section_header(index) {
+                Ok(section) => self.sections.push(section),
+                Err(error) => self.diagnostics.push(Diagnostic {
+                    table: SECTION_HEADER_TABLE,
+                    index: Some(index),
+                    error,
+                }),
+            }
+        }
+
+        self.resolve_section_names();
+
+        for section in &self.sections {
+            if section.has_file_data() && self.section_data(section).is_none() {
+                self.diagnostics.push(Diagnostic {
+                    table: SECTION_HEADER_TABLE,
+                    index: Some(section.index),
+                    error: Error::SectionOutOfBounds {
+                        name: section.name.clone(),
+                        offset: section.offset,
+                        size: section.size,
+                        available: self.data.len() as u64,
+                    },
+                });
+            }
+        }
+    }
+
+    fn resolve_section_names(&mut self) {
+        let names_index = self.section_names_index;
+        let Some(names) = self.section(names_index).cloned() else {
+            self.diagnostics.push(Diagnostic {
+                table: SECTION_HEADER_TABLE,
+                index: Option::None,
+                error: Error::OutOfRange {
+                    what: "section name string table index",
+                    offset: names_index as u64,
+                    limit: self.sections.len() as u64,
+                },
+            });
+            return;
+        };
+
+        let mut names_diagnostics = Vec::new();
+        let names = self
+            .sections
+            .iter()
+            .map(|section| {
+                self.string_from_table(&names, section.name_offset)
+                    .unwrap_or_else(|error| {
+                        names_diagnostics.push(Diagnostic {
+                            table: SECTION_HEADER_TABLE,
+                            index: Some(section.index),
+                            error,
+                        });
+                        String::new()
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        for (section, name) in self.sections.iter_mut().zip(names) {
+            section.name = name;
+        }
+
+        self.diagnostics.append(&mut names_diagnostics);
+    }
+
+    /// Reads the NUL terminated string at `offset` in the string table `table`.
+    pub fn string_from_table(&self, table: &SectionHeader, offset: u32) -> Result<String> {
+        if offset as u64 >= table.size {
+            return Err(Error::OutOfRange {
+                what: "string table offset",
+                offset: offset as u64,
+                limit: table.size,
+            });
+        }
+
+        let Some(bytes) = self.section_data(table) else {
+            return Err(Error::SectionOutOfBounds {
+                name: table.name.clone(),
+                offset: table.offset,
+                size: table.size,
+                available: self.data.len() as u64,
+            });
+        };
+
+        let bytes = &bytes[offset as usize..];
+
+        let Some(length) = bytes.iter().position(|byte| *byte == 0) else {
+            return Err(Error::Truncated {
+                what: "string",
+                offset: table.offset.saturating_add(offset as u64),
+                needed: bytes.len() as u64 + 1,
+                available: self.data.len() as u64,
+            });
+        };
+
+        Ok(String::from_utf8_lossy(&bytes[..length]).into_owned())
+    }
+
+    pub fn sections(&self) -> &[SectionHeader] {
+        &self.sections
+    }
+
+    /// Looks up a section by its index in the section header table.
+    pub fn section(&self, index: usize) -> Option<&SectionHeader> {
+        self.sections.iter().find(|section| section.index == index)
+    }
+
+    pub fn section_by_name(&self, name: &str) -> Option<&SectionHeader> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// The bytes of `section` in the file, or `None` if it has none or they lie outside the file.
+    pub fn section_data(&self, section: &SectionHeader) -> Option<&[u8]> {
+        if !section.has_file_data() {
+            return Option::None;
+        }
+
+        let offset = usize::try_from(section.offset).ok()?;
+        let size = usize::try_from(section.size).ok()?;
+
+        self.data.get(offset..offset.checked_add(size)?)
+    }
+
+    /// Formats the section header table the way `readelf -S -W` does.
+    pub fn section_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "There are {} section headers, starting at offset {:#x}:",
+                self.section_count, self.header.section_header_offset
+            ),
+            String::new(),
+            String::from("Section Headers:"),
+            format!(
+                "  [Nr] {:<17} {:<15} {:<16} {:<6} {:<6} ES Flg Lk Inf Al",
+                "Name", "Type", "Address", "Off", "Size"
+            ),
+        ];
+
+        let address_width = self.format.address_size() * 2;
+
+        for section in &self.sections {
+            lines.push(format!(
+                "  [{:>2}] {:<17} {:<15} {:0address_width$x} {:06x} {:06x} {:02x} {:>3} {:>2} {:>3} {:>2}",
+                section.index,
+                section.name,
+                section_type_name(section.section_type),
+                section.address,
+                section.offset,
+                section.size,
+                section.entry_size,
+                section_flags_string(section.flags),
+                section.link,
+                section.info,
+                section.alignment
+            ));
+        }
+
+        lines.extend([
+            String::from("Key to Flags:"),
+            String::from("  W (write), A (alloc), X (execute), M (merge), S (strings), I (info),"),
+            String::from("  L (link order), O (extra OS processing required), G (group), T (TLS),"),
+            String::from(
+                "  C (compressed), R (retain), x (unknown), o (OS specific), E (exclude),",
+            ),
+            String::from("  p (processor specific)"),
+        ]);
+
+        lines
+    }
+
+    pub fn dump_sections(&self, out: &mut dyn OutputSink) {
+        out.begin_section("sections");
+
+        for line in self.section_lines() {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+}
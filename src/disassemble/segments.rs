@@ -0,0 +1,313 @@
+use super::{BitFormat, ElfHeader, SHF_ALLOC, SHF_TLS, SHT_NOBITS, SectionHeader};
+use crate::error::{Diagnostic, Result};
+use crate::output::OutputSink;
+
+pub const PT_NULL: u32 = 0;
+pub const PT_LOAD: u32 = 1;
+pub const PT_DYNAMIC: u32 = 2;
+pub const PT_INTERP: u32 = 3;
+pub const PT_NOTE: u32 = 4;
+pub const PT_SHLIB: u32 = 5;
+pub const PT_PHDR: u32 = 6;
+pub const PT_TLS: u32 = 7;
+pub const PT_GNU_EH_FRAME: u32 = 0x6474E550;
+pub const PT_GNU_STACK: u32 = 0x6474E551;
+pub const PT_GNU_RELRO: u32 = 0x6474E552;
+pub const PT_GNU_PROPERTY: u32 = 0x6474E553;
+pub const PT_GNU_SFRAME: u32 = 0x6474E554;
+
+pub const PF_X: u32 = 0x1;
+pub const PF_W: u32 = 0x2;
+pub const PF_R: u32 = 0x4;
+
+const PROGRAM_HEADER_TABLE: &str = "program header table";
+
+/// One entry of the program header table, describing a segment.
+#[derive(Clone, Debug)]
+pub struct ProgramHeader {
+    pub index: usize,
+    pub segment_type: u32,
+    pub flags: u32,
+    pub offset: u64,
+    pub virtual_address: u64,
+    pub physical_address: u64,
+    pub file_size: u64,
+    pub memory_size: u64,
+    pub alignment: u64,
+}
+
+impl ProgramHeader {
+    /// The `readelf` style `RWE` column, with spaces for missing permissions.
+    pub fn flags_string(&self) -> String {
+        let flag = |bit: u32, letter: char| if self.flags & bit != 0 { letter } else { ' ' };
+        [flag(PF_R, 'R'), flag(PF_W, 'W'), flag(PF_X, 'E')]
+            .iter()
+            .collect()
+    }
+
+    /// Whether `section` lies inside this segment, using the same rules as `readelf -l`.
+    pub fn contains_section(&self, section: &SectionHeader) -> bool {
+        let is_tls = section.flags & SHF_TLS != 0;
+        let is_alloc = section.flags & SHF_ALLOC != 0;
+        let is_nobits = section.section_type == SHT_NOBITS;
+
+        // .tbss only takes up space in the PT_TLS segment
+        if is_tls && is_nobits && self.segment_type != PT_TLS {
+            return false;
+        }
+
+        let type_allowed = if is_tls {
+            matches!(self.segment_type, PT_TLS | PT_GNU_RELRO | PT_LOAD)
+        } else {
+            self.segment_type != PT_TLS && self.segment_type != PT_PHDR
+        };
+
+        if !type_allowed {
+            return false;
+        }
+
+        let needs_alloc = matches!(
+            self.segment_type,
+            PT_LOAD | PT_DYNAMIC | PT_GNU_EH_FRAME | PT_GNU_STACK | PT_GNU_RELRO | PT_GNU_SFRAME
+        );
+
+        if needs_alloc && !is_alloc {
+            return false;
+        }
+
+        let size = section.size;
+
+        let in_file = is_nobits
+            || (section.offset >= self.offset
+                && section.offset - self.offset < self.file_size.max(1)
+                && (section.offset - self.offset).saturating_add(size) <= self.file_size);
+
+        let in_memory = !is_alloc
+            || (section.address >= self.virtual_address
+                && section.address - self.virtual_address < self.memory_size.max(1)
+                && (section.address - self.virtual_address).saturating_add(size)
+                    <= self.memory_size);
+
+        if !in_file || !in_memory {
+            return false;
+        }
+
+        // empty sections sitting exactly on the edge of PT_DYNAMIC or PT_NOTE do not belong to it
+        if matches!(self.segment_type, PT_DYNAMIC | PT_NOTE) && size == 0 && self.memory_size != 0 {
+            let inside_file = is_nobits
+                || (section.offset > self.offset && section.offset - self.offset < self.file_size);
+            let inside_memory = !is_alloc
+                || (section.address > self.virtual_address
+                    && section.address - self.virtual_address < self.memory_size);
+
+            return inside_file && inside_memory;
+        }
+
+        true
+    }
+}
+
+pub fn segment_type_name(segment_type: u32) -> String {
+    let name = match segment_type {
+        PT_NULL => "NULL",
+        PT_LOAD => "LOAD",
+        PT_DYNAMIC => "DYNAMIC",
+        PT_INTERP => "INTERP",
+        PT_NOTE => "NOTE",
+        PT_SHLIB => "SHLIB",
+        PT_PHDR => "PHDR",
+        PT_TLS => "TLS",
+        PT_GNU_EH_FRAME => "GNU_EH_FRAME",
+        PT_GNU_STACK => "GNU_STACK",
+        PT_GNU_RELRO => "GNU_RELRO",
+        PT_GNU_PROPERTY => "GNU_PROPERTY",
+        PT_GNU_SFRAME => "GNU_SFRAME",
+        0x60000000..=0x6FFFFFFF => return format!("LOOS+{:#x}", segment_type - 0x60000000),
+        0x70000000..=0x7FFFFFFF => return format!("LOPROC+{:#x}", segment_type - 0x70000000),
+        _ => return format!("<unknown>: {segment_type:x}"),
+    };
+
+    String::from(name)
+}
+
+impl ElfHeader {
+    fn program_header_offset_of(&self, index: usize) -> u64 {
+        self.header.program_header_offset.saturating_add(
+            (index as u64).saturating_mul(self.header.program_header_entry_size as u64),
+        )
+    }
+
+    fn parse_program_header(&self, index: usize) -> Result<ProgramHeader> {
+        let base = self.program_header_offset_of(index);
+        let field = |offset: u64| base.saturating_add(offset);
+        let what = "program header";
+
+        // the 64 bit layout moves p_flags up next to p_type to keep the address fields aligned
+        match self.format {
+            BitFormat::Bit32 => Ok(ProgramHeader {
+                index,
+                segment_type: self.read::<u32>(what, field(0x00))?,
+                offset: self.read::<u32>(what, field(0x04))? as u64,
+                virtual_address: self.read::<u32>(what, field(0x08))? as u64,
+                physical_address: self.read::<u32>(what, field(0x0C))? as u64,
+                file_size: self.read::<u32>(what, field(0x10))? as u64,
+                memory_size: self.read::<u32>(what, field(0x14))? as u64,
+                flags: self.read::<u32>(what, field(0x18))?,
+                alignment: self.read::<u32>(what, field(0x1C))? as u64,
+            }),
+            BitFormat::Bit64 => Ok(ProgramHeader {
+                index,
+                segment_type: self.read::<u32>(what, field(0x00))?,
+                flags: self.read::<u32>(what, field(0x04))?,
+                offset: self.read::<u64>(what, field(0x08))?,
+                virtual_address: self.read::<u64>(what, field(0x10))?,
+                physical_address: self.read::<u64>(what, field(0x18))?,
+                file_size: self.read::<u64>(what, field(0x20))?,
+                memory_size: self.read::<u64>(what, field(0x28))?,
+                alignment: self.read::<u64>(what, field(0x30))?,
+            }),
+        }
+    }
+
+    pub(super) fn parse_program_headers(&mut self) {
+        let count = self.readable_entry_count(
+            PROGRAM_HEADER_TABLE,
+            self.header.program_header_offset,
+            self.header.program_header_entry_size,
+            self.format.program_header_size(),
+            self.header.program_header_entry_count as u64,
+        );
+
+        for index in 0..count {
+            match self.parse_program_header(index) {
+                Ok(segment) => self.segments.push(segment),
+                Err(error) => self.diagnostics.push(Diagnostic {
+                    table: PROGRAM_HEADER_TABLE,
+                    index: Some(index),
+                    error,
+                }),
+            }
+        }
+    }
+
+    pub fn segments(&self) -> &[ProgramHeader] {
+        &self.segments
+    }
+
+    /// The bytes of `segment` that are backed by the file, or `None` if they lie outside it.
+    pub fn segment_data(&self, segment: &ProgramHeader) -> Option<&[u8]> {
+        let offset = usize::try_from(segment.offset).ok()?;
+        let size = usize::try_from(segment.file_size).ok()?;
+
+        self.data.get(offset..offset.checked_add(size)?)
+    }
+
+    /// The program interpreter requested by a `PT_INTERP` segment.
+    pub fn interpreter(&self) -> Option<String> {
+        let segment = self
+            .segments
+            .iter()
+            .find(|segment| segment.segment_type == PT_INTERP)?;
+
+        let bytes = self.segment_data(segment)?;
+        let length = bytes
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(bytes.len());
+
+        Some(String::from_utf8_lossy(&bytes[..length]).into_owned())
+    }
+
+    /// For every segment, the names of the sections it contains.
+    pub fn section_to_segment_mapping(&self) -> Vec<Vec<&str>> {
+        self.segments
+            .iter()
+            .map(|segment| {
+                self.sections
+                    .iter()
+                    .filter(|section| section.index != 0 && segment.contains_section(section))
+                    .map(|section| section.name.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Formats the program header table and section to segment mapping the way `readelf -l -W` does.
+    pub fn segment_lines(&self) -> Vec<String> {
+        if self.segments.is_empty() {
+            return vec![String::from("There are no program headers in this file.")];
+        }
+
+        let mut lines = vec![
+            format!("Elf file type is {}", self.file_type_name()),
+            format!("Entry point {:#x}", self.header.entry),
+            format!(
+                "There are {} program headers, starting at offset {}",
+                self.header.program_header_entry_count, self.header.program_header_offset
+            ),
+            String::new(),
+            String::from("Program Headers:"),
+        ];
+
+        let address_width = self.format.address_size() * 2;
+
+        lines.push(format!(
+            "  {:<14} {:<8} {:<aw$} {:<aw$} {:<8} {:<8} Flg Align",
+            "Type",
+            "Offset",
+            "VirtAddr",
+            "PhysAddr",
+            "FileSiz",
+            "MemSiz",
+            aw = address_width + 2
+        ));
+
+        for segment in &self.segments {
+            lines.push(format!(
+                "  {:<14} 0x{:06x} 0x{:0aw$x} 0x{:0aw$x} 0x{:06x} 0x{:06x} {} 0x{:x}",
+                segment_type_name(segment.segment_type),
+                segment.offset,
+                segment.virtual_address,
+                segment.physical_address,
+                segment.file_size,
+                segment.memory_size,
+                segment.flags_string(),
+                segment.alignment,
+                aw = address_width
+            ));
+
+            if segment.segment_type == PT_INTERP
+                && let Some(interpreter) = self.interpreter()
+            {
+                lines.push(format!(
+                    "      [Requesting program interpreter: {interpreter}]"
+                ));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(String::from(" Section to Segment mapping:"));
+        lines.push(String::from("  Segment Sections..."));
+
+        for (i, names) in self.section_to_segment_mapping().iter().enumerate() {
+            let names = names
+                .iter()
+                .map(|name| format!("{name} "))
+                .collect::<String>();
+
+            lines.push(format!("   {i:02}     {names}"));
+        }
+
+        lines
+    }
+
+    pub fn dump_segments(&self, out: &mut dyn OutputSink) {
+        out.begin_section("segments");
+
+        for line in self.segment_lines() {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+}
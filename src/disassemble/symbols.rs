@@ -0,0 +1,386 @@
+use std::collections::BTreeMap;
+
+use super::{
+    BitFormat, ET_REL, ElfHeader, SHT_DYNSYM, SHT_SYMTAB, SHT_SYMTAB_SHNDX, SectionHeader,
+    SymbolVersion,
+};
+use crate::demangle::{demangle_or_original, demangle_without_parameters};
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+pub const STB_LOCAL: u8 = 0;
+pub const STB_GLOBAL: u8 = 1;
+pub const STB_WEAK: u8 = 2;
+pub const STB_GNU_UNIQUE: u8 = 10;
+
+pub const STT_NOTYPE: u8 = 0;
+pub const STT_OBJECT: u8 = 1;
+pub const STT_FUNC: u8 = 2;
+pub const STT_SECTION: u8 = 3;
+pub const STT_FILE: u8 = 4;
+pub const STT_COMMON: u8 = 5;
+pub const STT_TLS: u8 = 6;
+pub const STT_GNU_IFUNC: u8 = 10;
+
+pub const STV_DEFAULT: u8 = 0;
+pub const STV_INTERNAL: u8 = 1;
+pub const STV_HIDDEN: u8 = 2;
+pub const STV_PROTECTED: u8 = 3;
+
+pub const SHN_UNDEF: u16 = 0;
+pub const SHN_ABS: u16 = 0xFFF1;
+pub const SHN_COMMON: u16 = 0xFFF2;
+pub const SHN_XINDEX: u16 = 0xFFFF;
+
+const SYMBOL_TABLE: &str = "symbol table";
+
+/// One entry of a `.symtab` or `.dynsym` table.
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    /// Index of the symbol within its table.
+    pub index: usize,
+    /// Section header index of the table the symbol came from.
+    pub table: usize,
+    pub name: String,
+    pub value: u64,
+    pub size: u64,
+    pub symbol_type: u8,
+    pub binding: u8,
+    pub visibility: u8,
+    /// Index of the section the symbol is defined in, with `SHN_XINDEX` already resolved.
+    pub section_index: u32,
+}
+
+impl Symbol {
+    pub fn is_defined(&self) -> bool {
+        self.section_index != SHN_UNDEF as u32
+    }
+
+    pub fn is_function(&self) -> bool {
+        matches!(self.symbol_type, STT_FUNC | STT_GNU_IFUNC)
+    }
+}
+
+pub fn symbol_type_name(symbol_type: u8) -> String {
+    let name = match symbol_type {
+        STT_NOTYPE => "NOTYPE",
+        STT_OBJECT => "OBJECT",
+        STT_FUNC => "FUNC",
+        STT_SECTION => "SECTION",
+        STT_FILE => "FILE",
+        STT_COMMON => "COMMON",
+        STT_TLS => "TLS",
+        STT_GNU_IFUNC => "IFUNC",
+        11..=12 => return format!("<OS specific>: {symbol_type}"),
+        13..=15 => return format!("<processor specific>: {symbol_type}"),
+        _ => return format!("<unknown>: {symbol_type}"),
+    };
+
+    String::from(name)
+}
+
+pub fn symbol_binding_name(binding: u8) -> String {
+    let name = match binding {
+        STB_LOCAL => "LOCAL",
+        STB_GLOBAL => "GLOBAL",
+        STB_WEAK => "WEAK",
+        STB_GNU_UNIQUE => "UNIQUE",
+        11..=12 => return format!("<OS specific>: {binding}"),
+        13..=15 => return format!("<processor specific>: {binding}"),
+        _ => return format!("<unknown>: {binding}"),
+    };
+
+    String::from(name)
+}
+
+pub fn symbol_visibility_name(visibility: u8) -> &'static str {
+    match visibility {
+        STV_DEFAULT => "DEFAULT",
+        STV_INTERNAL => "INTERNAL",
+        STV_HIDDEN => "HIDDEN",
+        _ => "PROTECTED",
+    }
+}
+
+pub fn symbol_section_name(section_index: u32) -> String {
+    match section_index {
+        0 => String::from("UND"),
+        0xFFF1 => String::from("ABS"),
+        0xFFF2 => String::from("COM"),
+        index => index.to_string(),
+    }
+}
+
+impl ElfHeader {
+    fn symbol_entry_size(&self) -> u64 {
+        match self.format {
+            BitFormat::Bit32 => 0x10,
+            BitFormat::Bit64 => 0x18,
+        }
+    }
+
+    fn parse_symbol(
+        &self,
+        table: &SectionHeader,
+        names: Option<&SectionHeader>,
+        extended_indices: Option<&SectionHeader>,
+        index: usize,
+    ) -> Result<Symbol> {
+        let base = table
+            .offset
+            .saturating_add((index as u64).saturating_mul(self.symbol_entry_size()));
+        let field = |offset: u64| base.saturating_add(offset);
+        let what = "symbol";
+
+        // Elf32_Sym orders its fields name, value, size, info, other, shndx while
+        // Elf64_Sym moves info, other and shndx ahead of the two address sized fields
+        let (value, size, info, other, shndx) = match self.format {
+            BitFormat::Bit32 => (
+                self.read::<u32>(what, field(0x04))? as u64,
+                self.read::<u32>(what, field(0x08))? as u64,
+                self.read::<u8>(what, field(0x0C))?,
+                self.read::<u8>(what, field(0x0D))?,
+                self.read::<u16>(what, field(0x0E))?,
+            ),
+            BitFormat::Bit64 => (
+                self.read::<u64>(what, field(0x08))?,
+                self.read::<u64>(what, field(0x10))?,
+                self.read::<u8>(what, field(0x04))?,
+                self.read::<u8>(what, field(0x05))?,
+                self.read::<u16>(what, field(0x06))?,
+            ),
+        };
+        let name_offset = self.read::<u32>(what, field(0x00))?;
+
+        let name = match names {
+            Some(names) if name_offset != 0 => self.string_from_table(names, name_offset)?,
+            _ => String::new(),
+        };
+
+        let section_index = match (shndx, extended_indices) {
+            (SHN_XINDEX, Some(extended)) => self.read::<u32>(
+                "extended section index",
+                extended.offset.saturating_add(index as u64 * 4),
+            )?,
+            _ => shndx as u32,
+        };
+
+        Ok(Symbol {
+            index,
+            table: table.index,
+            name,
+            value,
+            size,
+            symbol_type: info & 0xF,
+            binding: info >> 4,
+            visibility: other & 0x3,
+            section_index,
+        })
+    }
+
+    pub(super) fn parse_symbol_tables(&mut self) {
+        let tables = self
+            .sections
+            .iter()
+            .filter(|section| matches!(section.section_type, SHT_SYMTAB | SHT_DYNSYM))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for table in tables {
+            let names = self.section(table.link as usize).cloned();
+            let extended_indices = self
+                .sections
+                .iter()
+                .find(|section| {
+                    section.section_type == SHT_SYMTAB_SHNDX && section.link as usize == table.index
+                })
+                .cloned();
+
+            if names.is_none() {
+                self.diagnostics.push(Diagnostic {
+                    table: SYMBOL_TABLE,
+                    index: Option::None,
+                    error: Error::OutOfRange {
+                        what: "string table index",
+                        offset: table.link as u64,
+                        limit: self.sections.len() as u64,
+                    },
+                });
+            }
+
+            if self.section_data(&table).is_none() {
+                continue;
+            }
+
+            let count = table.size / self.symbol_entry_size();
+
+            for index in 0..count as usize {
+                match self.parse_symbol(&table, names.as_ref(), extended_indices.as_ref(), index) {
+                    Ok(symbol) => self.symbols.push(symbol),
+                    Err(error) => self.diagnostics.push(Diagnostic {
+                        table: SYMBOL_TABLE,
+                        index: Some(index),
+                        error,
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Every symbol from every `SHT_SYMTAB` and `SHT_DYNSYM` section, in file order.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// The symbols of the table in section `table`.
+    pub fn symbols_in(&self, table: usize) -> impl Iterator<Item = &Symbol> {
+        self.symbols
+            .iter()
+            .filter(move |symbol| symbol.table == table)
+    }
+
+    /// Looks up a defined symbol by its exact or demangled name, with or without C++ parameters.
+    pub fn symbol_by_name(&self, name: &str) -> Option<&Symbol> {
+        let defined = || self.symbols.iter().filter(|symbol| symbol.is_defined());
+
+        if let Some(symbol) = defined().find(|symbol| symbol.name == name) {
+            return Some(symbol);
+        }
+
+        defined().find(|symbol| {
+            demangle_or_original(symbol.name.as_str()) == name
+                || demangle_without_parameters(symbol.name.as_str()).as_deref() == Some(name)
+        })
+    }
+
+    /// The virtual address of `symbol`; in relocatable files symbol values are relative to their section.
+    pub fn symbol_address(&self, symbol: &Symbol) -> u64 {
+        if self.header.file_type != ET_REL {
+            return symbol.value;
+        }
+
+        match self.section(symbol.section_index as usize) {
+            Some(section) => section.address.wrapping_add(symbol.value),
+            Option::None => symbol.value,
+        }
+    }
+
+    /// The symbols defined in section `section_index` that should label code, keyed by address.
+    ///
+    /// Section, file and ARM mapping (`$x`, `$d`) symbols are skipped, and when several symbols share
+    /// an address functions win over other types and global symbols over local ones.
+    pub fn symbol_labels(&self, section_index: usize) -> BTreeMap<u64, &Symbol> {
+        let mut labels = BTreeMap::<u64, &Symbol>::new();
+
+        let candidates = self.symbols.iter().filter(|symbol| {
+            symbol.section_index as usize == section_index
+                && !matches!(symbol.symbol_type, STT_SECTION | STT_FILE)
+                && !symbol.name.is_empty()
+                && !symbol.name.starts_with('$')
+        });
+
+        let rank = |symbol: &Symbol| (symbol.is_function(), symbol.binding != STB_LOCAL);
+
+        for symbol in candidates {
+            let address = self.symbol_address(symbol);
+
+            match labels.get(&address) {
+                Some(existing) if rank(existing) >= rank(symbol) => {}
+                _ => {
+                    labels.insert(address, symbol);
+                }
+            }
+        }
+
+        labels
+    }
+
+    /// The name to show for `symbol`; section symbols are unnamed so they take their section's name.
+    pub fn symbol_display_name(&self, symbol: &Symbol, demangle: bool) -> String {
+        if symbol.symbol_type == STT_SECTION && symbol.name.is_empty() {
+            return self
+                .section(symbol.section_index as usize)
+                .map(|section| section.name.clone())
+                .unwrap_or_default();
+        }
+
+        if demangle {
+            return demangle_or_original(symbol.name.as_str());
+        }
+
+        symbol.name.clone()
+    }
+
+    /// Formats every symbol table the way `readelf -s -W` does, optionally demangling names.
+    /// Dynamic symbols carry their versions, like `memcpy@GLIBC_2.17 (3)`.
+    pub fn symbol_lines(&self, demangle: bool) -> Vec<String> {
+        let mut lines = Vec::new();
+        let address_width = self.format.address_size() * 2;
+
+        let tables = self
+            .sections
+            .iter()
+            .filter(|section| matches!(section.section_type, SHT_SYMTAB | SHT_DYNSYM));
+
+        for table in tables {
+            let symbols = self.symbols_in(table.index).collect::<Vec<_>>();
+
+            lines.push(String::new());
+            lines.push(format!(
+                "Symbol table '{}' contains {} entries:",
+                table.name,
+                symbols.len()
+            ));
+            lines.push(format!(
+                "   Num: {:<aw$} {:>5} Type    Bind   Vis      Ndx Name",
+                "   Value",
+                "Size",
+                aw = address_width
+            ));
+
+            for symbol in symbols {
+                let mut name = self.symbol_display_name(symbol, demangle);
+
+                match self.symbol_version(symbol) {
+                    Some(SymbolVersion::Needed {
+                        name: version,
+                        index,
+                    }) => {
+                        name.push_str(format!("@{version} ({index})").as_str());
+                    }
+                    Some(version) => name.push_str(version.suffix().as_str()),
+                    Option::None => {}
+                }
+
+                lines.push(format!(
+                    "{:>6}: {:0aw$x} {:>5} {:<7} {:<6} {:<8} {:>3} {}",
+                    symbol.index,
+                    symbol.value,
+                    symbol.size,
+                    symbol_type_name(symbol.symbol_type),
+                    symbol_binding_name(symbol.binding),
+                    symbol_visibility_name(symbol.visibility),
+                    symbol_section_name(symbol.section_index),
+                    name,
+                    aw = address_width
+                ));
+            }
+        }
+
+        if lines.is_empty() {
+            lines.push(String::from("There are no symbol tables in this file."));
+        }
+
+        lines
+    }
+
+    pub fn dump_symbols(&self, demangle: bool, out: &mut dyn OutputSink) {
+        out.begin_section("symbols");
+
+        for line in self.symbol_lines(demangle) {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+}
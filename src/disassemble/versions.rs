@@ -0,0 +1,629 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use super::{ElfHeader, SHT_GNU_VERDEF, SHT_GNU_VERNEED, SHT_GNU_VERSYM, SectionHeader, Symbol};
+use crate::decode_byte::*;
+use crate::error::{Diagnostic, Error, Result};
+use crate::output::OutputSink;
+
+pub const VER_NDX_LOCAL: u16 = 0;
+pub const VER_NDX_GLOBAL: u16 = 1;
+
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+pub const VERSYM_VERSION: u16 = 0x7FFF;
+
+pub const VER_FLG_BASE: u16 = 0x1;
+pub const VER_FLG_WEAK: u16 = 0x2;
+pub const VER_FLG_INFO: u16 = 0x4;
+
+const VERSION_TABLE: &str = "version table";
+
+/// An `Elf_Vernaux`: one version needed from a library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionNeedEntry {
+    /// Offset of the entry from the start of `.gnu.version_r`.
+    pub offset: u64,
+    pub hash: u32,
+    pub flags: u16,
+    /// The version index `.gnu.version` uses for symbols bound to this version.
+    pub index: u16,
+    pub name: String,
+}
+
+/// An `Elf_Verneed`: the versions needed from one library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionNeed {
+    /// Offset of the entry from the start of `.gnu.version_r`.
+    pub offset: u64,
+    pub version: u16,
+    pub file: String,
+    pub entries: Vec<VersionNeedEntry>,
+}
+
+/// An `Elf_Verdef`: a version this file defines, with its name first and its parents after.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionDefinition {
+    /// Offset of the entry from the start of `.gnu.version_d`.
+    pub offset: u64,
+    pub revision: u16,
+    pub flags: u16,
+    pub index: u16,
+    pub hash: u32,
+    /// The `Elf_Verdaux` names with their offsets from the start of `.gnu.version_d`.
+    pub names: Vec<(u64, String)>,
+}
+
+impl VersionDefinition {
+    pub fn name(&self) -> Option<&str> {
+        self.names.first().map(|(_, name)| name.as_str())
+    }
+}
+
+/// The version a dynamic symbol is bound to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SymbolVersion {
+    /// Needed from a library, with the index of the version in `.gnu.version_r`.
+    Needed { name: String, index: u16 },
+    /// Defined by this file as the version that links against the bare name.
+    Default(String),
+    /// Defined by this file but only reachable with an explicit version, like `api@VERS_1.0`.
+    Hidden(String),
+}
+
+impl SymbolVersion {
+    pub fn name(&self) -> &str {
+        match self {
+            SymbolVersion::Needed { name, .. }
+            | SymbolVersion::Default(name)
+            | SymbolVersion::Hidden(name) => name.as_str(),
+        }
+    }
+
+    /// The suffix readelf appends to the symbol name: `@@` for default versions, `@` otherwise.
+    pub fn suffix(&self) -> String {
+        match self {
+            SymbolVersion::Default(name) => format!("@@{name}"),
+            _ => format!("@{}", self.name()),
+        }
+    }
+}
+
+/// The names of the `VER_FLG_*` bits, joined the way readelf does.
+pub fn version_flags_name(flags: u16) -> String {
+    if flags == 0 {
+        return String::from("none");
+    }
+
+    let mut names = Vec::new();
+
+    for (flag, name) in [
+        (VER_FLG_BASE, "BASE"),
+        (VER_FLG_WEAK, "WEAK"),
+        (VER_FLG_INFO, "INFO"),
+    ] {
+        if flags & flag != 0 {
+            names.push(String::from(name));
+        }
+    }
+
+    let unknown = flags & !(VER_FLG_BASE | VER_FLG_WEAK | VER_FLG_INFO);
+
+    if unknown != 0 {
+        names.push(format!("<unknown: {unknown:x}>"));
+    }
+
+    names.join(" | ")
+}
+
+/// Orders version names like `GLIBC_2.2.5` and `GLIBC_2.34` by their numeric parts, so `2.34`
+/// comes after `2.2.5` rather than before it.
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    let numbers = |name: &str| -> Vec<u64> {
+        name.split(|character: char| !character.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse().unwrap_or(u64::MAX))
+            .collect()
+    };
+
+    numbers(left)
+        .cmp(&numbers(right))
+        .then_with(|| left.cmp(right))
+}
+
+/// The part of a version name before its first digit, such as `GLIBC_` or `GLIBCXX_`, which
+/// names the series the numbers count along.
+fn version_series(name: &str) -> &str {
+    let end = name
+        .find(|character: char| character.is_ascii_digit())
+        .unwrap_or(name.len());
+
+    &name[..end]
+}
+
+/// A version needs or definitions offset as readelf's `%#06x` prints it, where zero loses its
+/// `0x` prefix.
+fn entry_offset(offset: u64) -> String {
+    if offset == 0 {
+        return String::from("000000");
+    }
+
+    format!("{offset:#06x}")
+}
+
+impl ElfHeader {
+    /// Reads the `Elf_Verneed` chain of `table`, following `vn_next` for at most `sh_info` entries.
+    fn parse_version_needs(&self, table: &SectionHeader) -> Result<Vec<VersionNeed>> {
+        let names = self.section(table.link as usize).ok_or(Error::OutOfRange {
+            what: "string table index",
+            offset: table.link as u64,
+            limit: self.sections.len() as u64,
+        })?;
+        let what = "version need";
+        let mut needs = Vec::new();
+        let mut offset = 0u64;
+
+        for _ in 0..table.info {
+            let base = table.offset.saturating_add(offset);
+            let version = self.read::<u16>(what, base)?;
+            let count = self.read::<u16>(what, base.saturating_add(2))?;
+            let file =
+                self.string_from_table(names, self.read::<u32>(what, base.saturating_add(4))?)?;
+            let auxiliary = self.read::<u32>(what, base.saturating_add(8))?;
+            let next = self.read::<u32>(what, base.saturating_add(12))?;
+
+            let mut entries = Vec::new();
+            let mut entry_offset = offset.saturating_add(auxiliary as u64);
+
+            for _ in 0..count {
+                let base = table.offset.saturating_add(entry_offset);
+                let hash = self.read::<u32>(what, base)?;
+                let flags = self.read::<u16>(what, base.saturating_add(4))?;
+                let index = self.read::<u16>(what, base.saturating_add(6))?;
+                let name =
+                    self.string_from_table(names, self.read::<u32>(what, base.saturating_add(8))?)?;
+                let next = self.read::<u32>(what, base.saturating_add(12))?;
+
+                entries.push(VersionNeedEntry {
+                    offset: entry_offset,
+                    hash,
+                    flags,
+                    index,
+                    name,
+                });
+
+                if next == 0 {
+                    break;
+                }
+
+                entry_offset = entry_offset.saturating_add(next as u64);
+            }
+
+            needs.push(VersionNeed {
+                offset,
+                version,
+                file,
+                entries,
+            });
+
+            if next == 0 {
+                break;
+            }
+
+            offset = offset.saturating_add(next as u64);
+        }
+
+        Ok(needs)
+    }
+
+    /// Reads the `Elf_Verdef` chain of `table`, following `vd_next` for at most `sh_info` entries.
+    fn parse_version_definitions(&self, table: &SectionHeader) -> Result<Vec<VersionDefinition>> {
+        let names = self.section(table.link as usize).ok_or(Error::OutOfRange {
+            what: "string table index",
+            offset: table.link as u64,
+            limit: self.sections.len() as u64,
+        })?;
+        let what = "version definition";
+        let mut definitions = Vec::new();
+        let mut offset = 0u64;
+
+        for _ in 0..table.info {
+            let base = table.offset.saturating_add(offset);
+            let revision = self.read::<u16>(what, base)?;
+            let flags = self.read::<u16>(what, base.saturating_add(2))?;
+            let index = self.read::<u16>(what, base.saturating_add(4))?;
+            let count = self.read::<u16>(what, base.saturating_add(6))?;
+            let hash = self.read::<u32>(what, base.saturating_add(8))?;
+            let auxiliary = self.read::<u32>(what, base.saturating_add(12))?;
+            let next = self.read::<u32>(what, base.saturating_add(16))?;
+
+            let mut definition_names = Vec::new();
+            let mut name_offset = offset.saturating_add(auxiliary as u64);
+
+            for _ in 0..count {
+                let base = table.offset.saturating_add(name_offset);
+                let name = self.string_from_table(names, self.read::<u32>(what, base)?)?;
+                let next = self.read::<u32>(what, base.saturating_add(4))?;
+
+                definition_names.push((name_offset, name));
+
+                if next == 0 {
+                    break;
+                }
+
+                name_offset = name_offset.saturating_add(next as u64);
+            }
+
+            definitions.push(VersionDefinition {
+                offset,
+                revision,
+                flags,
+                index,
+                hash,
+                names: definition_names,
+            });
+
+            if next == 0 {
+                break;
+            }
+
+            offset = offset.saturating_add(next as u64);
+        }
+
+        Ok(definitions)
+    }
+
+    pub(super) fn parse_version_tables(&mut self) {
+        let mut symbol_versions = Vec::new();
+        let mut needs = Vec::new();
+        let mut definitions = Vec::new();
+        let mut diagnostics = Vec::new();
+
+        for table in &self.sections {
+            let result = match table.section_type {
+                // versions apply to the first .gnu.version's symbol table only
+                SHT_GNU_VERSYM if symbol_versions.is_empty() => self
+                    .section_data(table)
+                    .map(|data| {
+                        data.chunks_exact(2)
+                            .filter_map(|entry| get_value::<u16>(self.endianness, entry))
+                            .collect()
+                    })
+                    .map(|versions| symbol_versions = versions)
+                    .ok_or_else(|| Error::SectionOutOfBounds {
+                        name: table.name.clone(),
+                        offset: table.offset,
+                        size: table.size,
+                        available: self.data.len() as u64,
+                    }),
+                SHT_GNU_VERNEED => self
+                    .parse_version_needs(table)
+                    .map(|entries| needs.extend(entries)),
+                SHT_GNU_VERDEF => self
+                    .parse_version_definitions(table)
+                    .map(|entries| definitions.extend(entries)),
+                _ => continue,
+            };
+
+            if let Err(error) = result {
+                diagnostics.push(Diagnostic {
+                    table: VERSION_TABLE,
+                    index: Some(table.index),
+                    error,
+                });
+            }
+        }
+
+        self.symbol_versions = symbol_versions;
+        self.version_needs = needs;
+        self.version_definitions = definitions;
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// The versions needed from each library, from `.gnu.version_r`.
+    pub fn version_needs(&self) -> &[VersionNeed] {
+        &self.version_needs
+    }
+
+    /// The versions this file defines, from `.gnu.version_d`.
+    pub fn version_definitions(&self) -> &[VersionDefinition] {
+        &self.version_definitions
+    }
+
+    /// The raw `.gnu.version` entry of `symbol`, if it belongs to the table versions apply to.
+    pub fn symbol_version_index(&self, symbol: &Symbol) -> Option<u16> {
+        let versions = self
+            .sections
+            .iter()
+            .find(|section| section.section_type == SHT_GNU_VERSYM)?;
+
+        if versions.link as usize != symbol.table {
+            return Option::None;
+        }
+
+        self.symbol_versions.get(symbol.index).copied()
+    }
+
+    fn needed_version(&self, index: u16) -> Option<&VersionNeedEntry> {
+        self.version_needs
+            .iter()
+            .flat_map(|need| need.entries.iter())
+            .find(|entry| entry.index == index)
+    }
+
+    fn defined_version(&self, index: u16) -> Option<&VersionDefinition> {
+        self.version_definitions
+            .iter()
+            .find(|definition| definition.index == index)
+    }
+
+    /// The version `symbol` is bound to, chosen the way readelf does: defined symbols look in
+    /// `.gnu.version_d` first and everything else in `.gnu.version_r`. The local and global
+    /// indexes, and the symbols naming a version definition itself, have none.
+    pub fn symbol_version(&self, symbol: &Symbol) -> Option<SymbolVersion> {
+        let entry = self.symbol_version_index(symbol)?;
+        let index = entry & VERSYM_VERSION;
+
+        if matches!(index, VER_NDX_LOCAL | VER_NDX_GLOBAL) {
+            return Option::None;
+        }
+
+        if symbol.is_defined()
+            && let Some(name) = self
+                .defined_version(index)
+                .and_then(|definition| definition.name())
+        {
+            if name == symbol.name {
+                return Option::None;
+            }
+
+            if entry & VERSYM_HIDDEN != 0 {
+                return Some(SymbolVersion::Hidden(String::from(name)));
+            }
+
+            return Some(SymbolVersion::Default(String::from(name)));
+        }
+
+        self.needed_version(index)
+            .map(|needed| SymbolVersion::Needed {
+                name: needed.name.clone(),
+                index: needed.index,
+            })
+    }
+
+    /// The newest version needed from each library, per version series, with the symbols that
+    /// need it. These are what stop a binary from loading against older libraries.
+    pub fn newest_needed_versions(&self) -> Vec<(String, String, Vec<&Symbol>)> {
+        let mut newest = BTreeMap::<(&str, &str), &VersionNeedEntry>::new();
+
+        for need in &self.version_needs {
+            for entry in &need.entries {
+                let key = (need.file.as_str(), version_series(&entry.name));
+
+                match newest.get(&key) {
+                    Some(existing)
+                        if compare_versions(&existing.name, &entry.name) != Ordering::Less => {}
+                    _ => {
+                        newest.insert(key, entry);
+                    }
+                }
+            }
+        }
+
+        newest
+            .into_iter()
+            .map(|((file, _), entry)| {
+                let symbols = self
+                    .symbols
+                    .iter()
+                    .filter(|symbol| {
+                        !symbol.is_defined()
+                            && self
+                                .symbol_version_index(symbol)
+                                .is_some_and(|index| index & VERSYM_VERSION == entry.index)
+                    })
+                    .collect();
+
+                (String::from(file), entry.name.clone(), symbols)
+            })
+            .collect()
+    }
+
+    /// The name readelf shows for a `.gnu.version` entry of the symbol at `index`.
+    fn version_entry_name(&self, table: &SectionHeader, index: usize, entry: u16) -> Option<&str> {
+        let version = entry & VERSYM_VERSION;
+        let symbol = self
+            .symbols_in(table.link as usize)
+            .find(|symbol| symbol.index == index);
+        let defined = symbol.is_some_and(Symbol::is_defined);
+
+        if !defined && let Some(needed) = self.needed_version(version) {
+            return Some(needed.name.as_str());
+        }
+
+        self.defined_version(version)
+            .and_then(VersionDefinition::name)
+            .or_else(|| {
+                self.needed_version(version)
+                    .map(|needed| needed.name.as_str())
+            })
+    }
+
+    fn version_section_heading(
+        &self,
+        table: &SectionHeader,
+        kind: &str,
+        count: u64,
+    ) -> Vec<String> {
+        let link_name = self
+            .section(table.link as usize)
+            .map(|section| section.name.as_str())
+            .unwrap_or_default();
+
+        vec![
+            String::new(),
+            format!(
+                "{} section '{}' contains {} {}:",
+                kind,
+                table.name,
+                count,
+                if count == 1 { "entry" } else { "entries" }
+            ),
+            format!(
+                " Addr: 0x{:0aw$x}  Offset: 0x{:08x}  Link: {} ({})",
+                table.address,
+                table.offset,
+                table.link,
+                link_name,
+                aw = self.format.address_size() * 2
+            ),
+        ]
+    }
+
+    /// Formats the version sections the way `readelf -V -W` does, followed by the newest version
+    /// needed from each library.
+    pub fn version_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for table in &self.sections {
+            match table.section_type {
+                SHT_GNU_VERSYM => {
+                    let count = table.size / 2;
+                    lines.extend(self.version_section_heading(table, "Version symbols", count));
+
+                    let entries = self
+                        .symbol_versions
+                        .iter()
+                        .copied()
+                        .enumerate()
+                        .collect::<Vec<_>>();
+
+                    for row in entries.chunks(4) {
+                        let mut line = format!("  {:03x}:", row[0].0);
+
+                        for (index, entry) in row {
+                            let cell = match *entry {
+                                VER_NDX_LOCAL => String::from("   0 (*local*)    "),
+                                VER_NDX_GLOBAL => String::from("   1 (*global*)   "),
+                                entry => {
+                                    let name = self
+                                        .version_entry_name(table, *index, entry)
+                                        .unwrap_or("???");
+                                    let hidden = if entry & VERSYM_HIDDEN != 0 { 'h' } else { ' ' };
+
+                                    format!(
+                                        "{:4x}{}({}{:<w$}",
+                                        entry & VERSYM_VERSION,
+                                        hidden,
+                                        name,
+                                        ")",
+                                        // readelf's `%-*s` takes a negative width as a
+                                        // positive one, padding long names past the cell
+                                        w = 12usize.abs_diff(name.len())
+                                    )
+                                }
+                            };
+
+                            line.push_str(&format!("{cell:<18}"));
+                        }
+
+                        lines.push(line);
+                    }
+                }
+                SHT_GNU_VERNEED => {
+                    lines.extend(self.version_section_heading(
+                        table,
+                        "Version needs",
+                        table.info as u64,
+                    ));
+
+                    for need in &self.version_needs {
+                        lines.push(format!(
+                            "  {}: Version: {}  File: {}  Cnt: {}",
+                            entry_offset(need.offset),
+                            need.version,
+                            need.file,
+                            need.entries.len()
+                        ));
+
+                        for entry in &need.entries {
+                            lines.push(format!(
+                                "  {}:   Name: {}  Flags: {}  Version: {}",
+                                entry_offset(entry.offset),
+                                entry.name,
+                                version_flags_name(entry.flags),
+                                entry.index
+                            ));
+                        }
+                    }
+                }
+                SHT_GNU_VERDEF => {
+                    lines.extend(self.version_section_heading(
+                        table,
+                        "Version definition",
+                        table.info as u64,
+                    ));
+
+                    for definition in &self.version_definitions {
+                        lines.push(format!(
+                            "  {}: Rev: {}  Flags: {}  Index: {}  Cnt: {}  Name: {}",
+                            entry_offset(definition.offset),
+                            definition.revision,
+                            version_flags_name(definition.flags),
+                            definition.index,
+                            definition.names.len(),
+                            definition.name().unwrap_or_default()
+                        ));
+
+                        for (parent, (offset, name)) in definition.names.iter().enumerate().skip(1)
+                        {
+                            lines.push(format!(
+                                "  {}: Parent {}: {}",
+                                entry_offset(*offset),
+                                parent,
+                                name
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if lines.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("No version information found in this file."));
+            return lines;
+        }
+
+        let newest = self.newest_needed_versions();
+
+        if !newest.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("Newest version needed from each library:"));
+        }
+
+        for (file, version, symbols) in newest {
+            let names = symbols
+                .iter()
+                .map(|symbol| symbol.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            if names.is_empty() {
+                lines.push(format!("  {file}: {version}"));
+            } else {
+                lines.push(format!("  {file}: {version} ({names})"));
+            }
+        }
+
+        lines
+    }
+
+    pub fn dump_versions(&self, out: &mut dyn OutputSink) {
+        out.begin_section("versions");
+
+        for line in self.version_lines() {
+            out.write_line(line.as_str());
+        }
+    }
+}
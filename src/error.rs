@@ -0,0 +1,181 @@
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong while loading or parsing a file.
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: String,
+        source: io::Error,
+    },
+    /// `what` needed `needed` bytes at `offset` but the file ends at `available`.
+    Truncated {
+        what: &'static str,
+        offset: u64,
+        needed: u64,
+        available: u64,
+    },
+    BadMagic,
+    UnsupportedClass(u8),
+    /// `EI_DATA` is neither little (1) nor big (2) endian.
+    UnsupportedDataEncoding(u8),
+    UnsupportedMachine(u16),
+    SectionOutOfBounds {
+        name: String,
+        offset: u64,
+        size: u64,
+        available: u64,
+    },
+    /// `what` refers to `offset`, which is not below `limit`.
+    OutOfRange {
+        what: &'static str,
+        offset: u64,
+        limit: u64,
+    },
+    InvalidValue {
+        what: &'static str,
+        value: u64,
+    },
+    SectionNotFound(String),
+    SymbolNotFound(String),
+    SectionNotExecutable(String),
+    /// No loaded segment or allocated section covers the virtual address.
+    AddressNotMapped(u64),
+    /// No structure, class or union in `.debug_info` has the requested name.
+    TypeNotFound(String),
+    /// A compressed section's `what` could not be inflated.
+    Decompression {
+        what: &'static str,
+        message: String,
+    },
+    /// An `ar` archive lacks its magic, or the member header at `offset` is malformed.
+    MalformedArchive {
+        offset: u64,
+        problem: &'static str,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Process exit code reported by the CLI; each variant has its own so scripts can tell them apart.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Io { .. } => 3,
+            Error::Truncated { .. } => 4,
+            Error::BadMagic => 5,
+            Error::UnsupportedClass(_) => 6,
+            Error::UnsupportedDataEncoding(_) => 7,
+            Error::UnsupportedMachine(_) => 8,
+            Error::SectionOutOfBounds { .. } => 9,
+            Error::OutOfRange { .. } => 10,
+            Error::InvalidValue { .. } => 11,
+            Error::SectionNotFound(_) => 12,
+            Error::SymbolNotFound(_) => 13,
+            Error::SectionNotExecutable(_) => 14,
+            Error::AddressNotMapped(_) => 15,
+            Error::TypeNotFound(_) => 16,
+            Error::Decompression { .. } => 17,
+            Error::MalformedArchive { .. } => 18,
+        }
+    }
+
+    /// An [`Error::Io`] for `path`.
+    pub fn io(path: &str, source: io::Error) -> Error {
+        Error::Io {
+            path: path.to_owned(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{path}: {source}"),
+            Error::Truncated {
+                what,
+                offset,
+                needed,
+                available,
+            } => write!(
+                f,
+                "truncated {what}: needed {needed} bytes at offset {offset:#X} but the file is {available} bytes long"
+            ),
+            Error::BadMagic => write!(f, "invalid header: not an ELF file"),
+            Error::UnsupportedClass(class) => write!(f, "unsupported ELF class {class:02X}"),
+            Error::UnsupportedDataEncoding(encoding) => {
+                write!(f, "unsupported ELF data encoding {encoding:02X}")
+            }
+            Error::UnsupportedMachine(machine) => {
+                write!(
+                    f,
+                    "no disassembler for machine {machine:#x} ({})",
+                    crate::disassemble::machine_name(*machine)
+                )
+            }
+            Error::SectionOutOfBounds {
+                name,
+                offset,
+                size,
+                available,
+            } => write!(
+                f,
+                "section {name} at offset {offset:#X} with size {size:#X} lies outside the file ({available:#X} bytes)"
+            ),
+            Error::OutOfRange {
+                what,
+                offset,
+                limit,
+            } => write!(f, "{what} {offset:#X} is out of range (limit {limit:#X})"),
+            Error::InvalidValue { what, value } => write!(f, "invalid {what} {value:#X}"),
+            Error::SectionNotFound(name) => write!(f, "no section named {name}"),
+            Error::SymbolNotFound(name) => write!(f, "no defined symbol named {name}"),
+            Error::SectionNotExecutable(name) => {
+                write!(
+                    f,
+                    "section {name} is not executable (use --force to disassemble it anyway)"
+                )
+            }
+            Error::AddressNotMapped(address) => {
+                write!(
+                    f,
+                    "virtual address {address:#x} is not mapped from the file"
+                )
+            }
+            Error::TypeNotFound(name) => write!(f, "no DWARF type named {name}"),
+            Error::Decompression { what, message } => {
+                write!(f, "cannot decompress {what}: {message}")
+            }
+            Error::MalformedArchive { offset, problem } => {
+                write!(f, "malformed archive at offset {offset:#X}: {problem}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => Option::None,
+        }
+    }
+}
+
+/// A problem found in one entry of an ELF table that was skipped so the rest of the file could still be read.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub table: &'static str,
+    pub index: Option<usize>,
+    pub error: Error,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "{}[{}]: {}", self.table, index, self.error),
+            Option::None => write!(f, "{}: {}", self.table, self.error),
+        }
+    }
+}
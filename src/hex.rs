@@ -1,63 +1,85 @@
 use colored::*;
-use std::fmt::Write;
 use std::fs::File;
 use std::io::Read;
-use std::sync::{Arc, Mutex};
 
 use crate::disassemble::*;
+use crate::error::{Error, Result};
+use crate::output::OutputSink;
+
+/// Where a search hit lies in the file and, for ELF files, in the loaded image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Occurrence {
+    pub offset: usize,
+    /// Name of the section whose bytes include the hit.
+    pub section: Option<String>,
+    /// Virtual address the hit is loaded at.
+    pub address: Option<u64>,
+}
+
+impl Occurrence {
+    /// A one line summary, e.g. `0x1234 in .rodata at vaddr 0x401234`.
+    pub fn location(&self) -> String {
+        let mut location = format!("{:#x}", self.offset);
+
+        if let Some(section) = self.section.as_ref() {
+            location.push_str(format!(" in {section}").as_str());
+        }
+
+        if let Some(address) = self.address {
+            location.push_str(format!(" at vaddr {address:#x}").as_str());
+        }
+
+        location
+    }
+}
 
+/// A file loaded into memory together with the layout used to hex dump it.
 pub struct Session {
     data: Vec<u8>,
     column_count: usize,
     group_count: usize,
-    output_string: Arc<Mutex<String>>,
     use_color: bool,
 }
 
 impl Session {
-    pub fn new(
+    /// Reads `filename` into a new session.
+    pub fn open(
         filename: &str,
         column_count: usize,
         group_count: usize,
-        output_string: Arc<Mutex<String>>,
         use_color: bool,
-    ) -> Option<Session> {
-        let file = File::open(filename);
-
-        if file.is_err() {
-            let mut s = output_string.lock().unwrap();
-            let _ = writeln!(s, "failed to open file {filename}");
-
-            return Option::None;
-        }
-
+    ) -> Result<Session> {
         let mut data = Vec::<u8>::new();
-        let result = file.unwrap().read_to_end(&mut data);
 
-        if result.is_err() {
-            let mut s = output_string.lock().unwrap();
-            let _ = writeln!(s, "failed to read file {filename}");
+        File::open(filename)
+            .and_then(|mut file| file.read_to_end(&mut data))
+            .map_err(|e| Error::io(filename, e))?;
 
-            return Option::None;
-        }
+        Ok(Session::new(data, column_count, group_count, use_color))
+    }
 
-        Some(Session {
+    /// Creates a session over bytes that are already in memory; zero column or group counts are treated as 1.
+    pub fn new(data: Vec<u8>, column_count: usize, group_count: usize, use_color: bool) -> Session {
+        Session {
             data,
-            column_count,
-            group_count,
-            output_string,
+            column_count: column_count.max(1),
+            group_count: group_count.max(1),
             use_color,
-        })
+        }
     }
 
-    pub fn elf_header(&self) -> Option<ElfHeader> {
-        ElfHeader::new(&self.data, self.output_string.clone())
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn elf_header(&self) -> Result<ElfHeader> {
+        ElfHeader::new(&self.data)
     }
 
     fn format_byte(&self, mut index: usize, byte: &u8) -> String {
         index += 1;
 
-        let format_str = if index % self.group_count == 0 {
+        let format_str = if index.is_multiple_of(self.group_count) {
             format!("{:02X} ", byte)
         } else {
             format!("{:02X}", byte)
@@ -74,8 +96,7 @@ impl Session {
         format_str
     }
 
-    fn format_hex_line(&self, bytes: &[u8], row_index: usize) -> String {
-        let offset = row_index * self.column_count;
+    fn format_hex_line(&self, bytes: &[u8], label: u64) -> String {
         let byte_stream = bytes
             .iter()
             .enumerate()
@@ -85,30 +106,30 @@ impl Session {
         let str_stream = bytes
             .iter()
             .map(|byte| {
-                if byte.is_ascii_graphic() && self.use_color {
-                    return (*byte as char).to_string().green().to_string();
+                if byte.is_ascii_graphic() {
+                    if self.use_color {
+                        return (*byte as char).to_string().green().to_string();
+                    }
+
+                    return (*byte as char).to_string();
                 }
 
                 if *byte != 0 && self.use_color {
                     return ".".to_string().red().to_string();
                 }
 
-                return ".".to_owned();
+                ".".to_owned()
             })
             .collect::<String>();
 
-        format!("{:08X} {} {}\n", offset, byte_stream, str_stream)
+        format!("{:08X} {} {}", label, byte_stream, str_stream)
     }
 
     fn get_sep(&self) -> String {
         let count =
             size_of::<u64>() + self.column_count * self.group_count + self.column_count * 2 + 1;
 
-        let mut sep = String::new();
-
-        for _ in 0..count {
-            sep += "-";
-        }
+        let sep = "-".repeat(count);
 
         if self.use_color {
             return sep.magenta().to_string();
@@ -117,52 +138,188 @@ impl Session {
         sep
     }
 
-    pub fn dump(&self) {
-        let contents = self
-            .data
+    /// Formats the row of the hex dump starting at `row_index * column_count`, if it exists.
+    pub fn hex_line(&self, row_index: usize) -> Option<String> {
+        let start = row_index.checked_mul(self.column_count)?;
+        let bytes = self.data.get(start..)?;
+
+        if bytes.is_empty() {
+            return Option::None;
+        }
+
+        let bytes = &bytes[..self.column_count.min(bytes.len())];
+        Some(self.format_hex_line(bytes, start as u64))
+    }
+
+    /// Iterates over every row of the hex dump.
+    pub fn hex_lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.hex_lines_in(0, self.data.len())
+    }
+
+    /// Iterates over the rows of a hex dump of `length` bytes starting at `offset`, clamped to the
+    /// end of the file. Rows start at `offset` and are labelled with their file offset.
+    pub fn hex_lines_in(&self, offset: usize, length: usize) -> impl Iterator<Item = String> + '_ {
+        self.hex_lines_at(offset, length, offset as u64)
+    }
+
+    /// Like [`Session::hex_lines_in`], but rows are labelled with addresses counting up from
+    /// `address` instead of with file offsets.
+    pub fn hex_lines_at(
+        &self,
+        offset: usize,
+        length: usize,
+        address: u64,
+    ) -> impl Iterator<Item = String> + '_ {
+        let start = offset.min(self.data.len());
+        let end = start.saturating_add(length).min(self.data.len());
+
+        self.hex_lines_of(&self.data[start..end], address)
+    }
+
+    /// Iterates over the rows of a hex dump of `bytes`, which need not come from the file, such as
+    /// a decompressed section. Rows are labelled with addresses counting up from `address`.
+    pub fn hex_lines_of<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        address: u64,
+    ) -> impl Iterator<Item = String> + 'a {
+        bytes
             .chunks(self.column_count)
             .enumerate()
-            .map(|(i, chunk)| self.format_hex_line(chunk, i))
-            .collect::<String>();
-
-        let _ = writeln!(self.output_string.lock().unwrap(), "{}", contents);
+            .map(move |(i, chunk)| {
+                let label = address.wrapping_add((i * self.column_count) as u64);
+                self.format_hex_line(chunk, label)
+            })
     }
 
-    pub fn list_occurrences(&self, bytes: &[u8]) {
-        let sep = self.get_sep();
+    /// Returns the file offset of every occurrence of `bytes`.
+    pub fn find(&self, bytes: &[u8]) -> Vec<usize> {
+        if bytes.is_empty() {
+            return Vec::new();
+        }
 
-        let occurrences = self
-            .data
+        self.data
             .windows(bytes.len())
             .enumerate()
             .filter(|(_, window)| bytes == *window)
-            .map(|(i, window)| {
-                let raw_index = i;
-                let row_index = raw_index / self.column_count;
-
-                let count = window.len() / 16 + 1;
-                let mut window_string = String::new();
-
-                for c in 0..count {
-                    let start = (row_index + c) * self.column_count;
-                    window_string += self
-                        .format_hex_line(
-                            &self.data[start..(start + self.column_count).min(self.data.len())],
-                            row_index + c,
-                        )
-                        .as_str();
-                }
+            .map(|(i, _)| i)
+            .collect()
+    }
 
-                window_string
+    /// Finds every occurrence of `bytes`, locating each in the ELF sections and segments when the
+    /// file parses as ELF.
+    pub fn occurrences(&self, bytes: &[u8]) -> Vec<Occurrence> {
+        let elf = self.elf_header().ok();
+
+        self.find(bytes)
+            .into_iter()
+            .map(|offset| {
+                let Some(elf) = elf.as_ref() else {
+                    return Occurrence {
+                        offset,
+                        section: Option::None,
+                        address: Option::None,
+                    };
+                };
+
+                Occurrence {
+                    offset,
+                    section: elf
+                        .section_containing_offset(offset as u64)
+                        .map(|section| section.name.clone()),
+                    address: elf.offset_to_vaddr(offset as u64),
+                }
             })
-            .map(|s| format!("{}\n{}", sep, s))
-            .collect::<String>();
+            .collect()
+    }
+
+    /// Formats the hex dump rows covering `length` bytes starting at `offset`.
+    pub fn occurrence_lines(&self, offset: usize, length: usize) -> Vec<String> {
+        let row_index = offset / self.column_count;
+        let count = (offset % self.column_count + length).div_ceil(self.column_count);
+
+        (row_index..row_index + count)
+            .map_while(|row| self.hex_line(row))
+            .collect()
+    }
+
+    pub fn dump(&self, out: &mut dyn OutputSink) {
+        self.dump_range(0, self.data.len(), out);
+    }
+
+    pub fn dump_range(&self, offset: usize, length: usize, out: &mut dyn OutputSink) {
+        self.dump_range_at(offset, length, offset as u64, out);
+    }
+
+    /// Hex dumps `length` bytes from `offset`, labelling the rows with addresses from `address`.
+    pub fn dump_range_at(
+        &self,
+        offset: usize,
+        length: usize,
+        address: u64,
+        out: &mut dyn OutputSink,
+    ) {
+        out.begin_section("hex");
+
+        for line in self.hex_lines_at(offset, length, address) {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+
+    /// Hex dumps `bytes`, labelling the rows with addresses from `address`.
+    pub fn dump_bytes(&self, bytes: &[u8], address: u64, out: &mut dyn OutputSink) {
+        out.begin_section("hex");
+
+        for line in self.hex_lines_of(bytes, address) {
+            out.write_line(line.as_str());
+        }
+
+        out.write_line("");
+    }
+
+    /// Hex dumps the section called `name` of `elf`, this session's file, the way `--hex-dump`
+    /// does: compressed sections decompressed with rows counting from 0, others by file offset.
+    pub fn dump_section(
+        &self,
+        elf: &ElfHeader,
+        name: &str,
+        out: &mut dyn OutputSink,
+    ) -> Result<()> {
+        let section = elf
+            .section_by_name(name)
+            .ok_or_else(|| Error::SectionNotFound(String::from(name)))?;
+
+        if let Some(contents) = elf.decompressed_data(section) {
+            self.dump_bytes(contents, 0, out);
+        } else if elf.section_data(section).is_some() {
+            self.dump_range(section.offset as usize, section.size as usize, out);
+        } else {
+            out.write_line(format!("Section '{name}' has no data to dump.").as_str());
+        }
+
+        Ok(())
+    }
+
+    pub fn list_occurrences(&self, bytes: &[u8], out: &mut dyn OutputSink) {
+        let sep = self.get_sep();
+
+        out.begin_section("search");
+
+        for occurrence in self.occurrences(bytes) {
+            out.write_line(sep.as_str());
+            out.write_line(occurrence.location().as_str());
+
+            for line in self.occurrence_lines(occurrence.offset, bytes.len()) {
+                out.write_line(line.as_str());
+            }
+        }
 
-        let _ = writeln!(self.output_string.lock().unwrap(), "{}", occurrences);
+        out.write_line("");
     }
 
-    pub fn list_occurrences_string(&self, s: &str) {
-        let bytes = s.to_owned().into_bytes();
-        self.list_occurrences(&bytes);
+    pub fn list_occurrences_string(&self, s: &str, out: &mut dyn OutputSink) {
+        self.list_occurrences(s.as_bytes(), out);
     }
 }
@@ -0,0 +1,26 @@
+//! Hex dumping and ELF/aarch64 disassembly.
+//!
+//! [`Session`] holds a file in memory and formats hex dumps and search results,
+//! [`ElfHeader`] parses an ELF image and disassembles its code, [`Archive`]
+//! splits an `ar` archive into its members, and the [`output`] module provides
+//! sinks for streaming any of them somewhere.
+
+pub mod archive;
+pub mod decode_byte;
+pub mod demangle;
+pub mod disassemble;
+pub mod error;
+pub mod hex;
+pub mod output;
+
+pub use archive::{Archive, ArchiveMember};
+pub use disassemble::aarch64_disassembler::{decode_aarch64_instruction, disassemble_aarch64};
+pub use disassemble::{
+    CompileUnit, DebugFunction, DisassembledSection, DynamicEntry, ElfHeader, FileHeader,
+    Instruction, LineProgram, MappedFiles, Note, NoteTable, ProcessInfo, ProcessStatus,
+    ProgramHeader, SectionHeader, SourceLocation, StructLayout, Symbol, SymbolVersion,
+    VersionDefinition, VersionNeed,
+};
+pub use error::{Diagnostic, Error, Result};
+pub use hex::{Occurrence, Session};
+pub use output::OutputSink;
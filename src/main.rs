@@ -1,16 +1,11 @@
-use std::{
-    fs::File,
-    io::{self, Error, Write},
-    process::Command,
-    sync::{Arc, Mutex},
-};
-
-mod decode_byte;
-mod disassemble;
-mod hex;
+use std::fs::File;
+use std::io;
+use std::process::{Command, ExitCode, Stdio};
 
 use clap::Parser;
-use hex::Session;
+use toydump::archive::{Archive, is_archive};
+use toydump::output::{FileSink, JsonSink, OutputSink, StdoutSink};
+use toydump::{ElfHeader, Error, Result, Session};
 
 #[derive(Parser)]
 #[command(
@@ -27,6 +22,128 @@ struct Args {
     #[arg(short, long, help = "Dump disassembly?")]
     disassembly: bool,
 
+    #[arg(
+        short = 'S',
+        long,
+        help = "Disassemble with source file names, line numbers and source lines interleaved, like objdump -S -l"
+    )]
+    source: bool,
+
+    #[arg(
+        long,
+        help = "List the members of an ar archive and its symbol index, like ar tv and readelf -c; the default for archives"
+    )]
+    members: bool,
+
+    #[arg(long, help = "Print every ELF header field, like readelf -h")]
+    elf_header: bool,
+
+    #[arg(long, help = "List the section headers, like readelf -S")]
+    sections: bool,
+
+    #[arg(
+        long,
+        help = "List the program headers and section to segment mapping, like readelf -l"
+    )]
+    segments: bool,
+
+    #[arg(
+        long,
+        help = "List the .symtab and .dynsym symbol tables, like readelf -s"
+    )]
+    symbols: bool,
+
+    #[arg(
+        long,
+        help = "Report hardening features: PIE, RELRO, NX, canary, FORTIFY, RPATH, BTI/PAC..."
+    )]
+    checksec: bool,
+
+    #[arg(
+        long,
+        help = "Decode the notes (build ID, ABI tag, GNU properties...), like readelf -n"
+    )]
+    notes: bool,
+
+    #[arg(
+        long,
+        help = "Summarise a core dump: the process, each thread's signal and registers, the auxiliary vector and the mapped files"
+    )]
+    core: bool,
+
+    #[arg(
+        long,
+        help = "Show the symbol versions needed and defined, like readelf -V, and the newest version needed from each library"
+    )]
+    version_info: bool,
+
+    #[arg(
+        long,
+        help = "Show the bucket list length histograms of .hash and .gnu.hash, like readelf -I"
+    )]
+    histogram: bool,
+
+    #[arg(long, help = "List the relocation sections, like readelf -r")]
+    relocs: bool,
+
+    #[arg(
+        long,
+        help = "Print the dynamic section and the libraries it needs, like readelf -d"
+    )]
+    dynamic: bool,
+
+    #[arg(
+        long,
+        help = "List the DWARF compilation units with their functions, parameters and types"
+    )]
+    dwarf: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Show the field offsets and sizes of a DWARF struct, class or union, e.g. MyStruct or nc::MyStruct"
+    )]
+    dwarf_type: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Disassemble only the named function"
+    )]
+    function: Option<String>,
+
+    #[arg(
+        long = "section",
+        value_name = "NAME",
+        help = "Disassemble only the named section; may be repeated"
+    )]
+    disassemble_sections: Vec<String>,
+
+    #[arg(long, help = "Disassemble --section even when it is not executable")]
+    force: bool,
+
+    #[arg(long, help = "Demangle Rust and C++ symbol names")]
+    demangle: bool,
+
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        value_parser = parse_address,
+        help = "Start at a virtual address, e.g. 0x400123: hex dumps to the end of its section, or disassembles from there with -d"
+    )]
+    vaddr: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        value_parser = parse_address,
+        help = "Hex dump at most this many bytes from --vaddr, e.g. 0x100 around a stack pointer in a core dump"
+    )]
+    length: Option<u64>,
+
+    #[arg(long, value_name = "SECTION", help = "Hex dump the named section")]
+    hex_dump: Option<String>,
+
     #[arg(long, help = "Optionally choose number of columns for hex dump")]
     columns: Option<u32>,
 
@@ -42,6 +159,12 @@ struct Args {
     #[arg(short, long, help = "Optional output filepath")]
     output: Option<String>,
 
+    #[arg(long, help = "Emit output as JSON")]
+    json: bool,
+
+    #[arg(long, help = "Disable colored output")]
+    no_color: bool,
+
     #[arg(
         short,
         long,
@@ -50,66 +173,306 @@ struct Args {
     editor: Option<String>,
 }
 
-fn main() {
-    let args = Args::parse();
-    let output = Arc::new(Mutex::new(String::new()));
+const USAGE_EXIT_CODE: u8 = 2;
+
+/// Parses a `0x` prefixed hexadecimal or a decimal address.
+fn parse_address(value: &str) -> std::result::Result<u64, String> {
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        Option::None => value.parse::<u64>(),
+    };
+
+    parsed.map_err(|e| format!("invalid address {value}: {e}"))
+}
 
-    let session = Session::new(
+fn create_sink(args: &Args) -> Result<Box<dyn OutputSink>> {
+    let Some(output_filepath) = args.output.as_ref() else {
+        if args.json {
+            return Ok(Box::new(JsonSink::new(io::stdout())));
+        }
+
+        return Ok(Box::new(StdoutSink::new(!args.no_color)));
+    };
+
+    let file = File::create(output_filepath).map_err(|e| Error::io(output_filepath, e))?;
+
+    if args.json {
+        return Ok(Box::new(JsonSink::new(io::BufWriter::new(file))));
+    }
+
+    Ok(Box::new(FileSink::new(file)))
+}
+
+/// Whether any of the options that parse the file as ELF were given.
+fn inspects_elf(args: &Args) -> bool {
+    args.elf_header
+        || args.sections
+        || args.segments
+        || args.symbols
+        || args.dynamic
+        || args.relocs
+        || args.version_info
+        || args.histogram
+        || args.notes
+        || args.core
+        || args.checksec
+        || args.dwarf
+        || args.dwarf_type.is_some()
+        || args.hex_dump.is_some()
+        || args.vaddr.is_some()
+        || args.disassembly
+        || args.source
+        || args.function.is_some()
+        || !args.disassemble_sections.is_empty()
+}
+
+fn run(args: &Args, output: &mut dyn OutputSink) -> Result<()> {
+    let session = Session::open(
         args.filepath.as_str(),
         args.columns.unwrap_or(16) as usize,
         args.groups.unwrap_or(2) as usize,
-        output.clone(),
-        args.output.is_none(),
-    );
+        output.use_color(),
+    )?;
 
-    if session.is_none() {
-        return;
+    if is_archive(session.data()) {
+        return run_archive(args, &session, output);
     }
 
-    let session = session.unwrap();
+    let elf = match inspects_elf(args) {
+        true => Some(session.elf_header()?),
+        false => Option::None,
+    };
 
-    if args.hex {
-        session.dump();
+    inspect(args, &session, elf.as_ref(), output)
+}
+
+/// Lists an archive's members, then runs the requested dumps on each ELF member in turn. A member
+/// that fails, say one without the `--function` asked for, is a warning; only when every ELF member
+/// fails does the first failure become the result.
+fn run_archive(args: &Args, session: &Session, output: &mut dyn OutputSink) -> Result<()> {
+    let archive = Archive::new(session.data())?;
+
+    for diagnostic in archive.diagnostics() {
+        eprintln!("toydump: warning: {diagnostic}");
     }
 
-    if args.disassembly {
-        let elf = session.elf_header();
-        if elf.is_some() {
-            elf.unwrap().dump_disassembly();
+    let per_member = inspects_elf(args) || args.hex || args.search.is_some();
+
+    if args.members || !per_member {
+        archive.dump_members(output);
+        archive.dump_index(args.filepath.as_str(), output);
+    }
+
+    if !per_member {
+        return Ok(());
+    }
+
+    let mut failure = Option::None;
+    let mut succeeded = false;
+
+    for member in archive.members() {
+        let name = format!("{}({})", args.filepath, member.name);
+        let data = archive.member_data(member);
+
+        output.begin_section("archive member");
+        output.write_line("");
+        output.write_line(format!("File: {name}").as_str());
+
+        let elf = match ElfHeader::new(data) {
+            Err(Error::BadMagic) => {
+                output.write_line("Not an ELF file - it has the wrong magic bytes at the start");
+                continue;
+            }
+            elf => elf,
+        };
+
+        let member_session = Session::new(
+            data.to_vec(),
+            args.columns.unwrap_or(16) as usize,
+            args.groups.unwrap_or(2) as usize,
+            output.use_color(),
+        );
+
+        // only the dumps that need ELF care whether the member parsed
+        let result = match elf {
+            _ if !inspects_elf(args) => inspect(args, &member_session, Option::None, output),
+            Ok(elf) => inspect(args, &member_session, Some(&elf), output),
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(()) => succeeded = true,
+            Err(error) => {
+                eprintln!("toydump: warning: {name}: {error}");
+                failure.get_or_insert(error);
+            }
         }
     }
 
-    if args.search.is_some() {
-        let search_string = args.search.unwrap();
-        session.list_occurrences_string(&search_string);
+    match failure {
+        Some(error) if !succeeded => Err(error),
+        _ => Ok(()),
     }
+}
 
-    if args.output.is_some() {
-        let output_filepath = args.output.unwrap();
-        let mut file = File::create(output_filepath.clone())
-            .expect(format!("failed to create file {output_filepath}").as_str());
+/// Runs the requested dumps on one file, or one member of an archive, given the file parsed as ELF
+/// when any of the options need it.
+fn inspect(
+    args: &Args,
+    session: &Session,
+    elf: Option<&ElfHeader>,
+    output: &mut dyn OutputSink,
+) -> Result<()> {
+    if args.hex && args.vaddr.is_none() {
+        session.dump(output);
+    }
 
-        let _ = file.write_all(output.lock().unwrap().as_bytes());
+    if let Some(elf) = elf {
+        let reported = elf.diagnostics().len();
 
-        if let Some(editor) = args.editor.as_ref() {
-            let status = Command::new(editor)
-                .arg(&output_filepath)
-                .stdin(std::process::Stdio::inherit())
-                .stdout(std::process::Stdio::inherit())
-                .stderr(std::process::Stdio::inherit())
-                .status()
-                .expect("failed to run editor");
+        for diagnostic in elf.diagnostics() {
+            eprintln!("toydump: warning: {diagnostic}");
+        }
+
+        if args.elf_header {
+            elf.dump_header(output);
+        }
 
-            println!("Editor exited with status: {}", status);
+        if args.sections {
+            elf.dump_sections(output);
         }
 
-        return;
+        if args.segments {
+            elf.dump_segments(output);
+        }
+
+        if args.symbols {
+            elf.dump_symbols(args.demangle, output);
+        }
+
+        if args.relocs {
+            elf.dump_relocations(args.demangle, output);
+        }
+
+        if args.version_info {
+            elf.dump_versions(output);
+        }
+
+        if args.histogram {
+            elf.dump_histogram(output);
+        }
+
+        if args.checksec {
+            elf.dump_checksec(output);
+        }
+
+        if args.notes {
+            elf.dump_notes(output);
+        }
+
+        if args.dynamic {
+            elf.dump_dynamic(output);
+        }
+
+        if args.core {
+            elf.dump_core(output);
+        }
+
+        if args.dwarf {
+            elf.dump_debug_info(output);
+        }
+
+        if let Some(name) = args.dwarf_type.as_ref() {
+            elf.dump_struct_layout(name, output)?;
+        }
+
+        if let Some(name) = args.hex_dump.as_ref() {
+            session.dump_section(elf, name, output)?;
+        }
+
+        let disassembly = args.disassembly || args.source;
+
+        if let Some(address) = args.vaddr.filter(|_| !disassembly) {
+            let (offset, length) = elf.file_range_at_vaddr(address)?;
+            let length = args.length.map_or(length, |limit| length.min(limit));
+            session.dump_range_at(offset as usize, length as usize, address, output);
+        }
+
+        if let Some(name) = args.function.as_ref() {
+            elf.dump_function_disassembly(name, args.demangle, args.source, output)?;
+        } else if !args.disassemble_sections.is_empty() {
+            elf.dump_section_disassembly(
+                &args.disassemble_sections,
+                args.force,
+                args.demangle,
+                args.source,
+                output,
+            )?;
+        } else if let Some(address) = args.vaddr.filter(|_| disassembly) {
+            elf.dump_disassembly_at(address, args.force, args.demangle, args.source, output)?;
+        } else if disassembly {
+            elf.dump_disassembly(args.demangle, args.source, output)?;
+        }
+
+        // the debug info and compressed sections are only parsed by the dumps that need them
+        for diagnostic in elf.diagnostics().into_iter().skip(reported) {
+            eprintln!("toydump: warning: {diagnostic}");
+        }
+    }
+
+    if let Some(search_string) = args.search.as_ref() {
+        session.list_occurrences_string(search_string, output);
+    }
+
+    Ok(())
+}
+
+fn open_editor(editor: &str, output_filepath: &str) -> Result<()> {
+    let status = Command::new(editor)
+        .arg(output_filepath)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|e| Error::io(editor, e))?;
+
+    println!("Editor exited with status: {}", status);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    if args.editor.is_some() && args.output.is_none() {
+        eprintln!("an output file must be specified in order to open it with an editor");
+        return ExitCode::from(USAGE_EXIT_CODE);
     }
 
-    if args.editor.is_some() {
-        println!("an output file must be specified in order to open it with an editor");
-        return;
+    let mut output = match create_sink(&args) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("toydump: {e}");
+            return ExitCode::from(e.exit_code());
+        }
+    };
+
+    let result = run(&args, output.as_mut());
+    output.finish();
+
+    let result = match (args.output.as_ref(), args.editor.as_ref()) {
+        (Some(output_filepath), Some(editor)) if result.is_ok() => {
+            open_editor(editor, output_filepath)
+        }
+        _ => result,
+    };
+
+    if let Err(e) = result {
+        eprintln!("toydump: {e}");
+        return ExitCode::from(e.exit_code());
     }
 
-    println!("{}", output.lock().unwrap());
+    ExitCode::SUCCESS
 }
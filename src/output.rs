@@ -0,0 +1,182 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Stdout, Write};
+
+/// Destination for the lines produced by toydump, written as soon as they are formatted.
+pub trait OutputSink {
+    fn write_line(&mut self, line: &str);
+
+    /// Marks the start of a logical block of output such as a hex dump or a disassembled section.
+    fn begin_section(&mut self, _name: &str) {}
+
+    /// Whether lines written to this sink may contain terminal color codes.
+    fn use_color(&self) -> bool {
+        false
+    }
+
+    /// Flushes anything still buffered; called once after the last line.
+    fn finish(&mut self) {}
+}
+
+/// Writes each line to stdout.
+pub struct StdoutSink {
+    stdout: Stdout,
+    use_color: bool,
+}
+
+impl StdoutSink {
+    pub fn new(use_color: bool) -> StdoutSink {
+        StdoutSink {
+            stdout: io::stdout(),
+            use_color,
+        }
+    }
+}
+
+impl OutputSink for StdoutSink {
+    fn write_line(&mut self, line: &str) {
+        let _ = writeln!(self.stdout.lock(), "{line}");
+    }
+
+    fn use_color(&self) -> bool {
+        self.use_color
+    }
+
+    fn finish(&mut self) {
+        let _ = self.stdout.flush();
+    }
+}
+
+/// Writes plain lines to a file.
+pub struct FileSink {
+    writer: BufWriter<File>,
+}
+
+impl FileSink {
+    pub fn new(file: File) -> FileSink {
+        FileSink {
+            writer: BufWriter::new(file),
+        }
+    }
+}
+
+impl OutputSink for FileSink {
+    fn write_line(&mut self, line: &str) {
+        let _ = writeln!(self.writer, "{line}");
+    }
+
+    fn finish(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+/// Collects lines into a `String`.
+pub struct MemorySink {
+    contents: String,
+    use_color: bool,
+}
+
+impl MemorySink {
+    pub fn new(use_color: bool) -> MemorySink {
+        MemorySink {
+            contents: String::new(),
+            use_color,
+        }
+    }
+
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+}
+
+impl OutputSink for MemorySink {
+    fn write_line(&mut self, line: &str) {
+        self.contents += line;
+        self.contents.push('\n');
+    }
+
+    fn use_color(&self) -> bool {
+        self.use_color
+    }
+}
+
+/// Streams `[{"section": "...", "lines": ["...", ...]}, ...]` as lines arrive.
+pub struct JsonSink<W: Write> {
+    writer: W,
+    in_section: bool,
+    first_section: bool,
+    first_line: bool,
+}
+
+impl<W: Write> JsonSink<W> {
+    pub fn new(writer: W) -> JsonSink<W> {
+        JsonSink {
+            writer,
+            in_section: false,
+            first_section: true,
+            first_line: true,
+        }
+    }
+
+    fn close_section(&mut self) {
+        if self.in_section {
+            let _ = write!(self.writer, "]}}");
+            self.in_section = false;
+        }
+    }
+}
+
+impl<W: Write> OutputSink for JsonSink<W> {
+    fn write_line(&mut self, line: &str) {
+        if !self.in_section {
+            self.begin_section("output");
+        }
+
+        let sep = if self.first_line { "" } else { "," };
+        self.first_line = false;
+
+        let _ = write!(self.writer, "{sep}\n    \"{}\"", escape_json(line));
+    }
+
+    fn begin_section(&mut self, name: &str) {
+        self.close_section();
+
+        let sep = if self.first_section { "[" } else { "," };
+        self.first_section = false;
+        self.in_section = true;
+        self.first_line = true;
+
+        let _ = write!(
+            self.writer,
+            "{sep}\n  {{\"section\": \"{}\", \"lines\": [",
+            escape_json(name)
+        );
+    }
+
+    fn finish(&mut self) {
+        self.close_section();
+
+        let open = if self.first_section { "[" } else { "" };
+        self.first_section = false;
+
+        let _ = writeln!(self.writer, "{open}\n]");
+        let _ = self.writer.flush();
+    }
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped += "\\\"",
+            '\\' => escaped += "\\\\",
+            '\n' => escaped += "\\n",
+            '\r' => escaped += "\\r",
+            '\t' => escaped += "\\t",
+            c if (c as u32) < 0x20 => escaped += format!("\\u{:04x}", c as u32).as_str(),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
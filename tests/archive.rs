@@ -0,0 +1,91 @@
+use std::fs;
+
+use toydump::archive::{ArchiveFormat, is_archive};
+use toydump::output::MemorySink;
+use toydump::{Archive, ElfHeader, Error};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+const ARCHIVE_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/archive");
+
+#[test]
+fn archives_list_members_and_index_their_symbols() {
+    let names = ["a.o", "a_module_with_a_long_name.o", "b.o", "notes.txt"];
+
+    for (file, format) in [
+        ("gnu.a", ArchiveFormat::Gnu),
+        ("bsd.a", ArchiveFormat::Bsd),
+        ("darwin.a", ArchiveFormat::Bsd),
+    ] {
+        let bytes = fs::read(format!("{ARCHIVE_CORPUS}/{file}")).unwrap();
+        assert!(is_archive(&bytes));
+
+        let archive = Archive::new(&bytes).unwrap();
+        assert_eq!(archive.format(), format);
+        assert!(archive.diagnostics().is_empty());
+
+        let members = archive
+            .members()
+            .iter()
+            .map(|member| member.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(members, names);
+
+        for (symbol, member) in [
+            ("add", "a.o"),
+            ("sub", "a.o"),
+            ("mul", "a_module_with_a_long_name.o"),
+            ("helper", "b.o"),
+        ] {
+            let entry = archive
+                .symbols()
+                .iter()
+                .find(|entry| entry.name == symbol)
+                .unwrap();
+            assert_eq!(archive.member_at(entry.member_offset).unwrap().name, member);
+        }
+
+        // members are objects in their own right, whatever the archive's flavour
+        let b = archive.member_by_name("b.o").unwrap();
+        let elf = ElfHeader::new(archive.member_data(b)).unwrap();
+        let helper = elf.disassemble_function("helper").unwrap();
+        assert_eq!(helper.instructions.len(), 2);
+
+        let notes = archive.member_by_name("notes.txt").unwrap();
+        assert_eq!(
+            ElfHeader::new(archive.member_data(notes))
+                .err()
+                .unwrap()
+                .exit_code(),
+            Error::BadMagic.exit_code()
+        );
+    }
+
+    let bytes = fs::read(format!("{ARCHIVE_CORPUS}/gnu.a")).unwrap();
+    let archive = Archive::new(&bytes).unwrap();
+
+    assert_eq!(
+        archive.member_lines()[0],
+        "rw-r--r-- 0/0   1160 Jan  1 00:00 1970 a.o"
+    );
+    assert_eq!(
+        archive.index_lines("libgnu.a")[0],
+        "Index of archive libgnu.a: (4 entries, 0x14 bytes in the symbol table)"
+    );
+
+    for end in 0..bytes.len() {
+        if let Ok(archive) = Archive::new(&bytes[..end]) {
+            archive.dump_members(&mut MemorySink::new(false));
+            archive.dump_index("libgnu.a", &mut MemorySink::new(false));
+        }
+    }
+
+    // a cut short member is reported, keeping the members before it
+    let archive = Archive::new(&bytes[..0x600]).unwrap();
+    assert_eq!(archive.members().len(), 1);
+    assert_eq!(archive.diagnostics()[0].table, "archive member");
+    assert_eq!(archive.diagnostics()[0].error.exit_code(), 4);
+
+    let object = fs::read(format!("{ELF_CORPUS}/llvm_mc_object.o")).unwrap();
+    assert!(!is_archive(&object));
+    assert_eq!(Archive::new(&object).err().unwrap().exit_code(), 18);
+}
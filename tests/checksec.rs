@@ -0,0 +1,102 @@
+use std::fs;
+
+use toydump::ElfHeader;
+use toydump::disassemble::{PT_INTERP, Pie, Relro};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn security_report_reads_segments_dynamic_and_notes() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/dynamic.elf")).unwrap();
+    let report = ElfHeader::new(&bytes).unwrap().security_report();
+
+    // DT_FLAGS_1 carries DF_1_PIE
+    assert_eq!(report.pie, Pie::Enabled);
+    assert_eq!(report.relro, Relro::None);
+    assert!(!report.nx);
+    assert!(report.stripped);
+    assert_eq!(report.runpath.as_deref(), Some("$ORIGIN/../lib"));
+    assert_eq!(report.bti, Some(false));
+
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_notes.o")).unwrap();
+    let report = ElfHeader::new(&bytes).unwrap().security_report();
+
+    assert_eq!(report.pie, Pie::Relocatable);
+    assert!(!report.stripped);
+    assert_eq!(report.bti, Some(true));
+    assert_eq!(report.pac, Some(true));
+}
+
+#[test]
+fn position_independent_executables_are_told_apart_by_df_1_pie() {
+    // a shared object with an .interp, as libc.so.6 has, but no DF_1_PIE
+    let bytes = fs::read(format!("{ELF_CORPUS}/interp_lib.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(
+        elf.segments()
+            .iter()
+            .any(|segment| segment.segment_type == PT_INTERP)
+    );
+    assert!(!elf.is_position_independent_executable());
+    assert_eq!(elf.file_type_name(), "DYN (Shared object file)");
+
+    let report = elf.security_report();
+    assert_eq!(report.pie, Pie::SharedObject);
+    assert!(
+        elf.checksec_lines()
+            .contains(&String::from("PIE:      DSO"))
+    );
+
+    let bytes = fs::read(format!("{ELF_CORPUS}/versioned_main.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.is_position_independent_executable());
+    assert_eq!(
+        elf.file_type_name(),
+        "DYN (Position-Independent Executable file)"
+    );
+    assert_eq!(elf.security_report().pie, Pie::Enabled);
+}
+
+#[test]
+fn stack_canaries_are_found_by_their_symbols() {
+    // gcc -fstack-protector-all imports __stack_chk_fail
+    let bytes = fs::read(format!("{ELF_CORPUS}/gcc_canary.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.security_report().canary);
+    assert!(
+        elf.checksec_lines()
+            .contains(&String::from("Stack:    Canary found"))
+    );
+
+    let bytes = fs::read(format!("{ELF_CORPUS}/gcc_fortify.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(!elf.security_report().canary);
+    assert!(
+        elf.checksec_lines()
+            .contains(&String::from("Stack:    No canary found"))
+    );
+}
+
+#[test]
+fn fortified_calls_are_listed_without_the_canary() {
+    // gcc -D_FORTIFY_SOURCE=2 turns a memcpy into a fixed size buffer into __memcpy_chk
+    let bytes = fs::read(format!("{ELF_CORPUS}/gcc_fortify.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert_eq!(elf.security_report().fortified, ["__memcpy_chk"]);
+    assert!(
+        elf.checksec_lines()
+            .contains(&String::from("FORTIFY:  Yes (1 fortified: __memcpy_chk)"))
+    );
+
+    // __stack_chk_fail ends in _chk too but is the canary, not a fortified call
+    let bytes = fs::read(format!("{ELF_CORPUS}/gcc_canary.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.security_report().fortified.is_empty());
+    assert!(elf.checksec_lines().contains(&String::from("FORTIFY:  No")));
+}
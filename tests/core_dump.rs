@@ -0,0 +1,79 @@
+use std::fs;
+
+use toydump::ElfHeader;
+use toydump::disassemble::{AT_EXECFN, AT_PAGESZ, AT_PHDR};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn core_dumps_decode_process_notes_and_memory() {
+    // a kernel written x86-64 core of a NULL+0x10 dereference, cut short by RLIMIT_CORE after the
+    // notes, so the memory segments point past the end of the file
+    let bytes = fs::read(format!("{ELF_CORPUS}/linux_x86_64_core.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    let info = elf.process_info().unwrap();
+    assert_eq!(info.name, "crash");
+    assert_eq!(info.arguments.trim_end(), "./crash arg1 arg2");
+
+    let status = &elf.process_statuses()[0];
+    assert_eq!(status.signal, 11);
+    assert_eq!(status.pid, info.pid);
+    assert_eq!(status.register("rax"), Some(0x10));
+
+    let files = elf.mapped_files().unwrap();
+    assert_eq!(files.page_size, 4096);
+
+    let (file, offset) = files.file_at(status.register("rip").unwrap()).unwrap();
+    assert_eq!((file.path.as_str(), offset), ("/tmp/core/crash", 0x113D));
+
+    let auxv = elf.auxiliary_vector();
+    let phdr = auxv
+        .iter()
+        .find(|entry| entry.key == AT_PHDR)
+        .unwrap()
+        .value;
+    assert!(
+        auxv.iter()
+            .any(|entry| entry.key == AT_PAGESZ && entry.value == 4096)
+    );
+    assert!(elf.memory_at(phdr).is_none());
+    assert_eq!(elf.file_range_at_vaddr(phdr).unwrap_err().exit_code(), 4);
+
+    assert!(
+        elf.note_lines()
+            .iter()
+            .any(|line| line.ends_with("NT_FILE (mapped files)\t    Page size: 4096"))
+    );
+
+    // the generated aarch64 core, whose stack page holds the AT_EXECFN string
+    let bytes = fs::read(format!("{ELF_CORPUS}/aarch64_core.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    let status = &elf.process_statuses()[0];
+    assert_eq!(status.registers.len(), 34);
+    assert_eq!(status.register("x30"), Some(30));
+    assert_eq!(status.register("pc"), Some(0x400010));
+
+    let execfn = elf
+        .auxiliary_vector()
+        .into_iter()
+        .find(|entry| entry.key == AT_EXECFN)
+        .unwrap();
+    assert!(
+        elf.memory_at(execfn.value)
+            .unwrap()
+            .starts_with(b"/bin/crashy\0")
+    );
+
+    let lines = elf.core_lines();
+    assert!(lines.contains(&String::from(
+        "  Stopped at 0x400010 in /bin/crashy at offset 0x10"
+    )));
+    assert!(lines.contains(&String::from(
+        "  AT_EXECFN:            0x7fffff00 (/bin/crashy)"
+    )));
+
+    // the text was mapped but not dumped, so it has no bytes to show
+    assert!(elf.memory_at(0x400000).is_none());
+}
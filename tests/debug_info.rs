@@ -0,0 +1,174 @@
+use std::fs;
+
+use toydump::disassemble::{ELFCOMPRESS_ZLIB, ELFCOMPRESS_ZSTD};
+use toydump::{ElfHeader, Error};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn line_tables_map_addresses_to_source_lines() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_debug_line.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.diagnostics().is_empty());
+    assert_eq!(elf.line_programs()[0].version, 5);
+
+    let location = elf.source_location(0xC).unwrap();
+    assert_eq!(location.path, "/tmp/d/l.s");
+    assert_eq!(location.line, 11);
+    assert!(elf.source_location(0x14).is_none());
+
+    let lines = elf.disassembly_lines(&elf.disassemble().unwrap()[0], false, true);
+    let helper = lines
+        .iter()
+        .position(|line| line.ends_with("<helper>:"))
+        .unwrap();
+    assert_eq!(lines[helper + 1], "/tmp/d/l.s:11");
+
+    // DWARF 4 from rustc, with relocated .debug_line in a section per function
+    let bytes = fs::read(format!("{ELF_CORPUS}/rustc_debug_info.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+    let program = &elf.line_programs()[0];
+
+    // the compilation directory comes from the unit in .debug_info
+    assert_eq!(program.version, 4);
+    assert_eq!(program.file_path(1).as_deref(), Some("/tmp/d/nc.rs"));
+    assert_eq!(elf.source_location(0x10).unwrap().line, 24);
+
+    // DWARF 5 from gcc, whose file names are .debug_line_str offsets filled in by relocations
+    let bytes = fs::read(format!("{ELF_CORPUS}/gcc_debug_x86_64.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+    let program = &elf.line_programs()[0];
+
+    assert_eq!(program.file_path(1).as_deref(), Some("/tmp/d/c.c"));
+    assert_eq!(
+        program.file_path(2).as_deref(),
+        Some("/usr/include/stdio.h")
+    );
+    assert_eq!(program.rows.len(), 23);
+}
+
+#[test]
+fn debug_info_describes_functions_and_struct_layouts() {
+    // DWARF 4 from rustc and DWARF 5 from rustc, whose names and addresses go through
+    // .debug_str_offsets and .debug_addr
+    for name in ["rustc_debug_info.o", "rustc_debug_info_v5.o"] {
+        let bytes = fs::read(format!("{ELF_CORPUS}/{name}")).unwrap();
+        let elf = ElfHeader::new(&bytes).unwrap();
+
+        assert!(elf.diagnostics().is_empty(), "{name}");
+
+        let functions = elf.debug_functions();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(
+            functions[0].signature(),
+            "nc::first_value(item: *const nc::MyStruct, bias: u32) -> u32"
+        );
+        assert_eq!((functions[0].low_pc, functions[0].high_pc), (0, 0x1C));
+
+        let layout = elf.struct_layout("MyStruct").unwrap();
+        assert_eq!(elf.struct_layout("nc::MyStruct"), Some(layout.clone()));
+        assert_eq!(layout.size, Some(16));
+
+        let members: Vec<_> = layout
+            .members
+            .iter()
+            .map(|member| (member.name.as_str(), member.offset, member.size))
+            .collect();
+        assert_eq!(
+            members,
+            [
+                ("tag", 0, Some(1)),
+                ("value", 4, Some(4)),
+                ("next", 8, Some(8))
+            ]
+        );
+
+        let lines = elf.struct_layout_lines("MyStruct").unwrap();
+        assert!(lines.contains(&String::from("  0x0001     3  <padding>")));
+    }
+
+    // DWARF 5 from gcc; helper is only ever inlined, so it has no address range of its own
+    let bytes = fs::read(format!("{ELF_CORPUS}/gcc_debug_x86_64.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert_eq!(
+        elf.debug_functions()[0].signature(),
+        "main(argc: int, argv: char **) -> int"
+    );
+
+    let layout = elf.struct_layout("Point").unwrap();
+    assert_eq!(layout.declared_at.as_deref(), Some("/tmp/d/c.c:2"));
+    assert_eq!(layout.members[2].type_name, "char[8]");
+    assert_eq!(layout.members[2].offset, 16);
+
+    assert!(matches!(
+        elf.struct_layout_lines("Missing"),
+        Err(Error::TypeNotFound(_))
+    ));
+}
+
+#[test]
+fn compressed_debug_sections_decompress_transparently() {
+    let mut line_rows = Vec::new();
+
+    // SHF_COMPRESSED with zlib and zstd, and the legacy .zdebug_* sections
+    for (name, compression_type) in [
+        ("gcc_debug_zlib.o", Some(ELFCOMPRESS_ZLIB)),
+        ("gcc_debug_zstd.o", Some(ELFCOMPRESS_ZSTD)),
+        ("gcc_debug_zdebug.o", Option::None),
+    ] {
+        let bytes = fs::read(format!("{ELF_CORPUS}/{name}")).unwrap();
+        let elf = ElfHeader::new(&bytes).unwrap();
+
+        assert!(elf.diagnostics().is_empty(), "{name}");
+
+        let section = elf
+            .section_by_name(".debug_info")
+            .or_else(|| elf.section_by_name(".zdebug_info"))
+            .unwrap();
+        assert!(elf.is_compressed(section));
+        assert_eq!(
+            elf.compression_header(section)
+                .map(|header| header.compression_type),
+            compression_type
+        );
+
+        let contents = elf.decompressed_data(section).unwrap();
+        assert_ne!(Some(contents), elf.section_data(section));
+        assert_eq!(&contents[4..6], &[5, 0]);
+
+        let layout = elf.struct_layout("Point").unwrap();
+        assert_eq!(layout.size, Some(24));
+        assert_eq!(layout.declared_at.as_deref(), Some("/tmp/d/c.c:2"));
+        assert_eq!(elf.debug_functions()[0].name, "main");
+
+        line_rows.push(elf.line_programs()[0].rows.clone());
+    }
+
+    assert!(!line_rows[0].is_empty());
+    assert!(line_rows.iter().all(|rows| *rows == line_rows[0]));
+
+    // zstd is not held to zlib's ratio: 62 bytes of RLE blocks hold 1 MiB of zeros
+    let mut bytes = fs::read(format!("{ELF_CORPUS}/zstd_zeros.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+    let section = elf.section_by_name(".debug_zeros").unwrap();
+
+    assert!(elf.diagnostics().is_empty());
+    assert_eq!(section.size, 62);
+    let contents = elf.section_contents(section).unwrap();
+    assert_eq!(contents.len(), 1 << 20);
+    assert!(contents.iter().all(|byte| *byte == 0));
+
+    // but a claimed size beyond any real section is refused before decompressing, which only
+    // happens once the section is used
+    bytes[0x48..0x50].copy_from_slice(&(1u64 << 40).to_le_bytes());
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.diagnostics().is_empty());
+    assert!(
+        elf.section_contents(elf.section_by_name(".debug_zeros").unwrap())
+            .is_none()
+    );
+    assert_eq!(elf.diagnostics()[0].table, "compressed section");
+}
@@ -0,0 +1,164 @@
+use std::fs;
+
+use toydump::disassemble::{EM_ARM, EM_X86_64, machine_name, os_abi_name};
+use toydump::{ElfHeader, Session};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn valid_corpus_file_disassembles_text() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/aarch64_le.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.diagnostics().is_empty());
+
+    let sections = elf.disassemble().unwrap();
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].name, ".text");
+    assert_eq!(sections[0].instructions[0].text, "add x0, x1, #4");
+}
+
+#[test]
+fn elf32_corpus_file_uses_32_bit_layout() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/aarch64_elf32.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.diagnostics().is_empty());
+    assert_eq!(elf.sections().len(), 3);
+    assert_eq!(elf.sections()[1].offset, 0x34);
+
+    let sections = elf.disassemble().unwrap();
+    assert_eq!(sections[0].instructions.len(), 5);
+}
+
+#[test]
+fn function_disassembly_stops_at_next_label() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_object.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    let main = elf.disassemble_function("main").unwrap();
+    assert_eq!(main.instructions.len(), 5);
+
+    let helper = elf.disassemble_function("helper").unwrap();
+    assert_eq!(helper.instructions.len(), 2);
+    assert_eq!(helper.address_of(&helper.instructions[0]), 0x14);
+
+    let lines = elf.disassembly_lines(&elf.disassemble().unwrap()[0], false, false);
+    assert!(lines.contains(&String::from("0000000000000000 <main>:")));
+    assert!(lines.contains(&String::from("0000000000000014 <helper>:")));
+
+    assert!(elf.disassemble_function("missing").is_err());
+}
+
+#[test]
+fn every_executable_section_is_disassembled() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_sections.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    let names = elf
+        .disassemble()
+        .unwrap()
+        .into_iter()
+        .map(|code| code.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, [".text", ".init", ".mycode"]);
+
+    let data = [String::from(".data")];
+    assert!(elf.disassemble_sections(&data, false).is_err());
+
+    let forced = elf.disassemble_sections(&data, true).unwrap();
+    assert_eq!(forced[0].instructions[0].word, 0xD65F03C0);
+}
+
+#[test]
+fn any_machine_and_os_abi_parse_but_only_known_machines_disassemble() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/unknown_machine.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert_eq!(elf.sections().len(), 3);
+    assert!(!elf.can_disassemble());
+    assert!(elf.disassemble().is_err());
+    assert!(elf.header_lines().contains(&String::from(
+        "  Machine:                           Advanced Micro Devices X86-64"
+    )));
+
+    let bytes = fs::read(format!("{ELF_CORPUS}/freebsd_abi.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.can_disassemble());
+    assert!(elf.header_lines().contains(&String::from(
+        "  OS/ABI:                            UNIX - FreeBSD"
+    )));
+
+    assert_eq!(machine_name(0x28), "ARM");
+    assert_eq!(machine_name(0xF3), "RISC-V");
+    assert_eq!(machine_name(0x1234), "<unknown>: 0x1234");
+    assert_eq!(os_abi_name(97, EM_ARM), "ARM");
+    assert_eq!(os_abi_name(97, EM_X86_64), "<unknown: 61>");
+}
+
+#[test]
+fn big_endian_files_parse_and_aarch64_be_code_decodes() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_aarch64_be.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.diagnostics().is_empty());
+    assert_eq!(elf.symbol_by_name("helper").unwrap().value, 0x14);
+    assert_eq!(elf.relocations().len(), 3);
+    assert_eq!(elf.build_id().as_deref(), Some("0102030405060708"));
+
+    let main = elf.disassemble_function("main").unwrap();
+    assert_eq!(main.instructions[0].text, "add x0, x1, #4");
+
+    let generated = fs::read(format!("{ELF_CORPUS}/aarch64_be.elf")).unwrap();
+    let elf = ElfHeader::new(&generated).unwrap();
+    assert_eq!(
+        elf.disassemble().unwrap()[0].instructions[0].text,
+        "add x0, x1, #4"
+    );
+
+    for (name, value_size) in [("llvm_mc_ppc.o", 4), ("llvm_mc_ppc64.o", 8)] {
+        let bytes = fs::read(format!("{ELF_CORPUS}/{name}")).unwrap();
+        let elf = ElfHeader::new(&bytes).unwrap();
+
+        assert!(elf.diagnostics().is_empty());
+        assert_eq!(elf.section_by_name(".text").unwrap().size, 0x1C);
+        assert_eq!(elf.symbol_by_name("helper").unwrap().value, 0x14);
+        assert_eq!(elf.symbol_by_name("value").unwrap().size, 0);
+        assert_eq!(elf.relocations().len(), 3);
+        assert_eq!(elf.section_by_name(".data").unwrap().size, value_size);
+        assert!(!elf.can_disassemble());
+    }
+
+    let bytes = fs::read(format!("{ELF_CORPUS}/invalid_data_encoding.elf")).unwrap();
+    assert_eq!(ElfHeader::new(&bytes).err().unwrap().exit_code(), 7);
+}
+
+#[test]
+fn virtual_addresses_translate_through_load_segments() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/executable.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert_eq!(elf.vaddr_to_offset(0x400080), Some(0x80));
+    assert_eq!(elf.offset_to_vaddr(0x8C), Some(0x40008C));
+    assert_eq!(elf.vaddr_to_offset(0x500000), Option::None);
+    assert_eq!(
+        elf.section_containing_vaddr(0x40008E).unwrap().name,
+        ".rodata"
+    );
+    assert_eq!(elf.file_range_at_vaddr(0x40008E).unwrap(), (0x8E, 5));
+    assert!(elf.file_range_at_vaddr(0x500000).is_err());
+
+    let code = elf.disassemble_at(0x400080, false).unwrap();
+    assert_eq!(code.address_of(&code.instructions[0]), 0x400080);
+    assert_eq!(code.instructions.len(), 3);
+    assert!(elf.disassemble_at(0x40008C, false).is_err());
+
+    let session = Session::new(bytes, 16, 2, false);
+    let hits = session.occurrences(b"needle");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].location(), "0x8c in .rodata at vaddr 0x40008c");
+
+    let lines = session.hex_lines_at(0x8C, 7, 0x40008C).collect::<Vec<_>>();
+    assert!(lines[0].starts_with("0040008C 6E65"));
+}
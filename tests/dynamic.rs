@@ -0,0 +1,37 @@
+use std::fs;
+
+use toydump::ElfHeader;
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn dynamic_section_reports_dependencies() {
+    for file in ["dynamic.elf", "dynamic_elf32.elf"] {
+        let bytes = fs::read(format!("{ELF_CORPUS}/{file}")).unwrap();
+        let elf = ElfHeader::new(&bytes).unwrap();
+
+        assert!(elf.diagnostics().is_empty());
+        assert_eq!(elf.dynamic_entries().len(), 7);
+        assert_eq!(elf.needed_libraries(), ["libc.so.6", "libm.so.6"]);
+        assert_eq!(elf.soname().as_deref(), Some("libdemo.so.1"));
+        assert_eq!(elf.runpath().as_deref(), Some("$ORIGIN/../lib"));
+        assert_eq!(elf.rpath(), Option::None);
+        assert!(elf.is_bind_now());
+    }
+
+    // with the section headers stripped, PT_DYNAMIC finds the table and DT_STRTAB its strings
+    let mut bytes = fs::read(format!("{ELF_CORPUS}/interp_lib.elf")).unwrap();
+    bytes[0x28..0x30].fill(0);
+    bytes[0x3C..0x40].fill(0);
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.sections().is_empty());
+    assert_eq!(elf.needed_libraries(), ["libc.so.6"]);
+    assert_eq!(elf.soname().as_deref(), Some("libinterp.so.1"));
+    assert_eq!(elf.runpath().as_deref(), Some("$ORIGIN"));
+    assert!(
+        elf.dynamic_lines()
+            .iter()
+            .all(|line| !line.contains("<corrupt"))
+    );
+}
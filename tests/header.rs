@@ -0,0 +1,94 @@
+use std::fs;
+
+use toydump::ElfHeader;
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn header_lines_match_readelf_and_warn_about_inconsistencies() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/executable.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    let lines = elf.header_lines();
+    assert_eq!(lines[0], "ELF Header:");
+    assert_eq!(
+        lines[1],
+        "  Magic:   7f 45 4c 46 02 01 01 00 00 00 00 00 00 00 00 00"
+    );
+    assert!(lines.contains(&String::from(
+        "  Type:                              EXEC (Executable file)"
+    )));
+    assert!(lines.contains(&String::from(
+        "  Entry point address:               0x400078"
+    )));
+    assert_eq!(
+        lines.last().unwrap(),
+        "  Section header string table index: 3"
+    );
+    assert!(elf.header_warnings().is_empty());
+
+    let warnings = |file: &str| {
+        let bytes = fs::read(format!("{ELF_CORPUS}/{file}")).unwrap();
+        ElfHeader::new(&bytes).unwrap().header_warnings()
+    };
+
+    assert_eq!(
+        warnings("truncated_section_table.elf"),
+        ["section header table ends at 0x128, past the end of the file (0x108)"]
+    );
+    assert_eq!(
+        warnings("shstrndx_out_of_range.elf"),
+        ["e_shstrndx 9 is not below the section header count 3"]
+    );
+    assert_eq!(
+        warnings("dynamic.elf"),
+        ["executable or shared object has no program headers"]
+    );
+
+    // each patch of executable.elf breaks one header field
+    let patched = |offset: usize, value: &[u8]| {
+        let mut bytes = bytes.clone();
+        bytes[offset..offset + value.len()].copy_from_slice(value);
+        ElfHeader::new(&bytes).unwrap().header_warnings()
+    };
+
+    for (offset, value, warning) in [
+        (
+            6,
+            &[2][..],
+            "EI_VERSION is 2 but only 1 (current) is defined",
+        ),
+        (
+            0x14,
+            &2u32.to_le_bytes(),
+            "e_version is 0x2 but only 0x1 (current) is defined",
+        ),
+        (12, &[1], "EI_PAD bytes are not zero"),
+        (
+            0x34,
+            &0x50u16.to_le_bytes(),
+            "e_ehsize is 80 but an ELF64 header is 64 bytes",
+        ),
+        (
+            0x36,
+            &0x40u16.to_le_bytes(),
+            "e_phentsize is 64 but an ELF64 program header is 56 bytes",
+        ),
+        (
+            0x20,
+            &0x1000u64.to_le_bytes(),
+            "program header table ends at 0x1038, past the end of the file (0x1b0)",
+        ),
+        (
+            0x3A,
+            &0x48u16.to_le_bytes(),
+            "e_shentsize is 72 but an ELF64 section header is 64 bytes",
+        ),
+        (0x18, &0u64.to_le_bytes(), "executable has no entry point"),
+    ] {
+        assert!(
+            patched(offset, value).contains(&String::from(warning)),
+            "{warning}"
+        );
+    }
+}
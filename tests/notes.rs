@@ -0,0 +1,28 @@
+use std::fs;
+
+use toydump::ElfHeader;
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn notes_decode_build_id_properties_and_package() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_notes.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert!(elf.diagnostics().is_empty());
+    assert_eq!(elf.note_tables().len(), 4);
+    assert_eq!(elf.build_id().as_deref(), Some("deadbeef01234567"));
+    assert_eq!(elf.aarch64_feature_1(), Some(0x3));
+    assert_eq!(elf.go_build_id().as_deref(), Some("abc/def"));
+    assert_eq!(
+        elf.package_metadata().as_deref(),
+        Some(r#"{"type":"deb","name":"demo"}"#)
+    );
+
+    let lines = elf.note_lines();
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.ends_with("Properties: AArch64 feature: BTI, PAC"))
+    );
+}
@@ -0,0 +1,34 @@
+use toydump::output::{JsonSink, OutputSink};
+
+#[test]
+fn json_sink_escapes_quotes_backslashes_and_control_characters() {
+    let awkward = "say \"hi\"\\path\ttab\nline\r\u{1}\u{1f} déjà";
+    let mut json = Vec::new();
+
+    let mut sink = JsonSink::new(&mut json);
+    sink.write_line("before any section");
+    sink.begin_section("quote\"section");
+    sink.write_line(awkward);
+    sink.write_line("");
+    sink.begin_section("empty");
+    sink.finish();
+
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.contains("\\u0001") && json.contains("\\u001f"));
+
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!([
+            {"section": "output", "lines": ["before any section"]},
+            {"section": "quote\"section", "lines": [awkward, ""]},
+            {"section": "empty", "lines": []},
+        ])
+    );
+
+    // nothing written still makes an empty array
+    let mut json = Vec::new();
+    JsonSink::new(&mut json).finish();
+    let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(value, serde_json::json!([]));
+}
@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use proptest::prelude::*;
+use toydump::disassemble::{DW_TAG_CLASS_TYPE, DW_TAG_STRUCTURE_TYPE, DW_TAG_UNION_TYPE};
+use toydump::output::MemorySink;
+use toydump::{ElfHeader, Instruction, Session, decode_aarch64_instruction, disassemble_aarch64};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+/// Every file in the ELF corpus, read once and sorted by name.
+static CORPUS: LazyLock<Vec<Vec<u8>>> = LazyLock::new(|| {
+    let mut paths = fs::read_dir(Path::new(ELF_CORPUS))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+
+    paths.sort();
+    paths.iter().map(|path| fs::read(path).unwrap()).collect()
+});
+
+/// Runs every dump on `bytes`, as the `elf` fuzz target does, so any panic in a parser shows up.
+fn parse_and_dump(bytes: &[u8]) {
+    let Ok(elf) = ElfHeader::new(bytes) else {
+        return;
+    };
+
+    let session = Session::new(bytes.to_vec(), 16, 2, false);
+    let mut sink = MemorySink::new(false);
+
+    elf.dump_header(&mut sink);
+    elf.dump_sections(&mut sink);
+    elf.dump_segments(&mut sink);
+    elf.dump_symbols(true, &mut sink);
+    elf.dump_dynamic(&mut sink);
+    elf.dump_relocations(true, &mut sink);
+    elf.dump_notes(&mut sink);
+    elf.dump_checksec(&mut sink);
+    elf.dump_debug_info(&mut sink);
+    elf.dump_versions(&mut sink);
+    elf.dump_histogram(&mut sink);
+    elf.dump_core(&mut sink);
+
+    if elf.can_disassemble() {
+        elf.dump_disassembly(true, true, &mut sink).unwrap();
+    }
+
+    let names = elf
+        .sections()
+        .iter()
+        .map(|section| section.name.clone())
+        .collect::<Vec<_>>();
+
+    for name in &names {
+        session.dump_section(&elf, name, &mut sink).unwrap();
+    }
+
+    let _ = elf.dump_section_disassembly(&names, true, true, true, &mut sink);
+
+    for symbol in elf.symbols().iter().filter(|symbol| symbol.is_function()) {
+        let _ = elf.dump_function_disassembly(&symbol.name, true, true, &mut sink);
+    }
+
+    for unit in elf.compile_units() {
+        for entry in &unit.entries {
+            if let (DW_TAG_STRUCTURE_TYPE | DW_TAG_CLASS_TYPE | DW_TAG_UNION_TYPE, Some(name)) =
+                (entry.tag, entry.name())
+            {
+                let _ = elf.dump_struct_layout(name, &mut sink);
+            }
+        }
+    }
+
+    let entry = elf.file_header().entry;
+    let _ = elf.file_range_at_vaddr(entry);
+    let _ = elf.dump_disassembly_at(entry, true, true, true, &mut sink);
+
+    for diagnostic in elf.diagnostics() {
+        assert!(!diagnostic.to_string().is_empty());
+    }
+}
+
+fn word_from_line(line: &str) -> u32 {
+    let hex = line
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("0x"))
+        .unwrap();
+
+    u32::from_str_radix(hex, 16).unwrap()
+}
+
+#[test]
+fn corpus_parses_without_panicking() {
+    for bytes in CORPUS.iter() {
+        for len in 0..=bytes.len() {
+            parse_and_dump(&bytes[..len]);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn every_word_decodes(word in any::<u32>()) {
+        let text = decode_aarch64_instruction(word);
+
+        prop_assert!(!text.is_empty());
+        prop_assert!(text.is_ascii());
+        prop_assert_eq!(text.clone(), decode_aarch64_instruction(word));
+    }
+
+    #[test]
+    fn formatted_instruction_round_trips(word in any::<u32>(), index in 0usize..1 << 20) {
+        let instruction = Instruction {
+            offset: index * 4,
+            word,
+            text: decode_aarch64_instruction(word),
+        };
+
+        let line = instruction.to_string();
+
+        prop_assert_eq!(word_from_line(&line), word);
+        prop_assert!(line.ends_with(instruction.text.as_str()));
+    }
+
+    #[test]
+    fn gnu_hash_chains_survive_any_symbol_offset(
+        symbol_offset in u32::MAX - 16..=u32::MAX,
+        bucket_distance in 0u32..16,
+    ) {
+        // point every bucket of versioned_lib.elf's .gnu.hash near the top of the index range
+        let mut bytes = fs::read(format!("{ELF_CORPUS}/versioned_lib.elf")).unwrap();
+        let bucket = symbol_offset.saturating_add(bucket_distance);
+
+        bytes[0x29C..0x2A0].copy_from_slice(&symbol_offset.to_le_bytes());
+
+        for position in (0x2B0..0x2BC).step_by(4) {
+            bytes[position..position + 4].copy_from_slice(&bucket.to_le_bytes());
+        }
+
+        let elf = ElfHeader::new(&bytes).unwrap();
+
+        prop_assert_eq!(elf.gnu_hash_table().unwrap().symbol_offset, symbol_offset);
+        prop_assert!(!elf.histogram_lines().is_empty());
+        prop_assert!(elf.hashed_symbol("api").is_none());
+    }
+
+    #[test]
+    fn disassembly_covers_every_whole_word(bytes in proptest::collection::vec(any::<u8>(), 0..256)) {
+        let instructions = disassemble_aarch64(&bytes);
+
+        prop_assert_eq!(instructions.len(), bytes.len() / 4);
+
+        for instruction in instructions {
+            let start = instruction.offset;
+            let word = u32::from_le_bytes(bytes[start..start + 4].try_into().unwrap());
+            prop_assert_eq!(instruction.word, word);
+        }
+    }
+
+    #[test]
+    fn arbitrary_bytes_never_panic(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+        parse_and_dump(&bytes);
+    }
+
+    #[test]
+    fn mutated_corpus_never_panics(
+        file in any::<prop::sample::Index>(),
+        mutations in proptest::collection::vec((any::<usize>(), any::<u8>()), 1..16),
+    ) {
+        let mut bytes = CORPUS[file.index(CORPUS.len())].clone();
+
+        if !bytes.is_empty() {
+            for (position, value) in mutations {
+                let len = bytes.len();
+                bytes[position % len] = value;
+            }
+        }
+
+        parse_and_dump(&bytes);
+    }
+
+    #[test]
+    fn hex_dump_and_search_never_panic(
+        bytes in proptest::collection::vec(any::<u8>(), 0..256),
+        needle in proptest::collection::vec(any::<u8>(), 0..8),
+        columns in 0usize..40,
+        groups in 0usize..8,
+    ) {
+        let session = Session::new(bytes, columns, groups, false);
+        let mut sink = MemorySink::new(false);
+
+        session.dump(&mut sink);
+        session.list_occurrences(&needle, &mut sink);
+    }
+
+    #[test]
+    fn occurrence_lines_span_the_rows_of_the_match(
+        columns in 1usize..40,
+        offset in 0usize..256,
+        length in 1usize..64,
+    ) {
+        let session = Session::new(vec![0; 512], columns, 2, false);
+        let rows = (offset + length - 1) / columns - offset / columns + 1;
+
+        prop_assert_eq!(session.occurrence_lines(offset, length).len(), rows);
+    }
+}
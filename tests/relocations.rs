@@ -0,0 +1,45 @@
+use std::fs;
+
+use toydump::ElfHeader;
+use toydump::disassemble::{EM_AARCH64, EM_X86_64, relocation_type_name};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn relocations_resolve_symbols_and_annotate_disassembly() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_object.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    let relocation = &elf.relocations()[0];
+    assert_eq!(elf.relocations().len(), 1);
+    assert_eq!(relocation.offset, 0x14);
+    assert_eq!(relocation.addend, Some(0));
+    assert_eq!(
+        relocation_type_name(EM_AARCH64, relocation.relocation_type),
+        "R_AARCH64_ADR_PREL_PG_HI21"
+    );
+    assert_eq!(elf.relocation_target(relocation, false), ".data");
+
+    let lines = elf.disassembly_lines(&elf.disassemble().unwrap()[0], false, false);
+    let adrp = lines.iter().position(|line| line.contains("adrp")).unwrap();
+    assert!(lines[adrp + 1].ends_with("; R_AARCH64_ADR_PREL_PG_HI21 .data"));
+
+    // a symbol before the target whose name is past the string table is dropped, which must not
+    // shift the symbols the relocations name
+    let mut corrupt = bytes.clone();
+    corrupt[0x60 + 2 * 0x18..][..4].copy_from_slice(&0xFFFFu32.to_le_bytes());
+    let elf = ElfHeader::new(&corrupt).unwrap();
+
+    assert!(elf.symbols().iter().all(|symbol| symbol.name != "helper"));
+    assert_eq!(elf.relocation_target(&elf.relocations()[0], false), ".data");
+}
+
+#[test]
+fn relocation_names_depend_on_machine() {
+    assert_eq!(
+        relocation_type_name(EM_AARCH64, 1026),
+        "R_AARCH64_JUMP_SLOT"
+    );
+    assert_eq!(relocation_type_name(EM_X86_64, 7), "R_X86_64_JUMP_SLOT");
+    assert_eq!(relocation_type_name(0x28, 7), "unrecognized: 7");
+}
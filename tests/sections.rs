@@ -0,0 +1,111 @@
+use std::fs;
+
+use toydump::output::MemorySink;
+use toydump::{ElfHeader, Session};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn extended_section_numbering_reads_section_zero() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/executable.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+    let count = elf.sections().len();
+
+    // move e_shnum into section 0's sh_size and e_shstrndx into its sh_link
+    let extended = |names_index: u32| {
+        let mut bytes = bytes.clone();
+        let first = u64::from_le_bytes(bytes[0x28..0x30].try_into().unwrap()) as usize;
+        bytes[0x3C..0x3E].copy_from_slice(&0u16.to_le_bytes());
+        bytes[0x3E..0x40].copy_from_slice(&0xFFFFu16.to_le_bytes());
+        bytes[first + 0x20..first + 0x28].copy_from_slice(&(count as u64).to_le_bytes());
+        bytes[first + 0x28..first + 0x2C].copy_from_slice(&names_index.to_le_bytes());
+        ElfHeader::new(&bytes).unwrap()
+    };
+
+    let resolved = extended(3);
+    assert_eq!(resolved.sections().len(), count);
+    assert!(resolved.section_by_name(".text").is_some());
+    assert!(resolved.header_warnings().is_empty());
+    assert!(resolved.diagnostics().is_empty());
+
+    let lines = resolved.header_lines();
+    assert!(lines.contains(&format!("  Number of section headers:         0 ({count})")));
+    assert_eq!(
+        lines.last().unwrap(),
+        "  Section header string table index: 65535 (3)"
+    );
+    assert!(resolved.section_lines()[0].starts_with(&format!("There are {count} section headers")));
+
+    assert_eq!(
+        extended(9).header_warnings(),
+        [format!(
+            "e_shstrndx 9 is not below the section header count {count}"
+        )]
+    );
+}
+
+#[test]
+fn section_listing_matches_readelf_and_sections_hex_dump() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_sections.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+    let lines = elf.section_lines();
+
+    assert_eq!(
+        lines[0],
+        "There are 7 section headers, starting at offset 0x188:"
+    );
+    assert!(lines.contains(&String::from(
+        "  [ 4] .mycode           PROGBITS        0000000000000000 00004c 000004 00  AX  0   0  1"
+    )));
+    assert!(lines.contains(&String::from(
+        "  [ 6] .symtab           SYMTAB          0000000000000000 000058 0000d8 18      1   8  8"
+    )));
+
+    // --hex-dump dumps a section's file bytes, labelled with their file offsets
+    let bytes = fs::read(format!("{ELF_CORPUS}/executable.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+    let session = Session::new(bytes.clone(), 16, 2, false);
+
+    let text = elf.section_by_name(".text").unwrap();
+    let lines = session
+        .hex_lines_in(text.offset as usize, text.size as usize)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "00000078 2010 0091 6204 0051 4104 40F9 0000 0090  ....b..QA.@.....",
+            "00000088 C003 5FD6  .._.",
+        ]
+    );
+
+    let rodata = elf.section_by_name(".rodata").unwrap();
+    let mut sink = MemorySink::new(false);
+    session.dump_range(rodata.offset as usize, rodata.size as usize, &mut sink);
+    assert!(
+        sink.contents()
+            .contains("0000008C 6E65 6564 6C65 00 needle.")
+    );
+}
+
+#[test]
+fn linked_executable_maps_sections_to_segments() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/versioned_main.elf")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+    let mapping = elf.section_to_segment_mapping();
+
+    assert_eq!(mapping.len(), elf.segments().len());
+    // PT_PHDR covers no section, PT_INTERP just .interp
+    assert!(mapping[0].is_empty());
+    assert_eq!(mapping[1], [".interp"]);
+    assert_eq!(mapping[3], [".init", ".plt", ".plt.got", ".text", ".fini"]);
+    // the writable PT_LOAD ends in .bss, which has no file bytes but is still mapped
+    assert_eq!(mapping[5].last(), Some(&".bss"));
+    assert_eq!(mapping[6], [".dynamic"]);
+
+    let lines = elf.segment_lines();
+    assert!(lines.contains(&String::from(" Section to Segment mapping:")));
+    assert!(lines.contains(&String::from("   01     .interp ")));
+    assert!(lines.contains(&String::from(
+        "   12     .init_array .fini_array .dynamic .got "
+    )));
+}
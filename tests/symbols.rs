@@ -0,0 +1,136 @@
+use std::fs;
+
+use toydump::demangle::demangle;
+use toydump::disassemble::{SHT_DYNSYM, compare_versions};
+use toydump::{ElfHeader, SymbolVersion};
+
+const ELF_CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/elf");
+
+#[test]
+fn object_file_symbols_resolve_names() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/llvm_mc_object.o")).unwrap();
+    let elf = ElfHeader::new(&bytes).unwrap();
+
+    assert_eq!(elf.symbols().len(), 7);
+
+    let main = elf.symbol_by_name("main").unwrap();
+    assert_eq!(main.section_index, 2);
+    assert_eq!(main.binding, toydump::disassemble::STB_GLOBAL);
+
+    let helper = elf.symbol_by_name("helper").unwrap();
+    assert_eq!(helper.value, 0x14);
+}
+
+#[test]
+fn rust_and_cpp_names_demangle() {
+    assert_eq!(
+        demangle("_ZN5space3fooEibc").as_deref(),
+        Some("space::foo(int, bool, char)")
+    );
+    assert_eq!(
+        demangle("_ZN4core3fmt5write17h0123456789abcdefE").as_deref(),
+        Some("core::fmt::write")
+    );
+    assert_eq!(
+        demangle("_RNvCs1234_7mycrate3foo").as_deref(),
+        Some("mycrate::foo")
+    );
+    assert_eq!(demangle("main"), Option::None);
+}
+
+#[test]
+fn symbol_versions_and_hash_tables() {
+    let bytes = fs::read(format!("{ELF_CORPUS}/versioned_main.elf")).unwrap();
+    let main = ElfHeader::new(&bytes).unwrap();
+    let bytes = fs::read(format!("{ELF_CORPUS}/versioned_lib.elf")).unwrap();
+    let library = ElfHeader::new(&bytes).unwrap();
+
+    assert!(main.diagnostics().is_empty());
+    assert!(library.diagnostics().is_empty());
+
+    // .symtab copies of the symbols carry no version, only the .dynsym ones
+    let version = |elf: &ElfHeader, name: &str| {
+        elf.symbols()
+            .iter()
+            .filter(|symbol| symbol.name == name)
+            .find_map(|symbol| elf.symbol_version(symbol))
+            .map(|version| format!("{name}{}", version.suffix()))
+    };
+
+    assert_eq!(
+        version(&main, "printf").as_deref(),
+        Some("printf@GLIBC_2.2.5")
+    );
+    let api = main
+        .symbols()
+        .iter()
+        .find(|symbol| symbol.name == "api")
+        .unwrap();
+    assert_eq!(
+        main.symbol_version(api),
+        Some(SymbolVersion::Needed {
+            name: String::from("VERS_2.0"),
+            index: 4
+        })
+    );
+    assert_eq!(
+        version(&library, "helper").as_deref(),
+        Some("helper@@VERS_1.0")
+    );
+    assert_eq!(version(&library, "unversioned"), Option::None);
+
+    // the old and new implementations of api are both exported, only the new one by default
+    let api = library
+        .symbols()
+        .iter()
+        .filter(|symbol| symbol.name == "api")
+        .filter_map(|symbol| library.symbol_version(symbol))
+        .map(|version| format!("api{}", version.suffix()))
+        .collect::<Vec<_>>();
+    assert_eq!(api, ["api@VERS_1.0", "api@@VERS_2.0"]);
+
+    let newest = main.newest_needed_versions();
+    let (file, version, symbols) = newest
+        .iter()
+        .find(|(file, ..)| file == "libc.so.6")
+        .unwrap();
+    assert_eq!(
+        (file.as_str(), version.as_str()),
+        ("libc.so.6", "GLIBC_2.34")
+    );
+    assert_eq!(symbols[0].name, "__libc_start_main");
+
+    assert_eq!(library.version_definitions()[0].name(), Some("libvers.so"));
+    assert_eq!(
+        compare_versions("GLIBC_2.2.5", "GLIBC_2.34"),
+        std::cmp::Ordering::Less
+    );
+
+    // both files carry .hash and .gnu.hash, which find every defined dynamic symbol bar hidden
+    // versions, so the lookup of api lands on api@@VERS_2.0
+    for elf in [&main, &library] {
+        assert!(elf.hash_table().is_some());
+        assert!(elf.gnu_hash_table().is_some());
+
+        let dynamic = elf
+            .sections()
+            .iter()
+            .find(|section| section.section_type == SHT_DYNSYM)
+            .unwrap();
+
+        for symbol in elf.symbols_in(dynamic.index) {
+            let hidden = matches!(elf.symbol_version(symbol), Some(SymbolVersion::Hidden(_)));
+
+            if symbol.is_defined() && !symbol.name.is_empty() && !hidden {
+                assert_eq!(
+                    elf.hashed_symbol(&symbol.name).map(|found| found.index),
+                    Some(symbol.index),
+                    "{}",
+                    symbol.name
+                );
+            }
+        }
+
+        assert!(elf.hashed_symbol("no_such_symbol").is_none());
+    }
+}